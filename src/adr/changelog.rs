@@ -0,0 +1,281 @@
+use super::locale::Vocabulary;
+use super::{git, model, Adr, Status};
+use chrono::{Date, Datelike, Utc};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Change {
+    Created,
+    Accepted,
+    Rejected,
+    Superseded,
+    Deprecated,
+}
+
+impl Change {
+    fn from_status(status: Status) -> Option<Change> {
+        match status {
+            Status::Proposed => None,
+            Status::Accepted => Some(Change::Accepted),
+            Status::Rejected => Some(Change::Rejected),
+            Status::Superseded => Some(Change::Superseded),
+            Status::Deprecated => Some(Change::Deprecated),
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::Created => write!(f, "Created"),
+            Change::Accepted => write!(f, "Accepted"),
+            Change::Rejected => write!(f, "Rejected"),
+            Change::Superseded => write!(f, "Superseded"),
+            Change::Deprecated => write!(f, "Deprecated"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Event<'a> {
+    pub date: Date<Utc>,
+    pub change: Change,
+    pub adr: &'a Adr,
+}
+
+pub enum Grouping {
+    Month,
+    Release(Vec<git::Tag>),
+}
+
+/// Builds the events for an ADR using only what is written in the record. The
+/// current status is assumed to have been reached on the date of the record.
+pub fn events_from_content(adr: &Adr) -> Vec<Event<'_>> {
+    let date = match adr.date {
+        None => return Vec::new(),
+        Some(x) => x,
+    };
+
+    let mut res = vec![Event {
+        date,
+        change: Change::Created,
+        adr,
+    }];
+
+    if let Some(change) = adr.status.and_then(Change::from_status) {
+        res.push(Event { date, change, adr });
+    }
+
+    res
+}
+
+/// Builds the events for an ADR from the git history of its file, dating each
+/// status transition by the commit that introduced it. Returns `None` when the
+/// file has no history.
+pub fn events_from_history<'a>(
+    repo: &Path,
+    adr: &'a Adr,
+    vocab: &Vocabulary,
+) -> Option<Vec<Event<'a>>> {
+    let revisions = git::file_history(repo, &adr.path);
+    let first = revisions.first()?;
+
+    let mut res = vec![Event {
+        date: adr.date.unwrap_or(first.date),
+        change: Change::Created,
+        adr,
+    }];
+
+    let mut previous = None;
+
+    for rev in revisions.iter() {
+        let status = git::show(repo, rev).and_then(|x| model::status_from_content(&x, vocab));
+        push_transition(&mut res, adr, previous, status, rev.date);
+        previous = status;
+    }
+
+    push_transition(&mut res, adr, previous, adr.status, Utc::today());
+
+    Some(res)
+}
+
+fn push_transition<'a>(
+    events: &mut Vec<Event<'a>>,
+    adr: &'a Adr,
+    previous: Option<Status>,
+    current: Option<Status>,
+    date: Date<Utc>,
+) {
+    if previous == current {
+        return;
+    }
+
+    if let Some(change) = current.and_then(Change::from_status) {
+        events.push(Event { date, change, adr });
+    }
+}
+
+pub fn in_window(event: &Event, since: Date<Utc>, until: Option<Date<Utc>>) -> bool {
+    event.date >= since && until.is_none_or(|x| event.date <= x)
+}
+
+/// Renders the events as Markdown, newest group first. Links to the records
+/// are made relative to `base`.
+pub fn render(events: &[Event], grouping: &Grouping, base: &Path) -> String {
+    let mut groups: BTreeMap<(Reverse<Date<Utc>>, String), Vec<&Event>> = BTreeMap::new();
+
+    for event in events.iter() {
+        groups
+            .entry(group_for(event, grouping))
+            .or_default()
+            .push(event);
+    }
+
+    let mut res = String::from("# Architecture Decision Changelog\n");
+
+    if groups.is_empty() {
+        res.push_str("\nNo architecture decisions changed in this period.\n");
+        return res;
+    }
+
+    for ((_, label), mut events) in groups.into_iter() {
+        events.sort_by(|a, b| {
+            (a.change, a.date, &a.adr.index).cmp(&(b.change, b.date, &b.adr.index))
+        });
+
+        res.push_str(&format!("\n## {}\n", label));
+
+        let mut current = None;
+
+        for event in events {
+            if current != Some(event.change) {
+                res.push_str(&format!("\n### {}\n\n", event.change));
+                current = Some(event.change);
+            }
+
+            let link = event.adr.path.strip_prefix(base).unwrap_or(&event.adr.path);
+
+            res.push_str(&format!(
+                "- [{}. {}]({}) ({})\n",
+                event.adr.index,
+                event.adr.title,
+                link.to_string_lossy(),
+                event.date.format("%Y-%m-%d"),
+            ));
+        }
+    }
+
+    res
+}
+
+fn group_for(event: &Event, grouping: &Grouping) -> (Reverse<Date<Utc>>, String) {
+    match grouping {
+        Grouping::Month => {
+            let start = event.date.with_day(1).unwrap_or(event.date);
+            (Reverse(start), start.format("%B %Y").to_string())
+        }
+        Grouping::Release(tags) => match tags.iter().find(|x| x.date >= event.date) {
+            Some(tag) => (Reverse(tag.date), tag.name.clone()),
+            None => (Reverse(Date::<Utc>::MAX_UTC), "Unreleased".to_owned()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn adr(index: &str, title: &str, date: Date<Utc>, status: Status) -> Adr {
+        Adr {
+            index: index.to_owned(),
+            title: title.to_owned(),
+            path: std::path::PathBuf::from(format!("/repo/doc/adr/{}-x.md", index)),
+            preamble: String::new(),
+            sections: Vec::new(),
+            date: Some(date),
+            status: Some(status),
+            original: None,
+        }
+    }
+
+    #[test]
+    fn test_events_from_content() {
+        let a = adr("00001", "Foo", Utc.ymd(2022, 9, 1), Status::Accepted);
+        let b = adr("00002", "Bar", Utc.ymd(2022, 9, 2), Status::Proposed);
+
+        let changes: Vec<Change> = events_from_content(&a).iter().map(|x| x.change).collect();
+        assert_eq!(vec![Change::Created, Change::Accepted], changes);
+
+        let changes: Vec<Change> = events_from_content(&b).iter().map(|x| x.change).collect();
+        assert_eq!(vec![Change::Created], changes);
+    }
+
+    #[test]
+    fn test_render_by_month() {
+        let a = adr("00001", "Foo", Utc.ymd(2022, 8, 30), Status::Accepted);
+        let b = adr("00002", "Bar", Utc.ymd(2022, 9, 2), Status::Proposed);
+
+        let mut events = events_from_content(&a);
+        events.append(&mut events_from_content(&b));
+
+        let expected = "# Architecture Decision Changelog\n\
+            \n## September 2022\n\
+            \n### Created\n\n\
+            - [00002. Bar](doc/adr/00002-x.md) (2022-09-02)\n\
+            \n## August 2022\n\
+            \n### Created\n\n\
+            - [00001. Foo](doc/adr/00001-x.md) (2022-08-30)\n\
+            \n### Accepted\n\n\
+            - [00001. Foo](doc/adr/00001-x.md) (2022-08-30)\n";
+
+        assert_eq!(
+            expected,
+            render(&events, &Grouping::Month, Path::new("/repo"))
+        );
+    }
+
+    #[test]
+    fn test_render_by_release() {
+        let a = adr("00001", "Foo", Utc.ymd(2022, 8, 30), Status::Proposed);
+        let b = adr("00002", "Bar", Utc.ymd(2022, 9, 2), Status::Proposed);
+
+        let mut events = events_from_content(&a);
+        events.append(&mut events_from_content(&b));
+
+        let tags = vec![git::Tag {
+            name: "v1.0.0".to_owned(),
+            date: Utc.ymd(2022, 9, 1),
+        }];
+
+        let expected = "# Architecture Decision Changelog\n\
+            \n## Unreleased\n\
+            \n### Created\n\n\
+            - [00002. Bar](doc/adr/00002-x.md) (2022-09-02)\n\
+            \n## v1.0.0\n\
+            \n### Created\n\n\
+            - [00001. Foo](doc/adr/00001-x.md) (2022-08-30)\n";
+
+        assert_eq!(
+            expected,
+            render(&events, &Grouping::Release(tags), Path::new("/repo"))
+        );
+    }
+
+    #[test]
+    fn test_in_window() {
+        let a = adr("00001", "Foo", Utc.ymd(2022, 8, 30), Status::Proposed);
+        let events = events_from_content(&a);
+
+        assert!(in_window(&events[0], Utc.ymd(2022, 8, 30), None));
+        assert!(!in_window(&events[0], Utc.ymd(2022, 8, 31), None));
+        assert!(!in_window(
+            &events[0],
+            Utc.ymd(2022, 8, 1),
+            Some(Utc.ymd(2022, 8, 29))
+        ));
+    }
+}
@@ -0,0 +1,214 @@
+use super::sections::Section;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Line {
+    Same(String),
+    Added(String),
+    Removed(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SectionChange {
+    Added(Section),
+    Removed(Section),
+    Changed { heading: String, lines: Vec<Line> },
+}
+
+impl SectionChange {
+    pub fn heading(&self) -> &str {
+        match self {
+            SectionChange::Added(x) | SectionChange::Removed(x) => &x.heading,
+            SectionChange::Changed { heading, .. } => heading,
+        }
+    }
+}
+
+impl fmt::Display for SectionChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SectionChange::Added(x) => {
+                writeln!(f, "{} (added)", display_heading(&x.heading))?;
+                for line in x.body.lines() {
+                    writeln!(f, "+ {}", line)?;
+                }
+            }
+            SectionChange::Removed(x) => {
+                writeln!(f, "{} (removed)", display_heading(&x.heading))?;
+                for line in x.body.lines() {
+                    writeln!(f, "- {}", line)?;
+                }
+            }
+            SectionChange::Changed { heading, lines } => {
+                writeln!(f, "{} (changed)", display_heading(heading))?;
+                for line in lines {
+                    match line {
+                        Line::Same(x) => writeln!(f, "  {}", x)?,
+                        Line::Added(x) => writeln!(f, "+ {}", x)?,
+                        Line::Removed(x) => writeln!(f, "- {}", x)?,
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn display_heading(heading: &str) -> String {
+    match heading {
+        "" => "[preamble]".to_owned(),
+        x => format!("[{}]", x),
+    }
+}
+
+/// Compares two versions of a document section by section. Sections are
+/// matched by heading, so moving a section around is not reported as a change.
+/// Sections with the same heading are matched in the order they appear.
+pub fn diff_sections(old: &[Section], new: &[Section]) -> Vec<SectionChange> {
+    let mut res = Vec::new();
+
+    for (i, section) in new.iter().enumerate() {
+        match nth_with_heading(old, &section.heading, occurrence(new, i)) {
+            None => res.push(SectionChange::Added(section.clone())),
+            Some(x) if x.body != section.body => res.push(SectionChange::Changed {
+                heading: section.heading.clone(),
+                lines: diff_lines(&x.body, &section.body),
+            }),
+            Some(_) => (),
+        }
+    }
+
+    for (i, section) in old.iter().enumerate() {
+        if nth_with_heading(new, &section.heading, occurrence(old, i)).is_none() {
+            res.push(SectionChange::Removed(section.clone()));
+        }
+    }
+
+    res
+}
+
+/// How many sections before the `i`th one have the same heading.
+fn occurrence(sections: &[Section], i: usize) -> usize {
+    sections[..i]
+        .iter()
+        .filter(|x| x.heading == sections[i].heading)
+        .count()
+}
+
+fn nth_with_heading<'a>(sections: &'a [Section], heading: &str, n: usize) -> Option<&'a Section> {
+    sections.iter().filter(|x| x.heading == heading).nth(n)
+}
+
+/// A line diff based on the longest common subsequence.
+pub fn diff_lines(old: &str, new: &str) -> Vec<Line> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = match a[i] == b[j] {
+                true => lcs[i + 1][j + 1] + 1,
+                false => lcs[i + 1][j].max(lcs[i][j + 1]),
+            };
+        }
+    }
+
+    let mut res = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            res.push(Line::Same(a[i].to_owned()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            res.push(Line::Removed(a[i].to_owned()));
+            i += 1;
+        } else {
+            res.push(Line::Added(b[j].to_owned()));
+            j += 1;
+        }
+    }
+
+    res.extend(a[i..].iter().map(|x| Line::Removed(x.to_string())));
+    res.extend(b[j..].iter().map(|x| Line::Added(x.to_string())));
+
+    res
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adr::sections::SectionKind;
+
+    fn section(heading: &str, body: &str) -> Section {
+        Section::new(heading, SectionKind::Custom, body)
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let res = diff_lines("a\nb\nc", "a\nc\nd");
+
+        assert_eq!(
+            vec![
+                Line::Same("a".to_owned()),
+                Line::Removed("b".to_owned()),
+                Line::Same("c".to_owned()),
+                Line::Added("d".to_owned()),
+            ],
+            res
+        );
+    }
+
+    #[test]
+    fn test_diff_sections() {
+        let old = vec![
+            section("Status", "Proposed"),
+            section("Context", "Same"),
+            section("Options", "A"),
+        ];
+        let new = vec![
+            section("Status", "Accepted"),
+            section("Context", "Same"),
+            section("Decision", "B"),
+        ];
+
+        let headings: Vec<String> = diff_sections(&old, &new)
+            .iter()
+            .map(|x| x.to_string().lines().next().unwrap().to_owned())
+            .collect();
+
+        assert_eq!(
+            vec![
+                "[Status] (changed)",
+                "[Decision] (added)",
+                "[Options] (removed)"
+            ],
+            headings
+        );
+    }
+
+    #[test]
+    fn test_diff_duplicate_headings() {
+        let old = vec![section("Notes", "a"), section("Notes", "b")];
+        let new = vec![
+            section("Notes", "a"),
+            section("Notes", "c"),
+            section("Notes", "d"),
+        ];
+
+        let headings: Vec<String> = diff_sections(&old, &new)
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+
+        assert_eq!(
+            vec!["[Notes] (changed)\n- b\n+ c\n", "[Notes] (added)\n+ d\n"],
+            headings
+        );
+        assert!(diff_sections(&new, &new).is_empty());
+    }
+}
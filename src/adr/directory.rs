@@ -1,24 +1,40 @@
-use super::{Adr, IndexType, Status};
-use chrono::{Date, Utc};
+use super::locale::{Locale, Vocabulary};
+use super::lock::Lock;
+use super::{Adr, IndexType, SectionKind, Status};
+use crate::{Error, IoContext};
+use chrono::{Date, Duration, Utc};
 use inflector::Inflector;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(deny_unknown_fields)]
 pub struct Directory {
     pub path: std::path::PathBuf,
     pub name: String,
     pub index: IndexType,
+    #[serde(default, skip_serializing_if = "Locale::is_default")]
+    pub locale: Locale,
 
     #[serde(skip)]
     pub full_path: std::path::PathBuf,
 }
 
 impl Directory {
-    pub fn get_adrs(&self) -> Result<Vec<Adr>, Box<dyn std::error::Error>> {
+    pub fn vocabulary(&self) -> Vocabulary {
+        self.locale.vocabulary()
+    }
+
+    pub fn get_adrs(&self) -> crate::Result<Vec<Adr>> {
         let mut res = Vec::new();
+        let vocab = self.vocabulary();
+
+        let entries = self.full_path.read_dir().with_path(&self.full_path)?;
+
+        for entry in entries.flatten() {
+            let path = entry.path();
 
-        for entry in self.full_path.read_dir()?.flatten() {
-            if let Some(adr) = Adr::load(&entry.path())? {
+            if let Some(adr) = Adr::load(&path, &vocab).with_path(&path)? {
                 res.push(adr)
             }
         }
@@ -26,6 +42,19 @@ impl Directory {
         Ok(res)
     }
 
+    /// Finds an ADR by its index, ignoring leading zeros so that `3` finds
+    /// `00003`.
+    pub fn find_adr(&self, index: &str) -> crate::Result<Option<Adr>> {
+        let wanted = index
+            .parse::<u64>()
+            .map_err(|_| Error::Invalid(format!("`{}` is not an adr index", index)))?;
+
+        Ok(self
+            .get_adrs()?
+            .into_iter()
+            .find(|x| x.index.parse::<u64>().ok() == Some(wanted)))
+    }
+
     pub fn create_adr(
         &self,
         title: &str,
@@ -34,82 +63,173 @@ impl Directory {
         context: &str,
         decision: &str,
         consequences: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let index = self.next_index()?;
+    ) -> crate::Result<Adr> {
+        if !self.full_path.exists() {
+            std::fs::create_dir_all(&self.full_path).with_path(&self.full_path)?;
+        }
 
-        let filename = format!(
-            "{}-{}.md",
-            index,
-            title.trim().replace(' ', "-").to_lowercase(),
-        );
+        // Hold the lock from picking the index until the file exists, so that
+        // concurrent creators never hand out the same index.
+        let _lock = Lock::acquire(&self.full_path)?;
+
+        // An index can still be taken by a file that isn't a readable ADR, and
+        // then only creating the file tells, so the next one is tried.
+        let mut taken = Vec::new();
+
+        let (mut adr, mut file) = loop {
+            let index = self.pick_index(&taken)?;
+
+            let filename = format!(
+                "{}-{}.md",
+                index,
+                title.trim().replace(' ', "-").to_lowercase(),
+            );
+
+            let path = self.full_path.join(filename);
+
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(x) => {
+                    let adr = Adr::new(
+                        &index,
+                        &title.to_title_case(),
+                        path,
+                        date,
+                        status,
+                        &self.vocabulary(),
+                    );
+
+                    break (adr, x);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    match index.parse::<u64>() {
+                        Ok(x) => taken.push(x),
+                        Err(_) => {
+                            return Err(Error::Invalid(format!(
+                                "an adr already exists at {}",
+                                path.display()
+                            )))
+                        }
+                    }
+                }
+                Err(e) => return Err(Error::io(&path, e)),
+            }
+        };
 
-        if !self.full_path.exists() {
-            std::fs::create_dir_all(&self.full_path)?;
+        for (kind, body) in [
+            (SectionKind::Context, context),
+            (SectionKind::Decision, decision),
+            (SectionKind::Consequences, consequences),
+        ] {
+            if let Some(x) = adr.section_mut(kind) {
+                x.body = body.trim().to_owned();
+            }
         }
 
-        let template = format!(
-            "# {}\n\n\
-            Date: {}\n\n\
-            ## Status\n\n\
-            {} \n\n\
-            ## Context\n\n\
-            {}\n\n\
-            ## Decision\n\n\
-            {}\n\n\
-            ## Consequences\n\n\
-            {}\n",
-            title.to_title_case(),
-            date.format("%Y-%m-%d"),
-            status,
-            context,
-            decision,
-            consequences
-        );
-
-        let path = self.full_path.join(filename);
-
-        std::fs::write(path, template)?;
+        std::io::Write::write_all(&mut file, adr.to_markdown().as_bytes()).with_path(&adr.path)?;
 
-        Ok(())
+        Ok(adr)
     }
 
-    pub fn next_index(&self) -> Result<String, Box<dyn std::error::Error>> {
+    pub fn next_index(&self) -> crate::Result<String> {
+        self.pick_index(&[])
+    }
+
+    /// The next index that is neither used by an ADR nor in `taken`.
+    fn pick_index(&self, taken: &[u64]) -> crate::Result<String> {
+        let mut used = self.used_indexes()?;
+        used.extend_from_slice(taken);
+
         match &self.index {
-            IndexType::Sequential => Ok(format!("{:05}", self.get_seq_index()?)),
-            IndexType::Timestamp => Ok(format!("{}", Utc::now().format("%Y%m%d%H%M%S"))),
+            IndexType::Sequential => Ok(format!("{:05}", get_seq_index(&used))),
+            IndexType::Timestamp => Ok(get_timestamp_index(&used)),
         }
     }
 
-    fn get_seq_index(&self) -> Result<u32, Box<dyn std::error::Error>> {
-        let mut max_index = 0;
-
-        for entry in (self.full_path.read_dir()?).flatten() {
-            if let Some(idx) = self.index_from_entry(entry) {
-                if max_index < idx {
-                    max_index = idx
-                }
-            }
+    fn used_indexes(&self) -> crate::Result<Vec<u64>> {
+        if !self.full_path.exists() {
+            return Ok(Vec::new());
         }
 
-        Ok(max_index + 1)
+        let vocab = self.vocabulary();
+
+        Ok(self
+            .full_path
+            .read_dir()
+            .with_path(&self.full_path)?
+            .flatten()
+            .filter_map(|x| index_from_entry(x, &vocab))
+            .collect())
     }
+}
+
+fn get_seq_index(used: &[u64]) -> u64 {
+    used.iter().max().unwrap_or(&0) + 1
+}
+
+/// Uses the current time, bumped one second at a time past any timestamp that
+/// is already taken.
+fn get_timestamp_index(used: &[u64]) -> String {
+    let mut time = Utc::now().naive_utc();
 
-    fn index_from_entry(&self, entry: std::fs::DirEntry) -> Option<u32> {
-        let adr = Adr::load(&entry.path()).ok()??;
+    loop {
+        let index = format!("{}", time.format("%Y%m%d%H%M%S"));
 
-        match adr.index.parse::<u32>() {
-            Ok(x) => Some(x),
-            Err(_) => None,
+        match index.parse::<u64>() {
+            Ok(x) if used.contains(&x) => time += Duration::seconds(1),
+            _ => return index,
         }
     }
 }
 
+fn index_from_entry(entry: std::fs::DirEntry, vocab: &Vocabulary) -> Option<u64> {
+    let adr = Adr::load(&entry.path(), vocab).ok()??;
+
+    adr.index.parse::<u64>().ok()
+}
+
 #[cfg(test)]
 mod test {
     use super::{Directory, IndexType};
+    use crate::adr::locale::{Language, Locale};
+    use crate::adr::Status;
     use chrono::Utc;
+    use std::collections::HashSet;
     use std::error::Error;
 
+    fn create_in_parallel(dir: &Directory, count: usize) -> Vec<String> {
+        std::thread::scope(|s| {
+            let handles: Vec<_> = (0..count)
+                .map(|i| {
+                    s.spawn(move || {
+                        dir.create_adr(
+                            &format!("parallel {}", i),
+                            Utc::now().date(),
+                            Status::Proposed,
+                            "",
+                            "",
+                            "",
+                        )
+                        .is_ok()
+                    })
+                })
+                .collect();
+
+            for h in handles {
+                assert!(h.join().unwrap());
+            }
+        });
+
+        dir.get_adrs()
+            .unwrap()
+            .into_iter()
+            .map(|x| x.index)
+            .collect()
+    }
+
     #[test]
     fn test_directory_index_timestamp() -> Result<(), Box<dyn Error>> {
         let tmp = tempdir::TempDir::new("dir_test")?;
@@ -118,6 +238,7 @@ mod test {
             path: tmp.path().to_path_buf(),
             name: "foo".to_owned(),
             index: IndexType::Timestamp,
+            locale: Locale::default(),
             full_path: tmp.path().to_path_buf(),
         };
 
@@ -138,6 +259,7 @@ mod test {
             path: tmp.path().to_path_buf(),
             name: "foo".to_owned(),
             index: IndexType::Sequential,
+            locale: Locale::default(),
             full_path: tmp.path().to_path_buf(),
         };
 
@@ -167,4 +289,137 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_directory_parallel_sequential() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir::TempDir::new("dir_test")?;
+
+        let dir = Directory {
+            path: tmp.path().to_path_buf(),
+            name: "foo".to_owned(),
+            index: IndexType::Sequential,
+            locale: Locale::default(),
+            full_path: tmp.path().to_path_buf(),
+        };
+
+        let indexes = create_in_parallel(&dir, 8);
+        let unique: HashSet<&String> = indexes.iter().collect();
+
+        assert_eq!(8, indexes.len());
+        assert_eq!(8, unique.len());
+        assert!(unique.contains(&"00008".to_owned()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_directory_parallel_timestamp() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir::TempDir::new("dir_test")?;
+
+        let dir = Directory {
+            path: tmp.path().to_path_buf(),
+            name: "foo".to_owned(),
+            index: IndexType::Timestamp,
+            locale: Locale::default(),
+            full_path: tmp.path().to_path_buf(),
+        };
+
+        let indexes = create_in_parallel(&dir, 8);
+        let unique: HashSet<&String> = indexes.iter().collect();
+
+        assert_eq!(8, indexes.len());
+        assert_eq!(8, unique.len());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_directory_existing_file() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir::TempDir::new("dir_test")?;
+
+        let dir = Directory {
+            path: tmp.path().to_path_buf(),
+            name: "foo".to_owned(),
+            index: IndexType::Sequential,
+            locale: Locale::default(),
+            full_path: tmp.path().to_path_buf(),
+        };
+
+        // An entry that cannot be read as an ADR is ignored when allocating, so
+        // the first file name collides with it and the next index is used.
+        let path = tmp.path().join("00001-foo.md");
+        std::fs::create_dir(&path)?;
+
+        let adr = dir.create_adr("foo", Utc::now().date(), Status::Proposed, "", "", "")?;
+
+        assert_eq!("00002", adr.index);
+        assert!(path.is_dir());
+        assert!(tmp.path().join("00002-foo.md").is_file());
+
+        let dir = Directory {
+            index: IndexType::Timestamp,
+            ..dir
+        };
+        let now = dir.next_index()?;
+        std::fs::create_dir(tmp.path().join(format!("{}-bar.md", now)))?;
+
+        let adr = dir.create_adr("bar", Utc::now().date(), Status::Proposed, "", "", "")?;
+        assert!(adr.index > now);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_adr() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir::TempDir::new("dir_test")?;
+
+        let dir = Directory {
+            path: tmp.path().to_path_buf(),
+            name: "foo".to_owned(),
+            index: IndexType::Sequential,
+            locale: Locale::default(),
+            full_path: tmp.path().to_path_buf(),
+        };
+
+        for title in ["first", "second"] {
+            dir.create_adr(title, Utc::now().date(), Status::Proposed, "", "", "")?;
+        }
+
+        assert_eq!("00002", dir.find_adr("2")?.unwrap().index);
+        assert_eq!("00002", dir.find_adr("0002")?.unwrap().index);
+        assert!(dir.find_adr("20")?.is_none());
+        assert!(dir.find_adr("two").is_err());
+        assert!(dir.find_adr("").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_directory_localised_template() -> Result<(), Box<dyn Error>> {
+        let tmp = tempdir::TempDir::new("dir_test")?;
+
+        let dir = Directory {
+            path: tmp.path().to_path_buf(),
+            name: "foo".to_owned(),
+            index: IndexType::Sequential,
+            locale: Locale {
+                language: Language::De,
+                ..Locale::default()
+            },
+            full_path: tmp.path().to_path_buf(),
+        };
+
+        dir.create_adr("foo", Utc::now().date(), Status::Accepted, "", "", "")?;
+
+        let adrs = dir.get_adrs()?;
+        let content = &adrs[0].to_markdown();
+
+        assert!(content.contains("Datum: "));
+        assert!(content.contains("## Status\n\nAkzeptiert"));
+        assert!(content.contains("## Entscheidung"));
+        assert_eq!(Some(Status::Accepted), adrs[0].status);
+        assert!(adrs[0].date.is_some());
+
+        Ok(())
+    }
 }
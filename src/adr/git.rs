@@ -0,0 +1,129 @@
+use chrono::{Date, NaiveDate, Utc};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Revision {
+    pub hash: String,
+    pub date: Date<Utc>,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+    pub name: String,
+    pub date: Date<Utc>,
+}
+
+pub fn is_repo(dir: &Path) -> bool {
+    run(dir, &["rev-parse", "--is-inside-work-tree"])
+        .map(|x| x.trim() == "true")
+        .unwrap_or(false)
+}
+
+pub fn ref_date(dir: &Path, rev: &str) -> Option<Date<Utc>> {
+    let out = run(dir, &["log", "-1", "--format=%cs", rev, "--"])?;
+
+    parse_date(out.trim())
+}
+
+pub fn tags(dir: &Path) -> Vec<Tag> {
+    let out = match run(
+        dir,
+        &[
+            "for-each-ref",
+            "--sort=creatordate",
+            "--format=%(refname:short) %(creatordate:short)",
+            "refs/tags",
+        ],
+    ) {
+        None => return Vec::new(),
+        Some(x) => x,
+    };
+
+    out.lines()
+        .filter_map(|line| {
+            let (name, date) = line.rsplit_once(' ')?;
+
+            Some(Tag {
+                name: name.to_owned(),
+                date: parse_date(date)?,
+            })
+        })
+        .collect()
+}
+
+/// Returns the revisions that touched the file, oldest first, following renames.
+pub fn file_history(dir: &Path, path: &Path) -> Vec<Revision> {
+    let file = path.to_string_lossy();
+    let out = match run(
+        dir,
+        &[
+            "log",
+            "--follow",
+            "--name-only",
+            "--format=%x00%H %cs",
+            "--",
+            &file,
+        ],
+    ) {
+        None => return Vec::new(),
+        Some(x) => x,
+    };
+
+    let mut res: Vec<Revision> = out
+        .split('\0')
+        .filter_map(|entry| {
+            let mut lines = entry.lines().filter(|x| !x.trim().is_empty());
+            let (hash, date) = lines.next()?.split_once(' ')?;
+            let path = lines.next()?;
+
+            Some(Revision {
+                hash: hash.to_owned(),
+                date: parse_date(date)?,
+                path: PathBuf::from(path),
+            })
+        })
+        .collect();
+
+    res.reverse();
+    res
+}
+
+/// Reads a file as it was at the given revision. The path is relative to the
+/// root of the repository.
+pub fn show(dir: &Path, rev: &Revision) -> Option<String> {
+    let spec = format!("{}:{}", rev.hash, rev.path.to_string_lossy());
+
+    run(dir, &["show", &spec])
+}
+
+/// Reads a file as it was at any revision, e.g. `HEAD~2` or a tag.
+pub fn show_file(rev: &str, path: &Path) -> Option<String> {
+    let dir = path.parent()?;
+    let name = path.file_name()?.to_string_lossy();
+    let spec = format!("{}:./{}", rev, name);
+
+    run(dir, &["show", &spec])
+}
+
+fn run(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8(output.stdout).ok()
+}
+
+pub fn parse_date(s: &str) -> Option<Date<Utc>> {
+    let date = NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").ok()?;
+
+    Some(Date::<Utc>::from_utc(date, Utc))
+}
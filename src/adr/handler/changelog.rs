@@ -0,0 +1,78 @@
+use crate::adr::changelog::{self, Event, Grouping};
+use crate::adr::{git, Repository};
+use crate::Error;
+use chrono::{Date, Utc};
+
+pub struct Handler {}
+
+pub enum GroupBy {
+    Month,
+    Release,
+}
+
+impl Handler {
+    pub fn handle(
+        self,
+        repo: &Repository,
+        name: Option<&str>,
+        since: &str,
+        until: Option<&str>,
+        group_by: GroupBy,
+        use_git: bool,
+    ) -> crate::Result<String> {
+        let dir = repo.dir(name)?;
+
+        let use_git = use_git && git::is_repo(&dir.full_path);
+
+        let since = resolve_date(since, &dir.full_path, use_git)?;
+        let until = match until {
+            None => None,
+            Some(x) => Some(resolve_date(x, &dir.full_path, use_git)?),
+        };
+
+        let adrs = dir.get_adrs()?;
+        let vocab = dir.vocabulary();
+
+        let events: Vec<Event> = adrs
+            .iter()
+            .flat_map(|adr| match use_git {
+                true => changelog::events_from_history(&dir.full_path, adr, &vocab)
+                    .unwrap_or_else(|| changelog::events_from_content(adr)),
+                false => changelog::events_from_content(adr),
+            })
+            .filter(|x| changelog::in_window(x, since, until))
+            .collect();
+
+        let grouping = match group_by {
+            GroupBy::Month => Grouping::Month,
+            GroupBy::Release if use_git => Grouping::Release(git::tags(&dir.full_path)),
+            GroupBy::Release => Err(Error::Config(
+                "grouping by release requires a git repository".to_owned(),
+            ))?,
+        };
+
+        Ok(changelog::render(&events, &grouping, repo.root()))
+    }
+}
+
+/// Resolves either a `YYYY-MM-DD` date or a git revision to a date.
+fn resolve_date(value: &str, dir: &std::path::Path, use_git: bool) -> crate::Result<Date<Utc>> {
+    if let Some(x) = git::parse_date(value) {
+        return Ok(x);
+    }
+
+    if !use_git {
+        Err(Error::Invalid(format!(
+            "`{}` is not a date in the YYYY-MM-DD format",
+            value
+        )))?;
+    }
+
+    match git::ref_date(dir, value) {
+        Some(x) => Ok(x),
+        None => Err(Error::Invalid(format!(
+            "`{}` is neither a date nor a git revision",
+            value
+        )))?,
+    }
+}
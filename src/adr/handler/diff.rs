@@ -0,0 +1,33 @@
+use crate::adr::diff::{self, SectionChange};
+use crate::adr::{git, sections, Repository};
+use crate::{Error, IoContext};
+
+pub struct Handler {}
+
+impl Handler {
+    pub fn handle(
+        self,
+        repo: &Repository,
+        dir_name: Option<&str>,
+        index: &str,
+        rev: &str,
+    ) -> crate::Result<Vec<SectionChange>> {
+        let adr = repo.find(dir_name, index)?;
+
+        let old = match git::show_file(rev, &adr.path) {
+            None => Err(Error::NotFound(format!(
+                "{} does not exist at revision {}",
+                adr.path.display(),
+                rev
+            )))?,
+            Some(x) => x,
+        };
+
+        let current = std::fs::read_to_string(&adr.path).with_path(&adr.path)?;
+
+        Ok(diff::diff_sections(
+            &sections::split(&old, 2),
+            &sections::split(&current, 2),
+        ))
+    }
+}
@@ -0,0 +1,143 @@
+use crate::adr::diff::{self, SectionChange};
+use crate::adr::locale::Vocabulary;
+use crate::adr::{git, model, sections, Adr, Repository};
+use crate::{Error, IoContext};
+use chrono::{Date, Utc};
+
+pub struct Handler {}
+
+/// The commits that touched an ADR, oldest first.
+#[derive(Debug, Clone)]
+pub struct History {
+    pub adr: Adr,
+    pub entries: Vec<Entry>,
+    /// What changed in the working tree since the last commit, if anything.
+    pub uncommitted: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub hash: String,
+    pub date: Date<Utc>,
+    /// What changed in the commit, e.g. `Proposed -> Accepted; edited Decision`.
+    pub summary: String,
+}
+
+impl Handler {
+    pub fn handle(
+        self,
+        repo: &Repository,
+        dir_name: Option<&str>,
+        index: &str,
+    ) -> crate::Result<History> {
+        let dir = repo.dir(dir_name)?;
+        let adr = repo.find(dir_name, index)?;
+
+        if !git::is_repo(&dir.full_path) {
+            Err(Error::Config(
+                "the adr history requires a git repository".to_owned(),
+            ))?;
+        }
+
+        let vocab = dir.vocabulary();
+        let revisions = git::file_history(&dir.full_path, &adr.path);
+
+        let mut entries = Vec::new();
+        let mut previous: Option<String> = None;
+
+        for rev in revisions.iter() {
+            let content = git::show(&dir.full_path, rev).unwrap_or_default();
+
+            entries.push(Entry {
+                hash: rev.hash.clone(),
+                date: rev.date,
+                summary: summarize(previous.as_deref(), &content, &vocab),
+            });
+
+            previous = Some(content);
+        }
+
+        let current = std::fs::read_to_string(&adr.path).with_path(&adr.path)?;
+
+        // An ADR that was never committed has no history to compare with.
+        let uncommitted = match previous {
+            Some(x) if x != current => Some(summarize(Some(&x), &current, &vocab)),
+            _ => None,
+        };
+
+        Ok(History {
+            adr,
+            entries,
+            uncommitted,
+        })
+    }
+}
+
+/// Describes what changed between two versions of an ADR in a single line.
+fn summarize(old: Option<&str>, new: &str, vocab: &Vocabulary) -> String {
+    let status = |x: &str| {
+        model::status_from_content(x, vocab)
+            .map_or("Unknown".to_owned(), |s| vocab.status_word(s).to_owned())
+    };
+
+    let old = match old {
+        None => return format!("created as {}", status(new)),
+        Some(x) => x,
+    };
+
+    let mut parts = Vec::new();
+
+    if status(old) != status(new) {
+        parts.push(format!("{} -> {}", status(old), status(new)));
+    }
+
+    let edited: Vec<String> =
+        diff::diff_sections(&sections::split(old, 2), &sections::split(new, 2))
+            .iter()
+            .filter(|x| !vocab.is_status_heading(x.heading()))
+            .map(|x| match x {
+                SectionChange::Added(s) => format!("added {}", s.heading),
+                SectionChange::Removed(s) => format!("removed {}", s.heading),
+                SectionChange::Changed { heading, .. } if heading.is_empty() => {
+                    "edited preamble".to_owned()
+                }
+                SectionChange::Changed { heading, .. } => format!("edited {}", heading),
+            })
+            .collect();
+
+    parts.extend(edited);
+
+    match parts.is_empty() {
+        true => "formatting only".to_owned(),
+        false => parts.join("; "),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adr::locale::{Keywords, Language, Locale};
+
+    #[test]
+    fn test_summarize() {
+        let vocab = Vocabulary::default();
+        let old = "# Foo\n\n## Status\n\nProposed\n\n## Decision\n\nA\n";
+        let new = "# Foo\n\n## Status\n\nAccepted\n\n## Decision\n\nB\n\n## Consequences\n\nC\n";
+
+        assert_eq!("created as Proposed", summarize(None, old, &vocab));
+        assert_eq!(
+            "Proposed -> Accepted; edited Decision; added Consequences",
+            summarize(Some(old), new, &vocab)
+        );
+
+        // A localised directory still reads records written in English.
+        let vocab = Locale {
+            language: Language::Pt,
+            keywords: Keywords::default(),
+        }
+        .vocabulary();
+        let pt = "# Foo\n\n## Estado\n\nAceita\n\n## Decision\n\nA\n";
+
+        assert_eq!("Proposta -> Aceita", summarize(Some(old), pt, &vocab));
+    }
+}
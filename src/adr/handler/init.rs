@@ -1,4 +1,7 @@
-use crate::adr::{Directory, IndexType, Status};
+use crate::adr::locale::{Language, Locale};
+use crate::adr::{Adr, Directory, IndexType, Status};
+use crate::state::{Context, Document};
+use crate::{Error, IoContext};
 use chrono::Utc;
 
 pub struct Handler {}
@@ -6,25 +9,28 @@ pub struct Handler {}
 impl Handler {
     pub fn handle(
         &self,
+        ctx: &Context,
         dir: &std::path::Path,
         name: String,
         index_type: IndexType,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let mut state = crate::state::State::load()?;
+        language: Language,
+    ) -> crate::Result<Directory> {
+        let state = crate::state::State::load(ctx)?;
 
-        let cwd = std::env::current_dir()?;
-
-        let adr_path = cwd.join(dir);
+        let adr_path = ctx.resolve(dir);
 
         if !adr_path.exists() {
-            std::fs::create_dir_all(&adr_path)?;
+            std::fs::create_dir_all(&adr_path).with_path(&adr_path)?;
         }
 
-        let canon_path = adr_path.canonicalize()?;
+        let canon_path = adr_path.canonicalize().with_path(&adr_path)?;
 
         if !path_is_parent(&state.path, &canon_path) {
-            println!("{:?} {:?}", state.path, canon_path);
-            return Err("path is not correct")?;
+            return Err(Error::Invalid(format!(
+                "{} is outside of {}, where the .docula file is",
+                canon_path.display(),
+                state.path.display()
+            )));
         }
 
         let parent_count = state.path.components().count();
@@ -36,31 +42,44 @@ impl Handler {
             path: relative_path,
             name,
             index: index_type,
+            locale: Locale {
+                language,
+                ..Locale::default()
+            },
             full_path: canon_path,
         };
 
         state.adr.validate_dir(&dir)?;
 
-        state.adr.dirs.push(dir.clone());
-
-        state.save()?;
+        // Only the new directory is written, to the repository's own file,
+        // leaving out whatever the other layers and the defaults set.
+        let mut document = Document::open(ctx, "adr.dirs")?;
+        document.push("adr.dirs", serde_yaml::to_value(&dir)?)?;
+        document.save()?;
 
-        let date = Utc::now().date();
+        create_first_adr(&dir)?;
 
-        dir.create_adr(
-            "Record architecture decisions",
-            date,
-            Status::Accepted,
-            "We need to record the architectural decisions made on this project.",
-            "We will use Architecture Decision Records, managed by \
-            [Docula](https://github.com/docula-io/docula),\n\
-            as described by Michael Nygard in this article: \
-            http://thinkrelevance.com/blog/2011/11/15/documenting-architecture-decisions",
-            "See Michael Nygard's article, linked above.",
-        )
+        Ok(dir)
     }
 }
 
+/// Records the decision to use ADRs, as the first ADR of a new directory.
+pub fn create_first_adr(dir: &Directory) -> crate::Result<Adr> {
+    let date = Utc::now().date();
+
+    dir.create_adr(
+        "Record architecture decisions",
+        date,
+        Status::Accepted,
+        "We need to record the architectural decisions made on this project.",
+        "We will use Architecture Decision Records, managed by \
+        [Docula](https://github.com/docula-io/docula),\n\
+        as described by Michael Nygard in this article: \
+        http://thinkrelevance.com/blog/2011/11/15/documenting-architecture-decisions",
+        "See Michael Nygard's article, linked above.",
+    )
+}
+
 fn path_is_parent(parent: &std::path::PathBuf, path: &std::path::PathBuf) -> bool {
     if path == parent {
         return true;
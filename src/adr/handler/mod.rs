@@ -1,3 +1,6 @@
+pub mod changelog;
+pub mod diff;
+pub mod history;
 pub mod init;
 pub mod list;
 pub mod new;
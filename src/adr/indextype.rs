@@ -1,7 +1,9 @@
 use clap::ValueEnum;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, ValueEnum, Debug, Clone)]
+/// How the records of a directory are numbered.
+#[derive(Serialize, Deserialize, ValueEnum, Debug, Clone, JsonSchema)]
 pub enum IndexType {
     Timestamp,
     Sequential,
@@ -0,0 +1,383 @@
+use super::sections::SectionKind;
+use super::Status;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// The language an ADR directory is written in, along with any keywords the
+/// team prefers over the built in translation.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Locale {
+    #[serde(default)]
+    pub language: Language,
+    #[serde(default, skip_serializing_if = "Keywords::is_empty")]
+    pub keywords: Keywords,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    En,
+    De,
+    Pt,
+    Es,
+    Fr,
+}
+
+/// Overrides for individual keywords, as written in `.docula`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Keywords {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decision: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consequences: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pros_cons: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proposed: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accepted: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejected: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub superseded: Option<String>,
+}
+
+impl Language {
+    pub const ALL: [Language; 5] = [
+        Language::En,
+        Language::De,
+        Language::Pt,
+        Language::Es,
+        Language::Fr,
+    ];
+}
+
+impl Keywords {
+    pub fn is_empty(&self) -> bool {
+        self == &Keywords::default()
+    }
+}
+
+impl Locale {
+    pub fn is_default(&self) -> bool {
+        self == &Locale::default()
+    }
+
+    pub fn vocabulary(&self) -> Vocabulary {
+        let base = Vocabulary::builtin(self.language);
+        let k = &self.keywords;
+        let pick = |x: &Option<String>, default: &'static str| {
+            x.clone().unwrap_or_else(|| default.to_owned())
+        };
+
+        Vocabulary {
+            date: pick(&k.date, base.date),
+            status: pick(&k.status, base.status),
+            context: pick(&k.context, base.context),
+            decision: pick(&k.decision, base.decision),
+            consequences: pick(&k.consequences, base.consequences),
+            options: pick(&k.options, base.options),
+            pros_cons: pick(&k.pros_cons, base.pros_cons),
+            proposed: pick(&k.proposed, base.proposed),
+            accepted: pick(&k.accepted, base.accepted),
+            rejected: pick(&k.rejected, base.rejected),
+            deprecated: pick(&k.deprecated, base.deprecated),
+            superseded: pick(&k.superseded, base.superseded),
+        }
+    }
+}
+
+/// The resolved keywords used to write and read ADRs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vocabulary {
+    pub date: String,
+    pub status: String,
+    pub context: String,
+    pub decision: String,
+    pub consequences: String,
+    pub options: String,
+    pub pros_cons: String,
+    pub proposed: String,
+    pub accepted: String,
+    pub rejected: String,
+    pub deprecated: String,
+    pub superseded: String,
+}
+
+/// Selects one of the keywords of a vocabulary.
+pub type Field = fn(&Vocabulary) -> &String;
+
+struct Builtin {
+    date: &'static str,
+    status: &'static str,
+    context: &'static str,
+    decision: &'static str,
+    consequences: &'static str,
+    options: &'static str,
+    pros_cons: &'static str,
+    proposed: &'static str,
+    accepted: &'static str,
+    rejected: &'static str,
+    deprecated: &'static str,
+    superseded: &'static str,
+}
+
+impl Default for Vocabulary {
+    fn default() -> Vocabulary {
+        Locale::default().vocabulary()
+    }
+}
+
+impl Vocabulary {
+    fn builtin(language: Language) -> Builtin {
+        match language {
+            Language::En => Builtin {
+                date: "Date",
+                status: "Status",
+                context: "Context",
+                decision: "Decision",
+                consequences: "Consequences",
+                options: "Considered Options",
+                pros_cons: "Pros and Cons of the Options",
+                proposed: "Proposed",
+                accepted: "Accepted",
+                rejected: "Rejected",
+                deprecated: "Deprecated",
+                superseded: "Superseded",
+            },
+            Language::De => Builtin {
+                date: "Datum",
+                status: "Status",
+                context: "Kontext",
+                decision: "Entscheidung",
+                consequences: "Konsequenzen",
+                options: "Betrachtete Optionen",
+                pros_cons: "Vor- und Nachteile der Optionen",
+                proposed: "Vorgeschlagen",
+                accepted: "Akzeptiert",
+                rejected: "Abgelehnt",
+                deprecated: "Veraltet",
+                superseded: "Ersetzt",
+            },
+            Language::Pt => Builtin {
+                date: "Data",
+                status: "Estado",
+                context: "Contexto",
+                decision: "Decisão",
+                consequences: "Consequências",
+                options: "Opções Consideradas",
+                pros_cons: "Prós e Contras das Opções",
+                proposed: "Proposta",
+                accepted: "Aceita",
+                rejected: "Rejeitada",
+                deprecated: "Obsoleta",
+                superseded: "Substituída",
+            },
+            Language::Es => Builtin {
+                date: "Fecha",
+                status: "Estado",
+                context: "Contexto",
+                decision: "Decisión",
+                consequences: "Consecuencias",
+                options: "Opciones Consideradas",
+                pros_cons: "Pros y Contras de las Opciones",
+                proposed: "Propuesta",
+                accepted: "Aceptada",
+                rejected: "Rechazada",
+                deprecated: "Obsoleta",
+                superseded: "Reemplazada",
+            },
+            Language::Fr => Builtin {
+                date: "Date",
+                status: "Statut",
+                context: "Contexte",
+                decision: "Décision",
+                consequences: "Conséquences",
+                options: "Options Envisagées",
+                pros_cons: "Avantages et Inconvénients des Options",
+                proposed: "Proposée",
+                accepted: "Acceptée",
+                rejected: "Rejetée",
+                deprecated: "Dépréciée",
+                superseded: "Remplacée",
+            },
+        }
+    }
+
+    pub fn status_word(&self, status: Status) -> &str {
+        match status {
+            Status::Proposed => &self.proposed,
+            Status::Accepted => &self.accepted,
+            Status::Rejected => &self.rejected,
+            Status::Deprecated => &self.deprecated,
+            Status::Superseded => &self.superseded,
+        }
+    }
+
+    /// Matches the status keyword a text starts with, in this vocabulary or
+    /// in English, so that records written before a directory was localised
+    /// keep working. Keywords can be several words, e.g. "Superseded by", so
+    /// the longest one that matches wins.
+    pub fn status_from_text(&self, text: &str) -> Option<Status> {
+        let mut phrases: Vec<(String, Status)> = [self, &*ENGLISH]
+            .into_iter()
+            .flat_map(|vocab| {
+                STATUSES
+                    .iter()
+                    .map(|x| (vocab.status_word(*x).trim().to_lowercase(), *x))
+            })
+            .filter(|(x, _)| !x.is_empty())
+            .collect();
+
+        phrases.sort_by_key(|(x, _)| std::cmp::Reverse(x.chars().count()));
+
+        let text = text.trim_start().to_lowercase();
+
+        phrases
+            .into_iter()
+            .find(|(x, _)| {
+                text.strip_prefix(x.as_str())
+                    .is_some_and(|rest| !rest.starts_with(|c: char| c.is_alphanumeric()))
+            })
+            .map(|(_, status)| status)
+    }
+
+    /// Works out which part of an ADR a heading introduces, accepting both the
+    /// localised and the English keywords.
+    pub fn section_kind(&self, heading: &str) -> SectionKind {
+        let kinds: [(SectionKind, Field); 6] = [
+            (SectionKind::Status, |x| &x.status),
+            (SectionKind::Context, |x| &x.context),
+            (SectionKind::Decision, |x| &x.decision),
+            (SectionKind::Consequences, |x| &x.consequences),
+            (SectionKind::Options, |x| &x.options),
+            (SectionKind::ProsAndCons, |x| &x.pros_cons),
+        ];
+
+        let heading = heading.trim().to_lowercase();
+
+        kinds
+            .into_iter()
+            .find(|(_, field)| {
+                self.alternatives(*field)
+                    .iter()
+                    .any(|x| x.to_lowercase() == heading)
+            })
+            .map_or(SectionKind::Custom, |(kind, _)| kind)
+    }
+
+    /// Whether a heading introduces the status, in this vocabulary or in
+    /// English like any other section.
+    pub fn is_status_heading(&self, heading: &str) -> bool {
+        self.section_kind(heading) == SectionKind::Status
+    }
+
+    /// The keywords accepted for a field, localised first and then English.
+    pub fn alternatives(&self, field: Field) -> Vec<&str> {
+        let localised = field(self).as_str();
+        let english = field(&ENGLISH);
+
+        match localised == english {
+            true => vec![localised],
+            false => vec![localised, english],
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref ENGLISH: Vocabulary = Vocabulary::default();
+}
+
+const STATUSES: [Status; 5] = [
+    Status::Proposed,
+    Status::Accepted,
+    Status::Rejected,
+    Status::Deprecated,
+    Status::Superseded,
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_custom_keywords() {
+        let locale = Locale {
+            language: Language::De,
+            keywords: Keywords {
+                status: Some("Zustand".to_owned()),
+                ..Keywords::default()
+            },
+        };
+
+        let vocab = locale.vocabulary();
+
+        assert_eq!("Datum", vocab.date);
+        assert_eq!("Zustand", vocab.status);
+    }
+
+    #[test]
+    fn test_status_from_word() {
+        let vocab = Locale {
+            language: Language::Pt,
+            keywords: Keywords::default(),
+        }
+        .vocabulary();
+
+        assert_eq!(Some(Status::Accepted), vocab.status_from_text("Aceita"));
+        assert_eq!(Some(Status::Accepted), vocab.status_from_text("accepted"));
+        assert_eq!(None, vocab.status_from_text("Foo"));
+        assert_eq!(None, vocab.status_from_text("Acceptedly"));
+        assert_eq!(None, vocab.status_from_text("Akzeptiert"));
+    }
+
+    #[test]
+    fn test_is_status_heading() {
+        let vocab = Locale {
+            language: Language::Pt,
+            keywords: Keywords::default(),
+        }
+        .vocabulary();
+
+        assert!(vocab.is_status_heading("Estado"));
+        assert!(vocab.is_status_heading("status"));
+        assert!(!vocab.is_status_heading(" Statut "));
+        assert!(!vocab.is_status_heading("Decision"));
+    }
+
+    #[test]
+    fn test_status_phrases() {
+        let vocab = Locale {
+            language: Language::En,
+            keywords: Keywords {
+                accepted: Some("Not yet decided".to_owned()),
+                rejected: Some("Not".to_owned()),
+                ..Keywords::default()
+            },
+        }
+        .vocabulary();
+
+        assert_eq!(
+            Some(Status::Accepted),
+            vocab.status_from_text("Not yet decided, see #12")
+        );
+        assert_eq!(Some(Status::Rejected), vocab.status_from_text("Not now"));
+        assert_eq!(
+            Some(Status::Superseded),
+            vocab.status_from_text("  superseded by 00002")
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use std::io::{Error, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+
+const LOCK_FILE: &str = ".docula.lock";
+const TIMEOUT: Duration = Duration::from_secs(10);
+const STALE: Duration = Duration::from_secs(30);
+const RETRY: Duration = Duration::from_millis(10);
+
+/// An exclusive lock on an ADR directory, held for as long as the value lives.
+/// The lock is a file created with `create_new` semantics, so it works across
+/// processes as well as threads. It holds a token naming its holder, so that
+/// only the holder ever removes it.
+#[derive(Debug)]
+pub struct Lock {
+    path: PathBuf,
+    token: String,
+}
+
+impl Lock {
+    pub fn acquire(dir: &Path) -> Result<Lock, Error> {
+        let path = dir.join(LOCK_FILE);
+        let token = new_token();
+        let start = Instant::now();
+
+        loop {
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(mut x) => {
+                    let lock = Lock { path, token };
+                    x.write_all(lock.token.as_bytes())?;
+
+                    return Ok(lock);
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => (),
+                Err(e) => return Err(e),
+            }
+
+            if is_stale(&path) {
+                reclaim(&path)?;
+                continue;
+            }
+
+            if start.elapsed() > TIMEOUT {
+                return Err(Error::new(
+                    ErrorKind::WouldBlock,
+                    format!(
+                        "timed out waiting for the lock {:?}, remove it if no other docula is running",
+                        path
+                    ),
+                ));
+            }
+
+            std::thread::sleep(RETRY);
+        }
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        // A lock held for too long may have been reclaimed and taken by
+        // someone else, whose lock must stay.
+        if read_token(&self.path).as_deref() == Some(self.token.as_str()) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// A token no other holder uses: the process, the time and a count for the
+/// locks taken by its threads.
+fn new_token() -> String {
+    static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |x| x.as_nanos());
+
+    format!(
+        "{} {} {}",
+        std::process::id(),
+        nanos,
+        COUNT.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+fn read_token(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path).ok()
+}
+
+/// Removes a stale lock. Only one process reclaims at a time, guarded by a
+/// second lock file, and it only removes the lock whose token it found stale,
+/// so a fresh lock created in the meantime is never touched.
+fn reclaim(path: &Path) -> Result<(), Error> {
+    let token = read_token(path);
+    let guard = path.with_extension("lock.reclaim");
+
+    match std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&guard)
+    {
+        Ok(_) => (),
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+            // The guard is only held for a moment, so an old one was left by
+            // a process that died while reclaiming.
+            if is_stale(&guard) {
+                let _ = std::fs::remove_file(&guard);
+            }
+
+            return Ok(());
+        }
+        Err(e) => return Err(e),
+    }
+
+    let res = match token.is_some() && read_token(path) == token && is_stale(path) {
+        true => std::fs::remove_file(path),
+        false => Ok(()),
+    };
+
+    let _ = std::fs::remove_file(&guard);
+
+    match res {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn is_stale(path: &Path) -> bool {
+    std::fs::metadata(path)
+        .and_then(|x| x.modified())
+        .ok()
+        .and_then(|x| SystemTime::now().duration_since(x).ok())
+        .is_some_and(|x| x > STALE)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_reclaim() {
+        let dir = TempDir::new("docula").unwrap();
+        let path = dir.path().join(LOCK_FILE);
+
+        std::fs::write(&path, "other").unwrap();
+        reclaim(&path).unwrap();
+
+        // A lock that is not stale is left alone.
+        assert!(path.exists());
+        assert_eq!(1, std::fs::read_dir(dir.path()).unwrap().count());
+
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(SystemTime::now() - STALE * 2)
+            .unwrap();
+        reclaim(&path).unwrap();
+
+        assert!(!path.exists());
+        assert_eq!(0, std::fs::read_dir(dir.path()).unwrap().count());
+
+        let lock = Lock::acquire(dir.path()).unwrap();
+        assert!(path.exists());
+        drop(lock);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_drop_keeps_other_lock() {
+        let dir = TempDir::new("docula").unwrap();
+        let path = dir.path().join(LOCK_FILE);
+
+        let lock = Lock::acquire(dir.path()).unwrap();
+        assert_eq!(Some(lock.token.clone()), read_token(&path));
+
+        // Another holder took over the lock in the meantime.
+        std::fs::write(&path, "other").unwrap();
+        drop(lock);
+
+        assert_eq!(Some("other".to_owned()), read_token(&path));
+        assert_ne!(new_token(), new_token());
+    }
+}
@@ -1,10 +1,19 @@
+pub mod changelog;
+pub mod diff;
 pub mod handler;
+pub mod locale;
+pub mod sections;
 pub mod state;
 
 mod directory;
+mod git;
 mod indextype;
+mod lock;
 mod model;
+mod repository;
 
-use directory::Directory;
+pub use directory::Directory;
 pub use indextype::IndexType;
-use model::{Adr, Status};
+pub use model::{Adr, Status};
+pub use repository::Repository;
+pub use sections::{Section, SectionKind};
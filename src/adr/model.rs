@@ -1,20 +1,45 @@
+use super::locale::Vocabulary;
+use super::sections::{self, Section, SectionKind};
 use chrono::{Date, NaiveDate, Utc};
 use inflector::Inflector;
+use std::borrow::Cow;
 use std::fmt;
 
-#[derive(Debug)]
+/// An architecture decision record, parsed into its sections so that commands
+/// can read and change parts of it without touching the rest.
+#[derive(Debug, Clone)]
 pub struct Adr {
     pub index: String,
     pub title: String,
-    pub content: String,
+    pub path: std::path::PathBuf,
     pub date: Option<chrono::Date<chrono::Utc>>,
     pub status: Option<Status>,
+    /// Everything between the title and the first section, e.g. the date.
+    pub preamble: String,
+    pub sections: Vec<Section>,
+    /// The text the record was parsed from, if it was.
+    pub(super) original: Option<Original>,
 }
 
-#[derive(Clone, Copy, Debug)]
+/// The text of a parsed record, which saving keeps as it is for the title,
+/// preamble and sections that did not change.
+#[derive(Debug, Clone)]
+pub(super) struct Original {
+    content: String,
+    title: String,
+    preamble: String,
+    /// Where the title and the preamble end.
+    head: usize,
+    sections: Vec<sections::Span>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Status {
     Accepted,
     Proposed,
+    Rejected,
+    Deprecated,
+    Superseded,
 }
 
 impl fmt::Display for Status {
@@ -22,12 +47,49 @@ impl fmt::Display for Status {
         match self {
             Status::Accepted => write!(f, "Accepted"),
             Status::Proposed => write!(f, "Proposed"),
+            Status::Rejected => write!(f, "Rejected"),
+            Status::Deprecated => write!(f, "Deprecated"),
+            Status::Superseded => write!(f, "Superseded"),
         }
     }
 }
 
 impl Adr {
-    pub fn load(path: &std::path::PathBuf) -> Result<Option<Adr>, std::io::Error> {
+    /// Creates a record using the standard template, with empty context,
+    /// decision and consequences sections.
+    pub fn new(
+        index: &str,
+        title: &str,
+        path: std::path::PathBuf,
+        date: Date<Utc>,
+        status: Status,
+        vocab: &Vocabulary,
+    ) -> Adr {
+        Adr {
+            index: index.to_owned(),
+            title: title.to_owned(),
+            path,
+            date: Some(date),
+            status: Some(status),
+            preamble: format!("{}: {}", vocab.date, date.format("%Y-%m-%d")),
+            sections: vec![
+                Section::new(
+                    &vocab.status,
+                    SectionKind::Status,
+                    vocab.status_word(status),
+                ),
+                Section::new(&vocab.context, SectionKind::Context, ""),
+                Section::new(&vocab.decision, SectionKind::Decision, ""),
+                Section::new(&vocab.consequences, SectionKind::Consequences, ""),
+            ],
+            original: None,
+        }
+    }
+
+    pub fn load(
+        path: &std::path::PathBuf,
+        vocab: &Vocabulary,
+    ) -> Result<Option<Adr>, std::io::Error> {
         let re: regex::Regex = regex::Regex::new(r"^(\d{5,14})-(.*)\.md$").unwrap();
 
         let fname = match filename_from_path(path) {
@@ -45,42 +107,224 @@ impl Adr {
             Some(x) => x,
         };
 
-        let content = std::fs::read_to_string(path)?;
-
         let filename = match caps.get(2).map(|m| m.as_str()) {
             None => return Ok(None),
             Some(x) => x,
         };
 
-        let title = match title_from_content(&content).or_else(|| title_from_filename(filename)) {
-            None => return Ok(None),
-            Some(x) => x,
-        };
+        let content = std::fs::read_to_string(path)?;
+
+        Ok(Adr::parse(index, filename, path, &content, vocab))
+    }
 
-        let date = date_from_content(&content);
+    /// Parses the content of a record. The first level one heading is the
+    /// title and every heading up to level two starts a new section.
+    pub fn parse(
+        index: &str,
+        filename: &str,
+        path: &std::path::Path,
+        content: &str,
+        vocab: &Vocabulary,
+    ) -> Option<Adr> {
+        let mut preamble = Vec::new();
+        let mut title = None;
+        let mut spans = Vec::new();
 
-        let status = status_from_content(&content);
+        for mut span in sections::spans(content, 2) {
+            match span.section.level {
+                0 => preamble.push(span.section.body),
+                1 if title.is_none() => {
+                    title = Some(span.section.heading);
+                    preamble.push(span.section.body);
+                }
+                _ => {
+                    span.section.kind = vocab.section_kind(&span.section.heading);
+                    spans.push(span);
+                }
+            }
+        }
+
+        let sections: Vec<Section> = spans.iter().map(|x| x.section.clone()).collect();
+
+        let title = title
+            .map(|x| x.trim().to_owned())
+            .filter(|x| !x.is_empty())
+            .or_else(|| title_from_filename(filename))?;
+
+        let preamble = preamble
+            .into_iter()
+            .filter(|x| !x.is_empty())
+            .collect::<Vec<String>>()
+            .join("\n\n");
 
-        Ok(Some(Adr {
+        let date = date_from_content(&preamble, vocab);
+        let status = sections
+            .iter()
+            .find(|x| x.kind == SectionKind::Status)
+            .and_then(|x| status_from_section(x, vocab));
+
+        Some(Adr {
             index: index.to_owned(),
-            content,
-            title,
+            path: path.to_path_buf(),
             date,
             status,
-        }))
+            original: Some(Original {
+                content: content.to_owned(),
+                title: title.clone(),
+                preamble: preamble.clone(),
+                head: spans.first().map_or(content.len(), |x| x.range.start),
+                sections: spans,
+            }),
+            title,
+            preamble,
+            sections,
+        })
     }
-}
 
-fn filename_from_path(path: &std::path::Path) -> Option<&str> {
-    path.file_name()?.to_str()
-}
+    pub fn section(&self, kind: SectionKind) -> Option<&Section> {
+        self.sections.iter().find(|x| x.kind == kind)
+    }
 
-fn title_from_content(content: &str) -> Option<String> {
-    let re: regex::Regex = regex::Regex::new(r"^#{1}\s{1,}(.*)[^\r\n]*").unwrap();
+    pub fn section_mut(&mut self, kind: SectionKind) -> Option<&mut Section> {
+        self.sections.iter_mut().find(|x| x.kind == kind)
+    }
 
-    let caps = re.captures(content)?;
+    /// Finds a section by its heading, which is how custom sections are
+    /// identified.
+    pub fn section_by_heading(&self, heading: &str) -> Option<&Section> {
+        self.sections
+            .iter()
+            .find(|x| x.heading.eq_ignore_ascii_case(heading.trim()))
+    }
+
+    /// Replaces the section of the same kind, or with the same heading for
+    /// custom sections, or appends it when there is none.
+    pub fn set_section(&mut self, section: Section) {
+        let existing = self.sections.iter_mut().find(|x| match section.kind {
+            SectionKind::Custom => x.heading.eq_ignore_ascii_case(&section.heading),
+            kind => x.kind == kind,
+        });
+
+        match existing {
+            Some(x) => x.body = section.body,
+            None => self.sections.push(section),
+        }
+    }
+
+    pub fn set_status(&mut self, status: Status, vocab: &Vocabulary) {
+        self.status = Some(status);
+        self.set_section(Section::new(
+            &vocab.status,
+            SectionKind::Status,
+            vocab.status_word(status),
+        ));
+    }
+
+    /// Writes the record as Markdown. The parts of a parsed record that did
+    /// not change are kept as they were written, and only the rest is
+    /// rendered from the model.
+    pub fn to_markdown(&self) -> String {
+        let original = match &self.original {
+            Some(x) => x,
+            None => return self.render(),
+        };
+
+        let content = original.content.as_str();
+        let mut blocks: Vec<(Cow<str>, bool)> = Vec::new();
+
+        match self.title == original.title && self.preamble == original.preamble {
+            true => blocks.push((Cow::Borrowed(&content[..original.head]), true)),
+            false => blocks.push((Cow::Owned(self.render_head()), false)),
+        }
+
+        let mut used = vec![false; original.sections.len()];
 
-    caps.get(1).map(|m| m.as_str().trim().to_owned())
+        for section in self.sections.iter() {
+            let found = original.sections.iter().enumerate().position(|(i, x)| {
+                !used[i] && x.section.heading == section.heading && x.section.level == section.level
+            });
+
+            let block = match found {
+                Some(i) => {
+                    used[i] = true;
+                    let span = &original.sections[i];
+
+                    match span.section.body == section.body {
+                        true => (Cow::Borrowed(&content[span.range.clone()]), true),
+                        false => {
+                            let heading = content[span.range.start..span.body_start].trim_end();
+                            (Cow::Owned(render_section(heading, &section.body)), false)
+                        }
+                    }
+                }
+                None => {
+                    let heading = format!("{} {}", "#".repeat(section.level), section.heading);
+                    (Cow::Owned(render_section(&heading, &section.body)), false)
+                }
+            };
+
+            blocks.push(block);
+        }
+
+        let mut res = String::new();
+        let mut kept = true;
+
+        for (text, x) in blocks {
+            // Kept parts already end with the blank lines that were between
+            // them, the others need one.
+            if !res.is_empty() && (!kept || !x) {
+                while !res.ends_with("\n\n") {
+                    res.push('\n');
+                }
+            }
+
+            res.push_str(&text);
+            kept = x;
+        }
+
+        res
+    }
+
+    fn render_head(&self) -> String {
+        match self.preamble.is_empty() {
+            true => format!("# {}\n", self.title),
+            false => format!("# {}\n\n{}\n", self.title, self.preamble),
+        }
+    }
+
+    fn render(&self) -> String {
+        let mut blocks = vec![format!("# {}", self.title)];
+
+        if !self.preamble.is_empty() {
+            blocks.push(self.preamble.clone());
+        }
+
+        for section in self.sections.iter() {
+            blocks.push(format!("{} {}", "#".repeat(section.level), section.heading));
+
+            if !section.body.is_empty() {
+                blocks.push(section.body.clone());
+            }
+        }
+
+        blocks.join("\n\n") + "\n"
+    }
+
+    /// Writes the record back to its path.
+    pub fn save(&self) -> Result<(), std::io::Error> {
+        std::fs::write(&self.path, self.to_markdown())
+    }
+}
+
+fn render_section(heading: &str, body: &str) -> String {
+    match body.is_empty() {
+        true => format!("{}\n", heading),
+        false => format!("{}\n\n{}\n", heading, body),
+    }
+}
+
+fn filename_from_path(path: &std::path::Path) -> Option<&str> {
+    path.file_name()?.to_str()
 }
 
 fn title_from_filename(filename: &str) -> Option<String> {
@@ -93,8 +337,20 @@ fn title_from_filename(filename: &str) -> Option<String> {
     }
 }
 
-fn date_from_content(content: &str) -> Option<Date<Utc>> {
-    let re: regex::Regex = regex::Regex::new(r"Date: (\d{4}-\d{2}-\d{2})").unwrap();
+fn keyword_pattern(alternatives: Vec<&str>) -> String {
+    alternatives
+        .into_iter()
+        .map(regex::escape)
+        .collect::<Vec<String>>()
+        .join("|")
+}
+
+fn date_from_content(content: &str, vocab: &Vocabulary) -> Option<Date<Utc>> {
+    let re: regex::Regex = regex::Regex::new(&format!(
+        r"(?:{}): (\d{{4}}-\d{{2}}-\d{{2}})",
+        keyword_pattern(vocab.alternatives(|x| &x.date))
+    ))
+    .unwrap();
 
     let caps = re.captures(content)?;
 
@@ -105,16 +361,154 @@ fn date_from_content(content: &str) -> Option<Date<Utc>> {
     Some(Date::<Utc>::from_utc(date, Utc))
 }
 
-fn status_from_content(content: &str) -> Option<Status> {
-    let re: regex::Regex = regex::Regex::new(r"## Status\s*(Proposed|Accepted)").unwrap();
+pub fn status_from_content(content: &str, vocab: &Vocabulary) -> Option<Status> {
+    sections::split(content, 2)
+        .iter()
+        .find(|x| vocab.is_status_heading(&x.heading))
+        .and_then(|x| status_from_section(x, vocab))
+}
 
-    let caps = re.captures(content)?;
+fn status_from_section(section: &Section, vocab: &Vocabulary) -> Option<Status> {
+    vocab.status_from_text(&section.body)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adr::locale::{Language, Locale};
+    use std::path::Path;
+
+    #[test]
+    fn test_localised_content() {
+        let vocab = Locale {
+            language: Language::Pt,
+            ..Locale::default()
+        }
+        .vocabulary();
+
+        let content = "# Usar Postgres\n\nData: 2022-09-01\n\n## Estado\n\nAceita\n";
+
+        assert_eq!(Some(Status::Accepted), status_from_content(content, &vocab));
+        assert!(date_from_content(content, &vocab).is_some());
+    }
+
+    #[test]
+    fn test_english_fallback() {
+        let vocab = Locale {
+            language: Language::De,
+            ..Locale::default()
+        }
+        .vocabulary();
+
+        let content = "# Foo\n\nDate: 2022-09-01\n\n## Status\n\nSuperseded by 00002\n";
+
+        assert_eq!(
+            Some(Status::Superseded),
+            status_from_content(content, &vocab)
+        );
+        assert!(date_from_content(content, &vocab).is_some());
+    }
+
+    const CONTENT: &str = "# Use Postgres\n\nDate: 2022-09-01\n\n## Status\n\nProposed\n\n\
+        ## Context\n\nWe need a database.\n\n## Considered Options\n\n* Postgres\n* MySQL\n\n\
+        ## Decision\n\n### Details\n\nPostgres.\n\n## Notes\n\nSee the wiki.\n";
+
+    fn parse() -> Adr {
+        Adr::parse(
+            "00001",
+            "use-postgres",
+            Path::new("00001-use-postgres.md"),
+            CONTENT,
+            &Vocabulary::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_sections() {
+        let adr = parse();
+
+        assert_eq!("Use Postgres", adr.title);
+        assert_eq!("Date: 2022-09-01", adr.preamble);
+        assert_eq!(Some(Status::Proposed), adr.status);
+        assert!(adr.date.is_some());
+
+        let kinds: Vec<SectionKind> = adr.sections.iter().map(|x| x.kind).collect();
+        assert_eq!(
+            vec![
+                SectionKind::Status,
+                SectionKind::Context,
+                SectionKind::Options,
+                SectionKind::Decision,
+                SectionKind::Custom,
+            ],
+            kinds
+        );
+
+        assert_eq!(
+            "### Details\n\nPostgres.",
+            adr.section(SectionKind::Decision).unwrap().body
+        );
+        assert_eq!(
+            "See the wiki.",
+            adr.section_by_heading("notes").unwrap().body
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        assert_eq!(CONTENT, parse().to_markdown());
+    }
+
+    #[test]
+    fn test_round_trip_as_written() {
+        let content = "Use *Postgres*\n==============\n\nDate: 2022-09-01  \nBy: team\n\n\
+            Context\n-------\n\nWe need a **database**.\n\n\n## Status\n\nProposed\n";
+        let vocab = Vocabulary::default();
+        let parse = || Adr::parse("00001", "x", Path::new("x.md"), content, &vocab).unwrap();
+
+        assert_eq!(content, parse().to_markdown());
+
+        let mut adr = parse();
+        adr.set_status(Status::Accepted, &vocab);
+
+        assert_eq!(content.replace("Proposed", "Accepted"), adr.to_markdown());
+
+        adr.sections[0].body = "We need one.".to_owned();
+
+        assert_eq!(
+            "Use *Postgres*\n==============\n\nDate: 2022-09-01  \nBy: team\n\n\
+                Context\n-------\n\nWe need one.\n\n## Status\n\nAccepted\n",
+            adr.to_markdown()
+        );
+
+        adr.title = "Use Postgres".to_owned();
+
+        assert!(adr
+            .to_markdown()
+            .starts_with("# Use Postgres\n\nDate: 2022-09-01\n"));
+    }
+
+    #[test]
+    fn test_mutate_sections() {
+        let vocab = Vocabulary::default();
+        let mut adr = parse();
+
+        adr.set_status(Status::Accepted, &vocab);
+        adr.set_section(Section::new(
+            &vocab.consequences,
+            SectionKind::Consequences,
+            "More ops work.",
+        ));
+        adr.set_section(Section::new("Notes", SectionKind::Custom, "Updated."));
 
-    let proposed_str = caps.get(1).map(|m| m.as_str().trim())?;
+        let content = adr.to_markdown();
 
-    match proposed_str {
-        "Proposed" => Some(Status::Proposed),
-        "Accepted" => Some(Status::Accepted),
-        _ => None,
+        assert!(content.contains("## Status\n\nAccepted\n"));
+        assert!(content.ends_with("## Notes\n\nUpdated.\n\n## Consequences\n\nMore ops work.\n"));
+        assert_eq!(
+            Some(Status::Accepted),
+            status_from_content(&content, &vocab)
+        );
     }
 }
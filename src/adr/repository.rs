@@ -0,0 +1,103 @@
+use super::diff::SectionChange;
+use super::handler::changelog::GroupBy;
+use super::handler::history::History;
+use super::handler::{changelog, diff, history, list, new};
+use super::{Adr, Directory};
+use crate::state::Context;
+use std::path::Path;
+
+/// The ADR directories of a repository, as set up in its `.docula` file.
+/// This is the entry point for working with ADRs from Rust:
+///
+/// ```no_run
+/// let repo = docula::adr::Repository::open(std::path::Path::new("."))?;
+///
+/// for adr in repo.list(None)? {
+///     println!("{} {}", adr.index, adr.title);
+/// }
+/// # Ok::<(), docula::Error>(())
+/// ```
+///
+/// Where a method takes the name of a directory, `None` picks the only
+/// directory there is, or the one the context's working directory is in.
+pub struct Repository {
+    state: crate::state::State,
+}
+
+impl Repository {
+    /// Loads the settings that apply to `dir`.
+    pub fn open(dir: &Path) -> crate::Result<Repository> {
+        Repository::load(&Context::new(dir.to_path_buf()))
+    }
+
+    /// Loads the settings that apply in the context.
+    pub fn load(ctx: &Context) -> crate::Result<Repository> {
+        Ok(Repository {
+            state: crate::state::State::load(ctx)?,
+        })
+    }
+
+    /// Loads the settings that apply to the working directory.
+    pub fn discover() -> crate::Result<Repository> {
+        Repository::load(&Context::current()?)
+    }
+
+    /// The directory the `.docula` file is in.
+    pub fn root(&self) -> &Path {
+        &self.state.path
+    }
+
+    pub fn dirs(&self) -> &[Directory] {
+        &self.state.adr.dirs
+    }
+
+    pub fn dir(&self, name: Option<&str>) -> crate::Result<&Directory> {
+        self.state
+            .adr
+            .require_dir(&name.map(String::from), &self.state.context.cwd)
+    }
+
+    /// Every ADR in a directory, ordered by index.
+    pub fn list(&self, dir: Option<&str>) -> crate::Result<Vec<Adr>> {
+        list::Handler {}.handle(self, dir)
+    }
+
+    /// Finds an ADR by its index, ignoring leading zeros.
+    pub fn find(&self, dir: Option<&str>, index: &str) -> crate::Result<Adr> {
+        self.dir(dir)?.find_adr(index)?.ok_or_else(|| {
+            crate::Error::NotFound(format!("no adr with the index {} could be found", index))
+        })
+    }
+
+    /// Creates a proposed ADR with the given title.
+    pub fn create(&self, dir: Option<&str>, title: &str) -> crate::Result<Adr> {
+        new::Handler {}.handle(self, dir, title)
+    }
+
+    /// What happened to an ADR in each commit that touched it.
+    pub fn history(&self, dir: Option<&str>, index: &str) -> crate::Result<History> {
+        history::Handler {}.handle(self, dir, index)
+    }
+
+    /// The sections of an ADR that changed since a git revision.
+    pub fn diff(
+        &self,
+        dir: Option<&str>,
+        index: &str,
+        rev: &str,
+    ) -> crate::Result<Vec<SectionChange>> {
+        diff::Handler {}.handle(self, dir, index, rev)
+    }
+
+    /// A Markdown changelog of the decisions made since a date or revision.
+    pub fn changelog(
+        &self,
+        dir: Option<&str>,
+        since: &str,
+        until: Option<&str>,
+        group_by: GroupBy,
+        use_git: bool,
+    ) -> crate::Result<String> {
+        changelog::Handler {}.handle(self, dir, since, until, group_by, use_git)
+    }
+}
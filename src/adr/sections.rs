@@ -0,0 +1,223 @@
+use crate::markdown::parser::{lexer, token};
+use std::ops::Range;
+
+/// A part of an ADR that starts at a heading, e.g. `## Decision`. Anything
+/// before the first heading is kept in a section with an empty heading.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub heading: String,
+    pub level: usize,
+    pub kind: SectionKind,
+    pub body: String,
+}
+
+/// The well known parts of an ADR. Any other heading is `Custom` and is
+/// identified by its text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectionKind {
+    Status,
+    Context,
+    Decision,
+    Consequences,
+    Options,
+    ProsAndCons,
+    Custom,
+}
+
+impl Section {
+    pub fn new(heading: &str, kind: SectionKind, body: &str) -> Section {
+        Section {
+            heading: heading.to_owned(),
+            level: 2,
+            kind,
+            body: body.trim().to_owned(),
+        }
+    }
+}
+
+/// A section and where it is in the document it was split from, so that it
+/// can be written back as it was.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub section: Section,
+    /// The bytes of the heading and the body, up to the next section.
+    pub range: Range<usize>,
+    /// Where the lines of the heading end and the body starts.
+    pub body_start: usize,
+}
+
+/// Splits a document into sections at every heading up to `max_level`, using
+/// the Markdown parser so that `#` lines inside code blocks are not headings.
+pub fn split(content: &str, max_level: usize) -> Vec<Section> {
+    spans(content, max_level)
+        .into_iter()
+        .map(|x| x.section)
+        .collect()
+}
+
+/// Like `split`, keeping where each section is in `content`.
+pub fn spans(content: &str, max_level: usize) -> Vec<Span> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut offsets: Vec<usize> = content
+        .split_inclusive('\n')
+        .scan(0, |acc, x| {
+            *acc += x.len();
+            Some(*acc - x.len())
+        })
+        .collect();
+    offsets.truncate(lines.len());
+    offsets.push(content.len());
+
+    let mut starts: Vec<(usize, usize, String, usize)> = lexer::lex_analysis(content)
+        .into_iter()
+        .filter_map(|x| match x.token {
+            token::Block::Heading {
+                level,
+                content,
+                style,
+            } if level <= max_level => {
+                let skip = match style {
+                    token::HeadingStyle::Atx => 1,
+                    token::HeadingStyle::Setex => 2,
+                };
+
+                Some((x.line_start, skip, text(&content), level))
+            }
+            _ => None,
+        })
+        .collect();
+
+    starts.sort_by_key(|x| x.0);
+
+    let mut res = Vec::new();
+    let first = starts.first().map_or(lines.len(), |x| x.0);
+
+    if lines[..first].iter().any(|x| !x.trim().is_empty()) {
+        res.push(Span {
+            section: Section {
+                heading: String::new(),
+                level: 0,
+                kind: SectionKind::Custom,
+                body: trim_lines(&lines[..first]),
+            },
+            range: 0..offsets[first],
+            body_start: 0,
+        });
+    }
+
+    for (i, (start, skip, heading, level)) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).map_or(lines.len(), |x| x.0);
+        let body_start = (start + skip).min(end);
+
+        res.push(Span {
+            section: Section {
+                heading: heading.clone(),
+                level: *level,
+                kind: SectionKind::Custom,
+                body: trim_lines(&lines[body_start..end]),
+            },
+            range: offsets[*start]..offsets[end],
+            body_start: offsets[body_start],
+        });
+    }
+
+    res
+}
+
+/// Flattens inline tokens back into plain text.
+pub fn text(content: &token::Text) -> String {
+    content
+        .iter()
+        .map(|x| match &x.token {
+            token::Inline::Chunk(s) | token::Inline::Code(s) => s.clone(),
+            token::Inline::Emphasis(t) | token::Inline::MoreEmphasis(t) => text(t),
+            token::Inline::Link { text: Some(t), .. } => text(t),
+            token::Inline::Link { link, .. } => link.clone().unwrap_or_default(),
+            token::Inline::Image { alt, .. } => text(alt),
+            token::Inline::LineBreak => "\n".to_owned(),
+        })
+        .collect()
+}
+
+fn trim_lines(lines: &[&str]) -> String {
+    let start = lines.iter().position(|x| !x.trim().is_empty());
+    let end = lines.iter().rposition(|x| !x.trim().is_empty());
+
+    match (start, end) {
+        (Some(s), Some(e)) => lines[s..=e]
+            .iter()
+            .map(|x| x.trim_end())
+            .collect::<Vec<&str>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        let content = "# Title\n\nDate: 2022-09-01\n\n## Status\n\nAccepted\n\n\
+            ## Decision\n\n```\n## Not a heading\n```\n\n### Detail\n\nMore\n";
+
+        let sections = split(content, 2);
+
+        assert_eq!(
+            vec![
+                Section {
+                    heading: "Title".to_owned(),
+                    level: 1,
+                    kind: SectionKind::Custom,
+                    body: "Date: 2022-09-01".to_owned(),
+                },
+                Section {
+                    heading: "Status".to_owned(),
+                    level: 2,
+                    kind: SectionKind::Custom,
+                    body: "Accepted".to_owned(),
+                },
+                Section {
+                    heading: "Decision".to_owned(),
+                    level: 2,
+                    kind: SectionKind::Custom,
+                    body: "```\n## Not a heading\n```\n\n### Detail\n\nMore".to_owned(),
+                },
+            ],
+            sections
+        );
+    }
+
+    #[test]
+    fn test_split_preamble_and_setext() {
+        let content = "Preamble\n\nContext\n-------\nSome context";
+
+        let sections = split(content, 2);
+
+        assert_eq!(2, sections.len());
+        assert_eq!("", sections[0].heading);
+        assert_eq!("Preamble", sections[0].body);
+        assert_eq!("Context", sections[1].heading);
+        assert_eq!("Some context", sections[1].body);
+    }
+
+    #[test]
+    fn test_spans() {
+        let content = "Preamble\n\nContext\n-------\nSome context\n\n## Decision\nNone";
+
+        let spans = spans(content, 2);
+
+        assert_eq!(3, spans.len());
+        assert_eq!("Preamble\n\n", &content[spans[0].range.clone()]);
+        assert_eq!(
+            "Context\n-------\n",
+            &content[spans[1].range.start..spans[1].body_start]
+        );
+        assert_eq!(
+            "Some context\n\n",
+            &content[spans[1].body_start..spans[1].range.end]
+        );
+        assert_eq!("## Decision\nNone", &content[spans[2].range.clone()]);
+    }
+}
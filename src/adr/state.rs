@@ -1,9 +1,11 @@
 use super::Directory;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
-use std::io::{Error, ErrorKind};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[schemars(rename = "AdrState")]
+#[serde(deny_unknown_fields)]
 pub struct State {
     pub dirs: Vec<Directory>,
     #[serde(skip)]
@@ -26,14 +28,45 @@ impl State {
         self.path = path
     }
 
-    pub fn get_dir(&self, name: &Option<String>) -> Result<Option<&Directory>, Error> {
+    /// Finds a directory by its name, or else the one `cwd` is in.
+    pub fn get_dir(
+        &self,
+        name: &Option<String>,
+        cwd: &std::path::Path,
+    ) -> std::io::Result<Option<&Directory>> {
         match name {
             Some(x) => self.get_named_dir(x),
-            None => self.get_current_dir(),
+            None => self.get_current_dir(cwd),
         }
     }
 
-    fn get_named_dir(&self, name: &str) -> Result<Option<&Directory>, Error> {
+    /// Like `get_dir`, but it is an error for there to be no directory.
+    pub fn require_dir(
+        &self,
+        name: &Option<String>,
+        cwd: &std::path::Path,
+    ) -> crate::Result<&Directory> {
+        if self.dirs.is_empty() {
+            return Err(crate::Error::Config(
+                "please set up an adr dir using the `init` command".to_owned(),
+            ));
+        }
+
+        match self.get_dir(name, cwd)? {
+            Some(x) => Ok(x),
+            None => match name {
+                Some(x) => Err(crate::Error::NotFound(format!(
+                    "no adr directory is named `{}`",
+                    x
+                ))),
+                None => Err(crate::Error::Config(
+                    "no adr directory could be determined, pick one with --name".to_owned(),
+                )),
+            },
+        }
+    }
+
+    fn get_named_dir(&self, name: &str) -> std::io::Result<Option<&Directory>> {
         for dir in self.dirs.iter() {
             if dir.name == name {
                 return Ok(Some(dir));
@@ -43,9 +76,7 @@ impl State {
         Ok(None)
     }
 
-    fn get_current_dir(&self) -> Result<Option<&Directory>, Error> {
-        let cwd = std::env::current_dir()?;
-
+    fn get_current_dir(&self, cwd: &std::path::Path) -> std::io::Result<Option<&Directory>> {
         for dir in self.dirs.iter() {
             let fpath = self.path.join(&dir.path);
 
@@ -61,16 +92,22 @@ impl State {
         Ok(None)
     }
 
-    pub fn validate_dir(&self, dir: &Directory) -> Result<(), Error> {
+    pub fn validate_dir(&self, dir: &Directory) -> crate::Result<()> {
         let paths: HashSet<std::path::PathBuf> = self.dirs.iter().map(|x| x.path.clone()).collect();
         let names: HashSet<&str> = self.dirs.iter().map(|x| x.name.as_ref()).collect();
 
         if paths.contains(&dir.path) {
-            return Err(Error::new(ErrorKind::AlreadyExists, "path already exists"));
+            return Err(crate::Error::Invalid(format!(
+                "an adr directory already uses the path {}",
+                dir.path.display()
+            )));
         }
 
         if names.contains(&dir.name.as_ref()) {
-            return Err(Error::new(ErrorKind::AlreadyExists, "name already exists"));
+            return Err(crate::Error::Invalid(format!(
+                "an adr directory is already named `{}`",
+                dir.name
+            )));
         }
 
         Ok(())
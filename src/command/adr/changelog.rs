@@ -0,0 +1,56 @@
+use crate::state::Context;
+use clap::{Args, ValueEnum};
+
+#[derive(ValueEnum, Debug, Clone)]
+enum GroupBy {
+    Month,
+    Release,
+}
+
+impl From<GroupBy> for crate::adr::handler::changelog::GroupBy {
+    fn from(item: GroupBy) -> crate::adr::handler::changelog::GroupBy {
+        match item {
+            GroupBy::Month => crate::adr::handler::changelog::GroupBy::Month,
+            GroupBy::Release => crate::adr::handler::changelog::GroupBy::Release,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ChangelogArgs {
+    #[clap(short, long, value_parser, help = "The name of the ADR dir")]
+    name: Option<String>,
+    #[clap(
+        long,
+        help = "Start of the window, as a YYYY-MM-DD date or a git revision"
+    )]
+    since: String,
+    #[clap(
+        long,
+        help = "End of the window, as a YYYY-MM-DD date or a git revision"
+    )]
+    until: Option<String>,
+    #[clap(short, long, value_enum, default_value = "month")]
+    group_by: GroupBy,
+    #[clap(long, help = "Only use the dates and statuses written in the ADRs")]
+    no_git: bool,
+}
+
+impl ChangelogArgs {
+    pub fn handle(self, ctx: &Context) -> crate::Result<()> {
+        let repo = crate::adr::Repository::load(ctx)?;
+        let h = crate::adr::handler::changelog::Handler {};
+        let changelog = h.handle(
+            &repo,
+            self.name.as_deref(),
+            &self.since,
+            self.until.as_deref(),
+            self.group_by.into(),
+            !self.no_git,
+        )?;
+
+        print!("{}", changelog);
+
+        Ok(())
+    }
+}
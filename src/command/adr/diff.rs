@@ -0,0 +1,30 @@
+use crate::state::Context;
+use clap::Args;
+
+#[derive(Debug, Args)]
+pub struct DiffArgs {
+    #[clap(help = "The index of the ADR")]
+    index: String,
+    #[clap(default_value = "HEAD", help = "The git revision to compare against")]
+    rev: String,
+    #[clap(short, long, value_parser, help = "The name of the ADR dir")]
+    name: Option<String>,
+}
+
+impl DiffArgs {
+    pub fn handle(self, ctx: &Context) -> crate::Result<()> {
+        let repo = crate::adr::Repository::load(ctx)?;
+        let h = crate::adr::handler::diff::Handler {};
+        let changes = h.handle(&repo, self.name.as_deref(), &self.index, &self.rev)?;
+
+        if changes.is_empty() {
+            println!("No sections changed since {}", self.rev);
+        }
+
+        for change in changes {
+            println!("{}", change);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use crate::state::Context;
+use clap::Args;
+
+#[derive(Debug, Args)]
+pub struct HistoryArgs {
+    #[clap(help = "The index of the ADR")]
+    index: String,
+    #[clap(short, long, value_parser, help = "The name of the ADR dir")]
+    name: Option<String>,
+}
+
+impl HistoryArgs {
+    pub fn handle(self, ctx: &Context) -> crate::Result<()> {
+        let repo = crate::adr::Repository::load(ctx)?;
+        let h = crate::adr::handler::history::Handler {};
+        let history = h.handle(&repo, self.name.as_deref(), &self.index)?;
+        let adr = &history.adr;
+
+        if history.entries.is_empty() {
+            println!("{}. {} has not been committed yet", adr.index, adr.title);
+            return Ok(());
+        }
+
+        println!("{}. {}\n", adr.index, adr.title);
+
+        for entry in history.entries.iter() {
+            let hash = entry.hash.get(..7).unwrap_or(&entry.hash);
+
+            println!(
+                "{}  {}  {}",
+                hash,
+                entry.date.format("%Y-%m-%d"),
+                entry.summary
+            );
+        }
+
+        if let Some(summary) = history.uncommitted {
+            println!("{:<19}  {}", "working tree", summary);
+        }
+
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use crate::state::Context;
 use clap::{Args, ValueEnum};
 
 #[derive(ValueEnum, Debug, Clone)]
@@ -15,6 +16,27 @@ impl From<IndexType> for crate::adr::IndexType {
     }
 }
 
+#[derive(ValueEnum, Debug, Clone)]
+enum Language {
+    En,
+    De,
+    Pt,
+    Es,
+    Fr,
+}
+
+impl From<Language> for crate::adr::locale::Language {
+    fn from(item: Language) -> crate::adr::locale::Language {
+        match item {
+            Language::En => crate::adr::locale::Language::En,
+            Language::De => crate::adr::locale::Language::De,
+            Language::Pt => crate::adr::locale::Language::Pt,
+            Language::Es => crate::adr::locale::Language::Es,
+            Language::Fr => crate::adr::locale::Language::Fr,
+        }
+    }
+}
+
 #[derive(Debug, Args)]
 pub struct InitArgs {
     #[clap(help = "The directory where the adrs will live")]
@@ -28,11 +50,27 @@ pub struct InitArgs {
     name: String,
     #[clap(short, long, value_enum, default_value = "timestamp")]
     index_type: IndexType,
+    #[clap(
+        short,
+        long,
+        value_enum,
+        default_value = "en",
+        help = "The language used for ADR headings and keywords"
+    )]
+    language: Language,
 }
 
 impl InitArgs {
-    pub fn handle(self) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn handle(self, ctx: &Context) -> crate::Result<()> {
         let h = crate::adr::handler::init::Handler{};
-        h.handle(&self.dir, self.name, self.index_type.into())
+        h.handle(
+            ctx,
+            &self.dir,
+            self.name,
+            self.index_type.into(),
+            self.language.into(),
+        )?;
+
+        Ok(())
     }
 }
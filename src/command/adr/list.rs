@@ -1,4 +1,8 @@
+use crate::adr::{Adr, Repository, Status};
+use crate::state::Context;
+use ansi_term::Colour;
 use clap::Args;
+use tabled::{Table, Tabled};
 
 #[derive(Debug, Args)]
 pub struct ListArgs {
@@ -7,8 +11,48 @@ pub struct ListArgs {
 }
 
 impl ListArgs {
-    pub fn handle(self) -> Result<(), Box<dyn std::error::Error>> {
-        let h = crate::adr::handler::list::Handler{};
-        h.handle(self.name)
+    pub fn handle(self, ctx: &Context) -> crate::Result<()> {
+        let repo = Repository::load(ctx)?;
+        let h = crate::adr::handler::list::Handler {};
+        let adrs = h.handle(&repo, self.name.as_deref())?;
+
+        println!("{}", table(&adrs));
+
+        Ok(())
+    }
+}
+
+/// Renders the ADRs as a table, newest first.
+fn table(adrs: &[Adr]) -> String {
+    let mut rows = Vec::new();
+
+    for adr in adrs.iter().rev() {
+        let status = match adr.status {
+            Some(Status::Proposed) => Colour::Yellow.paint("Proposed"),
+            Some(Status::Accepted) => Colour::Green.paint("Accepted"),
+            Some(Status::Rejected) => Colour::Red.paint("Rejected"),
+            Some(Status::Deprecated) => Colour::Purple.paint("Deprecated"),
+            Some(Status::Superseded) => Colour::Blue.paint("Superseded"),
+            None => Colour::Red.paint("Unknown"),
+        };
+
+        rows.push(AdrRow {
+            index: &adr.index,
+            title: &adr.title,
+            date: adr
+                .date
+                .map_or(String::new(), |x| format!("{}", x.format("%Y-%m-%d"))),
+            status: status.to_string(),
+        });
     }
+
+    Table::new(rows).with(tabled::Style::modern()).to_string()
+}
+
+#[derive(Tabled)]
+struct AdrRow<'a> {
+    index: &'a str,
+    title: &'a str,
+    date: String,
+    status: String,
 }
@@ -1,6 +1,9 @@
+use crate::state::Context;
 use clap::{Args, Subcommand};
-use std::error::Error;
 
+mod changelog;
+mod diff;
+mod history;
 mod init;
 mod list;
 mod new;
@@ -13,11 +16,14 @@ pub struct Adr {
 }
 
 impl Adr {
-    pub fn handle(self) -> Result<(), Box<dyn Error>> {
+    pub fn handle(self, ctx: &Context) -> crate::Result<()> {
         match self.command {
-            Command::Init(x) => x.handle(),
-            Command::New(x) => x.handle(),
-            Command::List(x) => x.handle(),
+            Command::Init(x) => x.handle(ctx),
+            Command::New(x) => x.handle(ctx),
+            Command::List(x) => x.handle(ctx),
+            Command::Changelog(x) => x.handle(ctx),
+            Command::History(x) => x.handle(ctx),
+            Command::Diff(x) => x.handle(ctx),
         }
     }
 }
@@ -30,4 +36,10 @@ enum Command {
     Init(init::InitArgs),
     #[clap(help = "Lists all ADRs in a directory")]
     List(list::ListArgs),
+    #[clap(help = "Generates a Markdown changelog of decisions made in a period")]
+    Changelog(changelog::ChangelogArgs),
+    #[clap(help = "Shows how an ADR evolved over its git history")]
+    History(history::HistoryArgs),
+    #[clap(help = "Shows which sections of an ADR changed since a git revision")]
+    Diff(diff::DiffArgs),
 }
@@ -1,3 +1,4 @@
+use crate::state::Context;
 use clap::Args;
 
 #[derive(Debug, Args)]
@@ -8,8 +9,11 @@ pub struct NewArgs {
 }
 
 impl NewArgs {
-    pub fn handle(self) -> Result<(), Box<dyn std::error::Error>> {
-        let h = crate::adr::handler::new::Handler{};
-        h.handle(self.dir_name, &self.name)
+    pub fn handle(self, ctx: &Context) -> crate::Result<()> {
+        let repo = crate::adr::Repository::load(ctx)?;
+        let h = crate::adr::handler::new::Handler {};
+        h.handle(&repo, self.dir_name.as_deref(), &self.name)?;
+
+        Ok(())
     }
 }
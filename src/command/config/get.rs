@@ -0,0 +1,22 @@
+use crate::state::Context;
+use clap::Args;
+
+#[derive(Debug, Args)]
+pub struct GetArgs {
+    #[clap(help = "The dotted key to read, e.g. markdown.fmt.width")]
+    key: String,
+}
+
+impl GetArgs {
+    pub fn handle(self, ctx: &Context) -> crate::Result<()> {
+        let h = crate::state::handler::get::Handler {};
+        let value = h.handle(ctx, &self.key)?;
+
+        match value.is_mapping() || value.is_sequence() {
+            true => print!("{}", serde_yaml::to_string(&value)?),
+            false => println!("{}", crate::state::encode(&value)),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+use crate::state::Context;
+use clap::Args;
+
+#[derive(Debug, Args)]
+pub struct ListArgs {
+    #[clap(
+        long = "show-origin",
+        help = "Shows the file or environment variable each value comes from"
+    )]
+    show_origin: bool,
+}
+
+impl ListArgs {
+    pub fn handle(self, ctx: &Context) -> crate::Result<()> {
+        let h = crate::state::handler::list::Handler {};
+        for setting in h.handle(ctx)? {
+            match self.show_origin {
+                true => println!("{}\t{}={}", setting.origin, setting.key, setting.value),
+                false => println!("{}={}", setting.key, setting.value),
+            }
+        }
+
+        Ok(())
+    }
+}
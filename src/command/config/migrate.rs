@@ -0,0 +1,43 @@
+use crate::adr::diff::Line;
+use crate::state::Context;
+use clap::Args;
+
+#[derive(Debug, Args)]
+pub struct MigrateArgs {
+    #[clap(long, help = "Print the changes without making them")]
+    dry_run: bool,
+}
+
+impl MigrateArgs {
+    pub fn handle(self, ctx: &Context) -> crate::Result<()> {
+        let h = crate::state::handler::migrate::Handler {};
+        let res = h.handle(ctx, self.dry_run)?;
+
+        if res.steps.is_empty() {
+            println!(
+                "{} is already at version {}",
+                res.path.display(),
+                res.version
+            );
+            return Ok(());
+        }
+
+        for step in res.steps.iter() {
+            println!("migration: {}", step);
+        }
+
+        for line in res.changes {
+            match line {
+                Line::Same(x) => println!("  {}", x),
+                Line::Added(x) => println!("+ {}", x),
+                Line::Removed(x) => println!("- {}", x),
+            }
+        }
+
+        if !self.dry_run {
+            println!("{} migrated to version {}", res.path.display(), res.version);
+        }
+
+        Ok(())
+    }
+}
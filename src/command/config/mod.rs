@@ -0,0 +1,49 @@
+use crate::state::Context;
+mod get;
+mod list;
+mod migrate;
+mod schema;
+mod set;
+mod unset;
+mod validate;
+
+use clap::{Args, Subcommand};
+
+#[derive(Debug, Args)]
+#[clap(args_conflicts_with_subcommands = true)]
+pub struct Config {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+impl Config {
+    pub fn handle(self, ctx: &Context) -> crate::Result<()> {
+        match self.command {
+            Command::Get(x) => x.handle(ctx),
+            Command::Set(x) => x.handle(ctx),
+            Command::Unset(x) => x.handle(ctx),
+            Command::List(x) => x.handle(ctx),
+            Command::Validate(x) => x.handle(ctx),
+            Command::Migrate(x) => x.handle(ctx),
+            Command::Schema(x) => x.handle(ctx),
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    #[clap(help = "Prints the value of a setting")]
+    Get(get::GetArgs),
+    #[clap(help = "Changes a setting in the .docula file")]
+    Set(set::SetArgs),
+    #[clap(help = "Removes a setting from the .docula file")]
+    Unset(unset::UnsetArgs),
+    #[clap(help = "Lists every setting with its value")]
+    List(list::ListArgs),
+    #[clap(help = "Checks that the .docula file can be loaded")]
+    Validate(validate::ValidateArgs),
+    #[clap(help = "Upgrades the .docula file to the current version")]
+    Migrate(migrate::MigrateArgs),
+    #[clap(help = "Prints the JSON Schema of the .docula file")]
+    Schema(schema::SchemaArgs),
+}
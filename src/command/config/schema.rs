@@ -0,0 +1,14 @@
+use crate::state::Context;
+use clap::Args;
+
+#[derive(Debug, Args)]
+pub struct SchemaArgs {}
+
+impl SchemaArgs {
+    pub fn handle(self, _ctx: &Context) -> crate::Result<()> {
+        let h = crate::state::handler::schema::Handler {};
+        println!("{}", h.handle()?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+use crate::state::Context;
+use clap::Args;
+
+#[derive(Debug, Args)]
+pub struct SetArgs {
+    #[clap(help = "The dotted key to change, e.g. adr.dirs.main.index")]
+    key: String,
+    #[clap(help = "The new value")]
+    value: String,
+}
+
+impl SetArgs {
+    pub fn handle(self, ctx: &Context) -> crate::Result<()> {
+        let h = crate::state::handler::set::Handler {};
+        let doc = h.handle(ctx, &self.key, &self.value)?;
+
+        if doc.rewritten {
+            eprintln!(
+                "warning: {} had to be rewritten, comments were not kept",
+                doc.path.display()
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use crate::state::Context;
+use clap::Args;
+
+#[derive(Debug, Args)]
+pub struct UnsetArgs {
+    #[clap(help = "The dotted key to remove")]
+    key: String,
+}
+
+impl UnsetArgs {
+    pub fn handle(self, ctx: &Context) -> crate::Result<()> {
+        let h = crate::state::handler::unset::Handler {};
+        let doc = h.handle(ctx, &self.key)?;
+
+        if doc.rewritten {
+            eprintln!(
+                "warning: {} had to be rewritten, comments were not kept",
+                doc.path.display()
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+use crate::state::Context;
+use crate::Error;
+use clap::Args;
+
+#[derive(Debug, Args)]
+pub struct ValidateArgs {}
+
+impl ValidateArgs {
+    pub fn handle(self, ctx: &Context) -> crate::Result<()> {
+        let h = crate::state::handler::validate::Handler {};
+        let res = h.handle(ctx)?;
+
+        for problem in res.problems.iter() {
+            println!("{}: {}", res.path.display(), problem);
+        }
+
+        if !res.problems.is_empty() {
+            Err(Error::Config(format!(
+                "{} problem(s) found in {}",
+                res.problems.len(),
+                res.path.display()
+            )))?;
+        }
+
+        println!("{} is valid", res.path.display());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use crate::state::Context;
+use clap::Args;
+
+#[derive(Debug, Args)]
+pub struct InitArgs {
+    #[clap(long, help = "Also creates a directory of ADRs at this path")]
+    adr: Option<std::path::PathBuf>,
+    #[clap(
+        long,
+        requires = "adr",
+        help = "The name of the ADR directory, its folder name by default"
+    )]
+    adr_name: Option<String>,
+    #[clap(long, help = "Creates docs/README.md")]
+    readme: bool,
+    #[clap(long, help = "Turns on every lint rule in the new .docula")]
+    lint: bool,
+    #[clap(
+        long,
+        help = "Installs a git pre-commit hook that lints staged Markdown"
+    )]
+    hook: bool,
+}
+
+impl InitArgs {
+    pub fn handle(self, ctx: &Context) -> crate::Result<()> {
+        let h = crate::state::handler::init::Handler {};
+        let setup = h.handle(
+            ctx,
+            self.adr,
+            self.adr_name,
+            self.readme,
+            self.lint,
+            self.hook,
+        )?;
+
+        if setup.detected > 0 {
+            println!(
+                "found {} Markdown file(s), settings are based on them",
+                setup.detected
+            );
+        }
+
+        for path in setup.created {
+            println!("created {}", path.display());
+        }
+
+        for path in setup.kept {
+            println!("kept the existing {}", path.display());
+        }
+
+        Ok(())
+    }
+}
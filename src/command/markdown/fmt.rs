@@ -1,17 +1,33 @@
+use super::Paths;
+use crate::state::Context;
 use clap::Args;
 
 #[derive(Debug, Args)]
 pub struct FmtArgs {
-    path: std::path::PathBuf,
-    #[clap(short, long, help = "Recursively search and fmt markdown")]
-    recursive: bool,
+    #[clap(flatten)]
+    paths: Paths,
     #[clap(long, help = "Print the output without making it")]
     dry_run: bool,
 }
 
 impl FmtArgs {
-    pub fn handle(self) -> Result<(), Box<dyn std::error::Error>> {
-        let h = crate::markdown::handler::fmt::Handler{};
-        h.handle(&self.path)
+    pub fn handle(self, ctx: &Context) -> crate::Result<()> {
+        let h = crate::markdown::handler::fmt::Handler {};
+        let formatted = h.handle(ctx, &self.paths.into())?;
+        let headers = formatted.len() > 1;
+
+        for (i, doc) in formatted.iter().enumerate() {
+            if headers {
+                if i > 0 {
+                    println!();
+                }
+
+                println!("==> {} <==", doc.input.name(ctx).display());
+            }
+
+            println!("{}", doc.content);
+        }
+
+        Ok(())
     }
 }
@@ -0,0 +1,55 @@
+use super::Paths;
+use crate::state::Context;
+use clap::{Args, ValueEnum};
+use std::path::PathBuf;
+
+#[derive(ValueEnum, Debug, Clone)]
+enum Format {
+    Text,
+    Dot,
+    Json,
+}
+
+impl From<Format> for crate::markdown::graph::Format {
+    fn from(item: Format) -> crate::markdown::graph::Format {
+        match item {
+            Format::Text => crate::markdown::graph::Format::Text,
+            Format::Dot => crate::markdown::graph::Format::Dot,
+            Format::Json => crate::markdown::graph::Format::Json,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct GraphArgs {
+    #[clap(flatten)]
+    paths: Paths,
+    #[clap(
+        long,
+        value_name = "FILE",
+        help = "A document readers start from, instead of those in the settings, can be repeated"
+    )]
+    entry: Vec<PathBuf>,
+    #[clap(
+        short,
+        long,
+        value_enum,
+        default_value = "text",
+        help = "Prints orphans, dead ends and cycles, or the whole graph as DOT or JSON"
+    )]
+    format: Format,
+}
+
+impl GraphArgs {
+    pub fn handle(self, ctx: &Context) -> crate::Result<()> {
+        let h = crate::markdown::handler::graph::Handler {};
+        let graph = h.handle(ctx, &self.paths.into(), &self.entry)?;
+
+        print!(
+            "{}",
+            crate::markdown::graph::render(self.format.into(), &graph)
+        );
+
+        Ok(())
+    }
+}
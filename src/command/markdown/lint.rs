@@ -1,14 +1,117 @@
-use clap::Args;
+use super::Paths;
+use crate::markdown::linter::BrokenLinks;
+use crate::markdown::state::Severity;
+use crate::markdown::Linter;
+use crate::state::Context;
+use clap::{Args, ValueEnum};
+
+#[derive(ValueEnum, Debug, Clone)]
+enum Format {
+    Pretty,
+    Json,
+    Sarif,
+    Checkstyle,
+    Junit,
+    Github,
+}
+
+impl From<Format> for crate::markdown::report::Format {
+    fn from(item: Format) -> crate::markdown::report::Format {
+        match item {
+            Format::Pretty => crate::markdown::report::Format::Pretty,
+            Format::Json => crate::markdown::report::Format::Json,
+            Format::Sarif => crate::markdown::report::Format::Sarif,
+            Format::Checkstyle => crate::markdown::report::Format::Checkstyle,
+            Format::Junit => crate::markdown::report::Format::Junit,
+            Format::Github => crate::markdown::report::Format::Github,
+        }
+    }
+}
 
 #[derive(Debug, Args)]
 pub struct LintArgs {
-    path: std::path::PathBuf,
-    #[clap(short, long, help = "Recursively search and fmt markdown")]
-    recursive: bool,
+    #[clap(flatten)]
+    paths: Paths,
+    #[clap(long, help = "Lists every rule with its ID, severity and options")]
+    list_rules: bool,
+    #[clap(long, help = "Applies the fixes rules suggest and rewrites the files")]
+    fix: bool,
+    #[clap(long, help = "Also requests links to other sites to check they work")]
+    check_external: bool,
+    #[clap(long, help = "Also reports images no Markdown file in the project refers to")]
+    unused_assets: bool,
+    #[clap(
+        short,
+        long,
+        value_enum,
+        default_value = "pretty",
+        help = "How to print the issues"
+    )]
+    format: Format,
 }
 
 impl LintArgs {
-    pub fn handle(self) -> Result<(), Box<dyn std::error::Error>> {
-        Ok(())
+    pub fn handle(self, ctx: &Context) -> crate::Result<()> {
+        if self.list_rules {
+            list_rules();
+            return Ok(());
+        }
+
+        let mut linters = crate::markdown::linter::all();
+
+        if self.check_external {
+            for linter in linters.iter_mut() {
+                if linter.id() == BrokenLinks::default().id() {
+                    *linter = Box::new(BrokenLinks::external());
+                }
+            }
+        }
+
+        let h = crate::markdown::handler::lint::Handler::new(linters);
+        let report = h.handle(ctx, &self.paths.into(), self.fix, self.unused_assets)?;
+
+        for (path, count) in report.fixed.iter() {
+            eprintln!("fixed {} issue(s) in {}", count, path.display());
+        }
+
+        print!(
+            "{}",
+            crate::markdown::report::render(
+                self.format.into(),
+                &report,
+                &crate::markdown::linter::all()
+            )
+        );
+
+        let errors = report
+            .findings
+            .iter()
+            .filter(|x| x.severity == Severity::Error)
+            .count();
+
+        match errors {
+            0 => Ok(()),
+            _ => Err(crate::Error::Lint { issues: errors }),
+        }
+    }
+}
+
+fn list_rules() {
+    let linters = crate::markdown::linter::all();
+    let width = linters.iter().map(|x| x.id().len()).max().unwrap_or(0);
+
+    for linter in linters {
+        println!(
+            "{:<width$}  {:<7}  {:<7}  {}",
+            linter.id(),
+            linter.default_severity().to_string(),
+            if linter.fixable() { "fixable" } else { "" },
+            linter.description(),
+            width = width
+        );
+
+        for (key, value) in linter.options() {
+            println!("    {}: {}", key, value);
+        }
     }
 }
@@ -1,7 +1,11 @@
+use crate::state::Context;
 mod fmt;
+mod graph;
 mod lint;
 
+use crate::markdown::files::Selection;
 use clap::{Args, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Debug, Args)]
 #[clap(args_conflicts_with_subcommands = true)]
@@ -11,10 +15,11 @@ pub struct Markdown {
 }
 
 impl Markdown {
-    pub fn handle(self) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn handle(self, ctx: &Context) -> crate::Result<()> {
         match self.command {
-            Command::Fmt(x) => x.handle(),
-            Command::Lint(x) => x.handle(),
+            Command::Fmt(x) => x.handle(ctx),
+            Command::Graph(x) => x.handle(ctx),
+            Command::Lint(x) => x.handle(ctx),
         }
     }
 }
@@ -22,5 +27,38 @@ impl Markdown {
 #[derive(Debug, Subcommand)]
 enum Command {
     Fmt(fmt::FmtArgs),
+    Graph(graph::GraphArgs),
     Lint(lint::LintArgs),
 }
+
+/// The documents a command works on.
+#[derive(Debug, Args)]
+struct Paths {
+    #[clap(help = "Markdown files or directories, - reads stdin")]
+    paths: Vec<PathBuf>,
+    #[clap(short, long, help = "Searches directories recursively")]
+    recursive: bool,
+    #[clap(
+        long,
+        value_name = "GLOB",
+        help = "Only picks files matching the glob, can be repeated"
+    )]
+    include: Vec<String>,
+    #[clap(
+        long,
+        value_name = "GLOB",
+        help = "Leaves out files and directories matching the glob, can be repeated"
+    )]
+    exclude: Vec<String>,
+}
+
+impl From<Paths> for Selection {
+    fn from(x: Paths) -> Selection {
+        Selection {
+            paths: x.paths,
+            recursive: x.recursive,
+            include: x.include,
+            exclude: x.exclude,
+        }
+    }
+}
@@ -1,19 +1,27 @@
+use crate::state::Context;
 use clap::Subcommand;
 
 mod adr;
+mod config;
+mod init;
 mod markdown;
 
 #[derive(Debug, Subcommand)]
 pub enum Command {
     Adr(adr::Adr),
+    Config(config::Config),
+    #[clap(about = "Sets up docula in the current directory")]
+    Init(init::InitArgs),
     Markdown(markdown::Markdown),
 }
 
 impl Command {
-    pub fn handle(self) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn handle(self, ctx: &Context) -> crate::Result<()> {
         match self {
-            Command::Adr(x) => x.handle(),
-            Command::Markdown(x) => x.handle(),
+            Command::Adr(x) => x.handle(ctx),
+            Command::Config(x) => x.handle(ctx),
+            Command::Init(x) => x.handle(ctx),
+            Command::Markdown(x) => x.handle(ctx),
         }
     }
 }
@@ -0,0 +1,140 @@
+use crate::state::ParseError;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Every way docula can fail. The exit code tells apart documents that have
+/// problems from docula itself being unable to do its job.
+#[derive(Debug)]
+pub enum Error {
+    /// The settings don't allow the command to run, e.g. no ADR directory
+    /// has been set up yet.
+    Config(String),
+    /// The arguments or the input don't make sense.
+    Invalid(String),
+    /// Something that was asked for doesn't exist, e.g. an ADR index.
+    NotFound(String),
+    /// A file could not be read or written.
+    Io {
+        path: Option<PathBuf>,
+        source: std::io::Error,
+    },
+    /// A `.docula` file could not be understood.
+    Parse(ParseError),
+    /// Data could not be converted, with the error that caused it.
+    Encoding {
+        msg: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    /// Linting worked and found problems in the documents.
+    Lint { issues: usize },
+}
+
+/// Exit code for problems found in the documents.
+pub const EXIT_ISSUES: i32 = 1;
+/// Exit code for docula failing to do its job.
+pub const EXIT_FAILURE: i32 = 2;
+
+impl Error {
+    pub fn io(path: &Path, source: std::io::Error) -> Error {
+        Error::Io {
+            path: Some(path.to_path_buf()),
+            source,
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Lint { .. } => EXIT_ISSUES,
+            _ => EXIT_FAILURE,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Config(x) | Error::Invalid(x) | Error::NotFound(x) => write!(f, "{}", x),
+            Error::Io { path: None, source } => write!(f, "{}", source),
+            Error::Io {
+                path: Some(path),
+                source,
+            } => write!(f, "{}: {}", path.display(), source),
+            Error::Parse(x) => write!(f, "{}", x),
+            Error::Encoding { msg, .. } => write!(f, "{}", msg),
+            Error::Lint { issues } => write!(f, "{} issue(s) found", issues),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => source.source(),
+            Error::Encoding { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(source: std::io::Error) -> Error {
+        Error::Io { path: None, source }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Error {
+        Error::Parse(e)
+    }
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(e: serde_yaml::Error) -> Error {
+        Error::Encoding {
+            msg: "the settings could not be converted".to_owned(),
+            source: Box::new(e),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Error {
+        Error::Encoding {
+            msg: "could not encode JSON".to_owned(),
+            source: Box::new(e),
+        }
+    }
+}
+
+/// Attaches the path to I/O errors, so the message says which file failed.
+pub trait IoContext<T> {
+    fn with_path(self, path: &Path) -> Result<T>;
+}
+
+impl<T> IoContext<T> for std::io::Result<T> {
+    fn with_path(self, path: &Path) -> Result<T> {
+        self.map_err(|e| Error::io(path, e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_display_and_exit_code() {
+        let err = std::fs::read_to_string("/does/not/exist")
+            .with_path(Path::new("/does/not/exist"))
+            .unwrap_err();
+
+        assert!(err.to_string().starts_with("/does/not/exist: "));
+        assert_eq!(EXIT_FAILURE, err.exit_code());
+
+        let err = Error::Lint { issues: 3 };
+
+        assert_eq!("3 issue(s) found", err.to_string());
+        assert_eq!(EXIT_ISSUES, err.exit_code());
+    }
+}
@@ -1,21 +1,62 @@
+//! docula manages documentation kept next to the code: Architecture Decision
+//! Records in [`adr`], Markdown documents in [`markdown`] and the `.docula`
+//! settings in [`state`]. The `docula` binary is a thin layer over this
+//! library, everything it prints is computed here first.
+
 use clap::Parser;
-use std::error::Error;
 
 pub mod adr;
 mod command;
+mod error;
 pub mod markdown;
 pub mod state;
 
+pub use error::{Error, IoContext, Result, EXIT_FAILURE, EXIT_ISSUES};
+
 #[derive(Debug, Parser)]
 #[clap(name = "docula")]
 #[clap(about = "An all in one toolbox for managing Documentation as Code", long_about=None)]
 pub struct Cli {
     #[clap(subcommand)]
     command: command::Command,
+    #[clap(
+        short,
+        long,
+        global = true,
+        help = "Shows what caused an error, not just the error itself"
+    )]
+    pub verbose: bool,
+    #[clap(
+        long,
+        global = true,
+        value_name = "FILE",
+        help = "Reads the settings from this file instead of searching for .docula files"
+    )]
+    config: Option<std::path::PathBuf>,
+    #[clap(
+        long,
+        global = true,
+        value_name = "DIR",
+        help = "The project root, no .docula file above it is read"
+    )]
+    root: Option<std::path::PathBuf>,
+    #[clap(
+        long,
+        global = true,
+        value_name = "DIR",
+        help = "Runs as if docula was started in this directory"
+    )]
+    cwd: Option<std::path::PathBuf>,
 }
 
 impl Cli {
-    pub fn handle(self) -> Result<(), Box<dyn Error>> {
-        self.command.handle()
+    pub fn handle(self) -> Result<()> {
+        let ctx = state::Context::from_options(
+            self.cwd.as_deref(),
+            self.root.as_deref(),
+            self.config.as_deref(),
+        )?;
+
+        self.command.handle(&ctx)
     }
 }
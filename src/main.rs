@@ -1,7 +1,26 @@
 use clap::Parser;
 use std::error::Error;
 
-fn main() -> Result<(), Box<dyn Error>> {
+fn main() {
     let args = docula::Cli::parse();
-    args.handle()
+    let verbose = args.verbose;
+
+    if let Err(e) = args.handle() {
+        eprintln!("error: {}", e);
+
+        let mut source = e.source();
+
+        if source.is_some() && !verbose {
+            eprintln!("  run again with --verbose to see what caused it");
+        }
+
+        if verbose {
+            while let Some(x) = source {
+                eprintln!("  caused by: {}", x);
+                source = x.source();
+            }
+        }
+
+        std::process::exit(e.exit_code());
+    }
 }
@@ -0,0 +1,108 @@
+//! Finds images in the project that no Markdown document shows or links to,
+//! which tend to pile up as documents are rewritten.
+
+use super::handler::lint::{Finding, Issue, Source};
+use super::linter::{resolve, split};
+use super::parser::token::{self, Inline};
+use super::state::Severity;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// The rule unused images are reported under.
+pub const UNUSED: &str = "unused-assets";
+
+lazy_static! {
+    static ref IMG_SRC: Regex =
+        Regex::new(r#"(?i)<img\b[^>]*?\ssrc\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap();
+}
+
+/// The local files a document refers to, through images, links and HTML
+/// `<img>` tags.
+pub fn references(source: &Source) -> Vec<PathBuf> {
+    let links = token::inlines(&source.document)
+        .into_iter()
+        .filter_map(|x| match &x.token {
+            Inline::Image {
+                link: Some(link), ..
+            }
+            | Inline::Link {
+                link: Some(link), ..
+            } => Some(link.as_str()),
+            _ => None,
+        });
+
+    let html = IMG_SRC
+        .captures_iter(source.text)
+        .filter_map(|x| x.get(1).or_else(|| x.get(2)))
+        .map(|x| x.as_str());
+
+    links
+        .chain(html)
+        .filter_map(split)
+        .filter_map(|(path, _)| resolve(source, path))
+        .map(|x| x.canonicalize().unwrap_or(x))
+        .collect()
+}
+
+/// Reports the images that aren't among the references. `name` gives the
+/// path to show for an image.
+pub fn unused(
+    images: Vec<PathBuf>,
+    references: &HashSet<PathBuf>,
+    name: impl Fn(&PathBuf) -> PathBuf,
+) -> Vec<Finding> {
+    images
+        .into_iter()
+        .filter(|x| !references.contains(x))
+        .map(|x| Finding {
+            path: name(&x),
+            rule: UNUSED,
+            severity: Severity::Warning,
+            issue: Issue {
+                line_start: 0,
+                line_end: 0,
+                col_start: 0,
+                col_end: 0,
+                content: String::new(),
+                msg: "Not referenced by any Markdown file".to_owned(),
+                edits: Vec::new(),
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_references() {
+        let text = "![a](img/a.png) [b](docs/b.svg#x) [c](https://a.io/c.png)\n\n<img alt=\"d\" src='img/d.gif'>\n";
+        let source = Source::new(text).at(Some(PathBuf::from("/p/README.md")), None);
+
+        assert_eq!(
+            vec![
+                PathBuf::from("/p/img/a.png"),
+                PathBuf::from("/p/docs/b.svg"),
+                PathBuf::from("/p/img/d.gif"),
+            ],
+            references(&source)
+        );
+    }
+
+    #[test]
+    fn test_unused() {
+        let references = HashSet::from([PathBuf::from("/p/a.png")]);
+        let res = unused(
+            vec![PathBuf::from("/p/a.png"), PathBuf::from("/p/b.png")],
+            &references,
+            |x| x.strip_prefix("/p").unwrap().to_path_buf(),
+        );
+
+        assert_eq!(1, res.len());
+        assert_eq!(PathBuf::from("b.png"), res[0].path);
+        assert_eq!(UNUSED, res[0].rule);
+    }
+}
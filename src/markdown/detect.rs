@@ -0,0 +1,163 @@
+//! Guesses the conventions an existing set of Markdown documents follows, so
+//! that new settings match how the documents are already written.
+
+use super::parser::{lexer, token};
+use super::state::HeadingStyle;
+use std::path::{Path, PathBuf};
+
+/// Directories that never hold documentation worth looking at.
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "vendor"];
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Conventions {
+    pub files: usize,
+    pub width: Option<usize>,
+    pub heading_style: Option<HeadingStyle>,
+}
+
+pub fn detect(root: &Path) -> Conventions {
+    let mut files = Vec::new();
+    find_markdown(root, &mut files);
+
+    let contents: Vec<String> = files
+        .iter()
+        .filter_map(|x| std::fs::read_to_string(x).ok())
+        .collect();
+
+    Conventions {
+        files: contents.len(),
+        width: width(&contents),
+        heading_style: heading_style(&contents),
+    }
+}
+
+fn find_markdown(dir: &Path, res: &mut Vec<PathBuf>) {
+    let entries = match std::fs::read_dir(dir) {
+        Err(_) => return,
+        Ok(x) => x,
+    };
+
+    let mut paths: Vec<PathBuf> = entries.filter_map(|x| x.ok()).map(|x| x.path()).collect();
+    paths.sort();
+
+    for path in paths {
+        let name = path.file_name().and_then(|x| x.to_str()).unwrap_or("");
+
+        if path.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name) {
+                find_markdown(&path, res);
+            }
+        } else if path.extension().and_then(|x| x.to_str()) == Some("md") {
+            res.push(path);
+        }
+    }
+}
+
+/// The width prose is wrapped at: the length that nearly all lines stay
+/// within, rounded up to a multiple of ten. Documents that are not wrapped
+/// at all are better served by the default.
+fn width(contents: &[String]) -> Option<usize> {
+    let mut lengths: Vec<usize> = contents.iter().flat_map(|x| prose_lengths(x)).collect();
+
+    if lengths.len() < 10 {
+        return None;
+    }
+
+    lengths.sort_unstable();
+
+    let p95 = lengths[(lengths.len() * 95 / 100).min(lengths.len() - 1)];
+
+    match p95 {
+        0..=80 => Some(80),
+        81..=120 => Some(p95.div_ceil(10) * 10),
+        _ => None,
+    }
+}
+
+fn prose_lengths(content: &str) -> Vec<usize> {
+    let mut res = Vec::new();
+    let mut in_code_block = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+
+        if in_code_block || trimmed.is_empty() {
+            continue;
+        }
+
+        if trimmed.starts_with('|') || trimmed.starts_with('#') || line.starts_with("    ") {
+            continue;
+        }
+
+        res.push(line.chars().count());
+    }
+
+    res
+}
+
+fn heading_style(contents: &[String]) -> Option<HeadingStyle> {
+    let (mut atx, mut setext) = (0, 0);
+
+    for content in contents {
+        for block in lexer::lex_analysis(content) {
+            // Setext headings only exist for the first two levels, so deeper
+            // headings say nothing about the preference.
+            if let token::Block::Heading { level, style, .. } = block.token {
+                match style {
+                    token::HeadingStyle::Atx if level <= 2 => atx += 1,
+                    token::HeadingStyle::Setex => setext += 1,
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    match (atx, setext) {
+        (0, 0) => None,
+        (a, s) if s > a => Some(HeadingStyle::Setext),
+        _ => Some(HeadingStyle::Atx),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_width() {
+        let wrapped = "word ".repeat(20).trim().to_owned() + "\n";
+        let doc = format!("# Title\n\n{}", wrapped.repeat(20));
+
+        // 99 characters per line wraps at 100.
+        assert_eq!(Some(100), width(&[doc]));
+
+        let doc = "short line\n".repeat(20);
+        assert_eq!(Some(80), width(&[doc]));
+
+        let doc = format!("{}\n", "word ".repeat(60)).repeat(20);
+        assert_eq!(None, width(&[doc]));
+
+        assert_eq!(None, width(&["too few\n".to_owned()]));
+    }
+
+    #[test]
+    fn test_heading_style() {
+        let setext = "Title\n=====\n\nText\n\nPart\n----\n\n### Detail\n".to_owned();
+        let atx = "# Title\n\nText\n".to_owned();
+
+        assert_eq!(
+            Some(HeadingStyle::Setext),
+            heading_style(std::slice::from_ref(&setext))
+        );
+        assert_eq!(
+            Some(HeadingStyle::Atx),
+            heading_style(&[atx.clone(), atx, setext])
+        );
+        assert_eq!(None, heading_style(&["Text\n".to_owned()]));
+    }
+}
@@ -0,0 +1,331 @@
+//! Finds the Markdown documents that `lint` and `fmt` work on, and the
+//! images next to them. Directories are searched in parallel, skipping
+//! hidden files and whatever `.gitignore` and `.doculaignore` files leave
+//! out.
+
+use crate::state::Context;
+use crate::{Error, IoContext};
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::{WalkBuilder, WalkState};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const EXTENSIONS: &[&str] = &["md", "markdown"];
+
+/// The files that count as images when looking for unused ones.
+const IMAGE_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "svg", "webp", "bmp", "ico", "avif",
+];
+
+/// The name of docula's own ignore file, written like a `.gitignore`.
+pub const IGNORE_FILE: &str = ".doculaignore";
+
+/// The paths given on the command line and how to search them.
+#[derive(Debug, Default)]
+pub struct Selection {
+    /// Files and directories, with `-` standing for stdin.
+    pub paths: Vec<PathBuf>,
+    /// Whether to search below the directories themselves.
+    pub recursive: bool,
+    /// Globs a file must match to be picked, relative to the working
+    /// directory. Empty picks every file.
+    pub include: Vec<String>,
+    /// Globs for files and directories to leave out.
+    pub exclude: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    Stdin,
+    File(PathBuf),
+}
+
+impl Input {
+    /// The name to show for the input, relative to the working directory
+    /// where possible.
+    pub fn name(&self, ctx: &Context) -> PathBuf {
+        match self {
+            Input::Stdin => PathBuf::from("<stdin>"),
+            Input::File(x) => x.strip_prefix(&ctx.cwd).unwrap_or(x).to_path_buf(),
+        }
+    }
+
+    /// The directory whose settings apply to the input.
+    pub fn dir(&self, ctx: &Context) -> PathBuf {
+        match self {
+            Input::Stdin => ctx.cwd.clone(),
+            Input::File(x) => x.parent().unwrap_or(x).to_path_buf(),
+        }
+    }
+
+    pub fn read(&self) -> crate::Result<String> {
+        match self {
+            Input::Stdin => Ok(std::io::read_to_string(std::io::stdin())?),
+            Input::File(x) => std::fs::read_to_string(x).with_path(x),
+        }
+    }
+}
+
+/// Resolves the selection into inputs, in the order they were given and
+/// sorted within each directory. Paths named directly are always used, the
+/// ignore files only apply to what is found below them. A file is picked
+/// once however many of the paths lead to it, and stdin can be given once.
+pub fn find(ctx: &Context, selection: &Selection) -> crate::Result<Vec<Input>> {
+    let include = globs(ctx, &selection.include)?;
+    let exclude = globs(ctx, &selection.exclude)?;
+
+    if selection.paths.is_empty() {
+        Err(Error::Invalid(
+            "no files or directories were given, use - to read stdin".to_owned(),
+        ))?;
+    }
+
+    let mut res = Vec::new();
+    let mut seen = HashSet::new();
+
+    for path in selection.paths.iter() {
+        if path == Path::new("-") {
+            if res.contains(&Input::Stdin) {
+                Err(Error::Invalid("stdin can only be read once".to_owned()))?;
+            }
+
+            res.push(Input::Stdin);
+            continue;
+        }
+
+        let path = ctx.resolve(path);
+        let path = path.canonicalize().with_path(&path)?;
+
+        if !path.is_dir() {
+            if !is_markdown(&path) {
+                Err(Error::Invalid(format!(
+                    "{} is not a Markdown file",
+                    path.display()
+                )))?;
+            }
+
+            if seen.insert(path.clone()) {
+                res.push(Input::File(path));
+            }
+
+            continue;
+        }
+
+        let mut found = walk(&path, selection.recursive, &include, &exclude, is_markdown)?;
+        found.sort();
+
+        for x in found {
+            if seen.insert(x.clone()) {
+                res.push(Input::File(x));
+            }
+        }
+    }
+
+    Ok(res)
+}
+
+/// Finds the images in the selected directories, the same way `find` finds
+/// documents in them. Files named directly are left out.
+pub fn images(ctx: &Context, selection: &Selection) -> crate::Result<Vec<PathBuf>> {
+    let include = globs(ctx, &selection.include)?;
+    let exclude = globs(ctx, &selection.exclude)?;
+    let mut res = Vec::new();
+
+    for path in selection.paths.iter().filter(|x| *x != Path::new("-")) {
+        let path = ctx.resolve(path);
+        let path = path.canonicalize().with_path(&path)?;
+
+        if path.is_dir() {
+            let mut found = walk(&path, selection.recursive, &include, &exclude, is_image)?;
+            found.sort();
+
+            for x in found {
+                if !res.contains(&x) {
+                    res.push(x);
+                }
+            }
+        }
+    }
+
+    Ok(res)
+}
+
+/// Every Markdown document below the directory, whatever was selected, so
+/// that images can be checked against all of them.
+pub fn documents(dir: &Path) -> crate::Result<Vec<PathBuf>> {
+    walk(dir, true, &Override::empty(), &Override::empty(), is_markdown)
+}
+
+fn walk(
+    dir: &Path,
+    recursive: bool,
+    include: &Override,
+    exclude: &Override,
+    matches: fn(&Path) -> bool,
+) -> crate::Result<Vec<PathBuf>> {
+    let exclude = exclude.clone();
+    let found = Mutex::new(Vec::new());
+    let errors = Mutex::new(Vec::new());
+
+    WalkBuilder::new(dir)
+        .max_depth(if recursive { None } else { Some(1) })
+        .require_git(false)
+        .add_custom_ignore_filename(IGNORE_FILE)
+        .filter_entry(move |x| {
+            let is_dir = x.file_type().is_some_and(|x| x.is_dir());
+            !exclude.matched(x.path(), is_dir).is_whitelist()
+        })
+        .build_parallel()
+        .run(|| {
+            Box::new(|entry| {
+                match entry {
+                    Err(e) => errors.lock().unwrap().push(e),
+                    Ok(x) => {
+                        let path = x.path();
+
+                        if x.file_type().is_some_and(|x| x.is_file())
+                            && matches(path)
+                            && (include.is_empty() || include.matched(path, false).is_whitelist())
+                        {
+                            found.lock().unwrap().push(path.to_path_buf());
+                        }
+                    }
+                }
+
+                WalkState::Continue
+            })
+        });
+
+    if let Some(e) = errors.into_inner().unwrap().into_iter().next() {
+        return Err(match e.into_io_error() {
+            Some(x) => Error::io(dir, x),
+            None => Error::Invalid(format!("{} could not be searched", dir.display())),
+        });
+    }
+
+    Ok(found.into_inner().unwrap())
+}
+
+fn globs(ctx: &Context, patterns: &[String]) -> crate::Result<Override> {
+    let mut builder = OverrideBuilder::new(&ctx.cwd);
+
+    for pattern in patterns {
+        builder
+            .add(pattern)
+            .map_err(|e| Error::Invalid(format!("invalid glob `{}`: {}", pattern, e)))?;
+    }
+
+    builder.build().map_err(|e| Error::Invalid(e.to_string()))
+}
+
+fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .and_then(|x| x.to_str())
+        .is_some_and(|x| EXTENSIONS.contains(&x))
+}
+
+/// Whether the file is an image, going by its extension in any case, as
+/// cameras and editors like to save `.PNG` and `.JPG`.
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|x| x.to_str())
+        .is_some_and(|x| IMAGE_EXTENSIONS.contains(&x.to_lowercase().as_str()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find() {
+        let tmp = tempdir::TempDir::new("files_test").unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+
+        for path in [
+            "a.md",
+            "b.markdown",
+            "c.txt",
+            "docs/d.md",
+            "docs/drafts/e.md",
+            "build/f.md",
+            "vendor/g.md",
+            ".hidden/h.md",
+        ] {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "# Title\n").unwrap();
+        }
+
+        std::fs::write(root.join(".gitignore"), "build/\n").unwrap();
+        std::fs::write(root.join(IGNORE_FILE), "vendor/\n").unwrap();
+
+        let ctx = Context::new(root.clone());
+        let names = |selection: &Selection| -> Vec<String> {
+            find(&ctx, selection)
+                .unwrap()
+                .iter()
+                .map(|x| x.name(&ctx).to_string_lossy().into_owned())
+                .collect()
+        };
+
+        let mut selection = Selection {
+            paths: vec![PathBuf::from(".")],
+            ..Selection::default()
+        };
+
+        assert_eq!(vec!["a.md", "b.markdown"], names(&selection));
+
+        selection.recursive = true;
+
+        assert_eq!(
+            vec!["a.md", "b.markdown", "docs/d.md", "docs/drafts/e.md"],
+            names(&selection)
+        );
+
+        selection.exclude = vec!["drafts".to_owned()];
+        selection.include = vec!["*.md".to_owned()];
+
+        assert_eq!(vec!["a.md", "docs/d.md"], names(&selection));
+
+        let selection = Selection {
+            paths: vec![PathBuf::from("build/f.md"), PathBuf::from("-")],
+            ..Selection::default()
+        };
+
+        assert_eq!(vec!["build/f.md", "<stdin>"], names(&selection));
+
+        let selection = Selection {
+            paths: vec![
+                PathBuf::from("docs"),
+                PathBuf::from("docs/d.md"),
+                PathBuf::from("./a.md"),
+                PathBuf::from("a.md"),
+                PathBuf::from("."),
+            ],
+            recursive: true,
+            ..Selection::default()
+        };
+
+        assert_eq!(
+            vec!["docs/d.md", "docs/drafts/e.md", "a.md", "b.markdown"],
+            names(&selection)
+        );
+
+        let selection = Selection {
+            paths: vec![PathBuf::from("-"), PathBuf::from("-")],
+            ..Selection::default()
+        };
+
+        assert!(find(&ctx, &selection).is_err());
+
+        let selection = Selection {
+            paths: vec![PathBuf::from("c.txt")],
+            ..Selection::default()
+        };
+
+        assert!(find(&ctx, &selection).is_err());
+
+        tmp.close().unwrap();
+    }
+}
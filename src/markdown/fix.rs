@@ -0,0 +1,149 @@
+//! Applies the edits that lint rules suggest for their issues.
+
+use super::handler::lint::{Edit, Issue};
+
+/// Applies the edits of every issue that doesn't overlap an earlier one, and
+/// returns the new content with the number of issues that were fixed. The
+/// edits of a single issue are applied all together or not at all.
+pub fn apply(content: &str, issues: &[&Issue]) -> (String, usize) {
+    let lines = line_offsets(content);
+    let mut taken: Vec<(usize, usize)> = Vec::new();
+    let mut accepted: Vec<(usize, usize, &str)> = Vec::new();
+    let mut fixed = 0;
+
+    for issue in issues.iter().filter(|x| !x.edits.is_empty()) {
+        let spans: Option<Vec<(usize, usize, &str)>> = issue
+            .edits
+            .iter()
+            .map(|x| {
+                Some((
+                    offset(content, &lines, x, true)?,
+                    offset(content, &lines, x, false)?,
+                    x.text.as_str(),
+                ))
+            })
+            .collect();
+
+        let spans = match spans {
+            Some(x) => x,
+            None => continue,
+        };
+
+        let overlaps = spans.iter().any(|(start, end, _)| {
+            taken
+                .iter()
+                .any(|(s, e)| (start < e && s < end) || (start == s && end == e))
+        });
+
+        if overlaps || spans.iter().any(|(start, end, _)| start > end) {
+            continue;
+        }
+
+        for (start, end, text) in spans {
+            taken.push((start, end));
+            accepted.push((start, end, text));
+        }
+
+        fixed += 1;
+    }
+
+    accepted.sort_by_key(|(start, end, _)| (*start, *end));
+
+    let mut res = String::with_capacity(content.len());
+    let mut pos = 0;
+
+    for (start, end, text) in accepted {
+        res.push_str(&content[pos..start]);
+        res.push_str(text);
+        pos = end;
+    }
+
+    res.push_str(&content[pos..]);
+
+    (res, fixed)
+}
+
+/// Where each line starts.
+fn line_offsets(content: &str) -> Vec<usize> {
+    let mut res = vec![0];
+    res.extend(content.match_indices('\n').map(|(i, _)| i + 1));
+    res
+}
+
+/// The byte offset of one end of an edit, if its column is within the line,
+/// not counting the line break.
+fn offset(content: &str, lines: &[usize], edit: &Edit, start: bool) -> Option<usize> {
+    let (line, col) = match start {
+        true => (edit.line_start, edit.col_start),
+        false => (edit.line_end, edit.col_end),
+    };
+
+    let line_start = *lines.get(line)?;
+    let line_end = lines.get(line + 1).map_or(content.len(), |x| x - 1);
+    let text = content[line_start..line_end].trim_end_matches('\r');
+
+    match col <= text.len() && text.is_char_boundary(col) {
+        true => Some(line_start + col),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn issue(edits: Vec<Edit>) -> Issue {
+        Issue {
+            line_start: 0,
+            line_end: 0,
+            col_start: 0,
+            col_end: 0,
+            content: String::new(),
+            msg: String::new(),
+            edits,
+        }
+    }
+
+    fn edit(line: usize, col_start: usize, col_end: usize, text: &str) -> Edit {
+        Edit {
+            line_start: line,
+            col_start,
+            line_end: line,
+            col_end,
+            text: text.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_apply() {
+        let content = "# A\n### B\n#### C\n";
+
+        let a = issue(vec![edit(1, 0, 3, "##")]);
+        let b = issue(vec![edit(2, 0, 4, "###")]);
+        let overlapping = issue(vec![edit(1, 1, 2, "")]);
+        let none = issue(Vec::new());
+
+        let (res, fixed) = apply(content, &[&a, &overlapping, &b, &none]);
+
+        assert_eq!("# A\n## B\n### C\n", res);
+        assert_eq!(2, fixed);
+    }
+
+    #[test]
+    fn test_apply_out_of_range() {
+        let content = "# A\n";
+        let a = issue(vec![edit(3, 0, 1, "x")]);
+
+        assert_eq!((content.to_owned(), 0), apply(content, &[&a]));
+
+        let content = "# A\r\n# B\n";
+        let past_end = issue(vec![edit(0, 0, 4, "x")]);
+        let newline = issue(vec![edit(0, 3, 4, "")]);
+        let to_end = issue(vec![edit(1, 2, 3, "C")]);
+
+        assert_eq!(
+            ("# A\r\n# C\n".to_owned(), 1),
+            apply(content, &[&past_end, &newline, &to_end])
+        );
+    }
+}
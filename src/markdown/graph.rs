@@ -0,0 +1,344 @@
+//! The links between the documents of a project, and what they tell about
+//! how readers get around: documents no entry point leads to, documents
+//! that lead nowhere, and documents that only lead to each other.
+
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+/// The documents, by their name, and the links between them. Each link is
+/// kept once, and links of a document to itself are left out.
+#[derive(Debug, Default, PartialEq)]
+pub struct Graph {
+    pub nodes: Vec<PathBuf>,
+    /// The indexes of the document a link is in and the one it points to.
+    pub edges: BTreeSet<(usize, usize)>,
+    /// The indexes of the documents readers start from.
+    pub entries: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Dot,
+    Json,
+}
+
+impl Graph {
+    /// The documents none of the entry points lead to, however many links
+    /// are followed. Without entry points there are none.
+    pub fn orphans(&self) -> Vec<usize> {
+        if self.entries.is_empty() {
+            return Vec::new();
+        }
+
+        let mut reached = vec![false; self.nodes.len()];
+        let mut todo = self.entries.clone();
+
+        while let Some(x) = todo.pop() {
+            if std::mem::replace(&mut reached[x], true) {
+                continue;
+            }
+
+            todo.extend(self.links(x).filter(|y| !reached[*y]));
+        }
+
+        (0..self.nodes.len()).filter(|x| !reached[*x]).collect()
+    }
+
+    /// The documents without links to other documents.
+    pub fn dead_ends(&self) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|x| self.links(*x).next().is_none())
+            .collect()
+    }
+
+    /// The groups of documents that lead to each other in a circle, with
+    /// the documents of each group in order.
+    pub fn cycles(&self) -> Vec<Vec<usize>> {
+        let mut tarjan = Tarjan {
+            graph: self,
+            index: vec![None; self.nodes.len()],
+            low: vec![0; self.nodes.len()],
+            stack: Vec::new(),
+            on_stack: vec![false; self.nodes.len()],
+            next: 0,
+            res: Vec::new(),
+        };
+
+        for x in 0..self.nodes.len() {
+            if tarjan.index[x].is_none() {
+                tarjan.visit(x);
+            }
+        }
+
+        let mut res = tarjan.res;
+
+        for x in res.iter_mut() {
+            x.sort();
+        }
+
+        res.sort();
+        res
+    }
+
+    fn links(&self, from: usize) -> impl Iterator<Item = usize> + '_ {
+        self.edges
+            .range((from, 0)..(from + 1, 0))
+            .map(|(_, to)| *to)
+    }
+
+    fn name(&self, node: usize) -> String {
+        self.nodes[node].to_string_lossy().into_owned()
+    }
+}
+
+/// Finds the strongly connected components of the graph, keeping those of
+/// more than one document.
+struct Tarjan<'a> {
+    graph: &'a Graph,
+    index: Vec<Option<usize>>,
+    low: Vec<usize>,
+    stack: Vec<usize>,
+    on_stack: Vec<bool>,
+    next: usize,
+    res: Vec<Vec<usize>>,
+}
+
+impl Tarjan<'_> {
+    /// Visits the documents reachable from `node` depth first. The path
+    /// followed is kept on an explicit stack rather than by recursing, so
+    /// long chains of links can't overflow the call stack.
+    fn visit(&mut self, node: usize) {
+        let mut path = vec![(node, self.enter(node))];
+
+        while let Some((from, links)) = path.last_mut() {
+            let from = *from;
+
+            match links.pop() {
+                Some(to) => match self.index[to] {
+                    None => {
+                        let links = self.enter(to);
+                        path.push((to, links));
+                    }
+                    Some(x) if self.on_stack[to] => self.low[from] = self.low[from].min(x),
+                    Some(_) => (),
+                },
+                None => {
+                    path.pop();
+
+                    if let Some((parent, _)) = path.last() {
+                        self.low[*parent] = self.low[*parent].min(self.low[from]);
+                    }
+
+                    self.leave(from);
+                }
+            }
+        }
+    }
+
+    /// Numbers the document and returns its links, last one first.
+    fn enter(&mut self, node: usize) -> Vec<usize> {
+        self.index[node] = Some(self.next);
+        self.low[node] = self.next;
+        self.next += 1;
+        self.stack.push(node);
+        self.on_stack[node] = true;
+
+        let mut links: Vec<usize> = self.graph.links(node).collect();
+        links.reverse();
+        links
+    }
+
+    /// Takes the component off the stack when `node` is its root.
+    fn leave(&mut self, node: usize) {
+        if Some(self.low[node]) != self.index[node] {
+            return;
+        }
+
+        let mut component = Vec::new();
+
+        while let Some(x) = self.stack.pop() {
+            self.on_stack[x] = false;
+            component.push(x);
+
+            if x == node {
+                break;
+            }
+        }
+
+        if component.len() > 1 {
+            self.res.push(component);
+        }
+    }
+}
+
+pub fn render(format: Format, graph: &Graph) -> String {
+    match format {
+        Format::Text => text(graph),
+        Format::Dot => dot(graph),
+        Format::Json => format!("{}\n", serde_json::to_string_pretty(&json(graph)).unwrap()),
+    }
+}
+
+fn text(graph: &Graph) -> String {
+    let mut res = String::new();
+    let sections = [
+        ("orphans", graph.orphans()),
+        ("dead ends", graph.dead_ends()),
+    ];
+
+    for (title, nodes) in sections {
+        if nodes.is_empty() {
+            continue;
+        }
+
+        writeln!(res, "{}:", title).unwrap();
+
+        for x in nodes {
+            writeln!(res, "  {}", graph.name(x)).unwrap();
+        }
+    }
+
+    let cycles = graph.cycles();
+
+    if !cycles.is_empty() {
+        writeln!(res, "cycles:").unwrap();
+    }
+
+    for cycle in cycles {
+        let names: Vec<String> = cycle.iter().map(|x| graph.name(*x)).collect();
+        writeln!(res, "  {}", names.join(", ")).unwrap();
+    }
+
+    res
+}
+
+fn dot(graph: &Graph) -> String {
+    let orphans = graph.orphans();
+    let mut res = String::from("digraph docs {\n");
+
+    for x in 0..graph.nodes.len() {
+        let style = match (graph.entries.contains(&x), orphans.contains(&x)) {
+            (true, _) => " [shape=box]",
+            (_, true) => " [style=dashed]",
+            _ => "",
+        };
+
+        writeln!(res, "  {}{};", quote(&graph.name(x)), style).unwrap();
+    }
+
+    for (from, to) in graph.edges.iter() {
+        writeln!(
+            res,
+            "  {} -> {};",
+            quote(&graph.name(*from)),
+            quote(&graph.name(*to))
+        )
+        .unwrap();
+    }
+
+    res.push_str("}\n");
+    res
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn json(graph: &Graph) -> Value {
+    let orphans = graph.orphans();
+    let dead_ends = graph.dead_ends();
+
+    let nodes: Vec<Value> = (0..graph.nodes.len())
+        .map(|x| {
+            json!({
+                "path": graph.name(x),
+                "entry": graph.entries.contains(&x),
+                "orphan": orphans.contains(&x),
+                "dead_end": dead_ends.contains(&x),
+            })
+        })
+        .collect();
+
+    let edges: Vec<Value> = graph
+        .edges
+        .iter()
+        .map(|(from, to)| json!({ "from": graph.name(*from), "to": graph.name(*to) }))
+        .collect();
+
+    let cycles: Vec<Vec<String>> = graph
+        .cycles()
+        .into_iter()
+        .map(|x| x.into_iter().map(|y| graph.name(y)).collect())
+        .collect();
+
+    json!({ "nodes": nodes, "edges": edges, "cycles": cycles })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// README -> a <-> b -> c, and d -> e -> d that nothing leads to.
+    fn graph() -> Graph {
+        Graph {
+            nodes: ["README.md", "a.md", "b.md", "c.md", "d.md", "e.md"]
+                .iter()
+                .map(PathBuf::from)
+                .collect(),
+            edges: BTreeSet::from([(0, 1), (1, 2), (2, 1), (2, 3), (4, 5), (5, 4)]),
+            entries: vec![0],
+        }
+    }
+
+    #[test]
+    fn test_analysis() {
+        let graph = graph();
+
+        assert_eq!(vec![4, 5], graph.orphans());
+        assert_eq!(vec![3], graph.dead_ends());
+        assert_eq!(vec![vec![1, 2], vec![4, 5]], graph.cycles());
+    }
+
+    #[test]
+    fn test_long_chain() {
+        let count = 200_000;
+        let graph = Graph {
+            nodes: (0..count)
+                .map(|x| PathBuf::from(format!("{}.md", x)))
+                .collect(),
+            edges: (0..count).map(|x| (x, (x + 1) % count)).collect(),
+            entries: vec![0],
+        };
+
+        let cycles = graph.cycles();
+
+        assert_eq!(1, cycles.len());
+        assert_eq!(count, cycles[0].len());
+    }
+
+    #[test]
+    fn test_render() {
+        let graph = graph();
+
+        assert_eq!(
+            "orphans:\n  d.md\n  e.md\ndead ends:\n  c.md\ncycles:\n  a.md, b.md\n  d.md, e.md\n",
+            render(Format::Text, &graph)
+        );
+
+        let dot = render(Format::Dot, &graph);
+        assert!(dot.starts_with("digraph docs {\n  \"README.md\" [shape=box];\n"));
+        assert!(dot.contains("  \"d.md\" [style=dashed];\n"));
+        assert!(dot.contains("  \"a.md\" -> \"b.md\";\n"));
+
+        let json: Value = serde_json::from_str(&render(Format::Json, &graph)).unwrap();
+        assert_eq!(json!(true), json["nodes"][4]["orphan"]);
+        assert_eq!(
+            json!({ "from": "README.md", "to": "a.md" }),
+            json["edges"][0]
+        );
+        assert_eq!(json!([["a.md", "b.md"], ["d.md", "e.md"]]), json["cycles"]);
+    }
+}
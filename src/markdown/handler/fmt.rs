@@ -1,34 +1,38 @@
-pub struct Handler {}
-
-impl Handler {
-    pub fn handle(self, path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
-        let path = std::env::current_dir()?.join(path).canonicalize()?;
+use crate::markdown::files::{self, Input, Selection};
+use crate::markdown::parser::{lexer, token};
+use crate::markdown::state::HeadingStyle;
+use crate::state::Context;
+use rayon::prelude::*;
 
-        if path.is_dir() {
-            return self.scan_dir();
-        }
+pub struct Handler {}
 
-        match path.extension().and_then(|x| x.to_str()) {
-            Some("md") => self.fmt_file(&path),
-            _ => Err("invalid file")?,
-        }
-    }
+/// A document with its contents formatted.
+pub struct Formatted {
+    pub input: Input,
+    pub content: String,
+}
 
-    fn scan_dir(&self) -> Result<(), Box<dyn std::error::Error>> {
-        Ok(())
+impl Handler {
+    /// Formats every selected document, without changing any file.
+    pub fn handle(self, ctx: &Context, selection: &Selection) -> crate::Result<Vec<Formatted>> {
+        files::find(ctx, selection)?
+            .into_par_iter()
+            .map(|x| self.fmt_input(ctx, x))
+            .collect()
     }
 
-    fn fmt_file(&self, path: &std::path::PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-        let contents = std::fs::read_to_string(&path)?;
+    fn fmt_input(&self, ctx: &Context, input: Input) -> crate::Result<Formatted> {
+        let contents = input.read()?;
+        let state = crate::state::State::load(&ctx.at(&input.dir(ctx)))?;
 
-        let contents = fix_line_length(&contents);
-
-        println!("{}", contents);
-        Ok(())
+        Ok(Formatted {
+            content: crate::markdown::format(&contents, &state.markdown),
+            input,
+        })
     }
 }
 
-fn fix_line_length(s: &str) -> String {
+pub(crate) fn fix_line_length(s: &str, width: usize) -> String {
     let mut res = String::new();
     let mut in_code_block = false;
 
@@ -40,7 +44,7 @@ fn fix_line_length(s: &str) -> String {
         }
 
         if !in_code_block && !is_table {
-            res.push_str(&split_line(line, 80).join("\n"));
+            res.push_str(&split_line(line, width).join("\n"));
         } else {
             res.push_str(line);
         }
@@ -51,8 +55,70 @@ fn fix_line_length(s: &str) -> String {
     res
 }
 
+/// Rewrites the first two levels of headings in the given style. Deeper
+/// headings have no setext form, so they are left as they are.
+pub(crate) fn fix_heading_style(s: &str, style: HeadingStyle) -> String {
+    let lines: Vec<&str> = s.lines().collect();
+    let mut replaced = std::collections::HashMap::new();
+
+    for block in lexer::lex_analysis(s) {
+        let num = block.line_start;
+
+        let (level, current) = match block.token {
+            token::Block::Heading { level, style, .. } => (level, style),
+            _ => continue,
+        };
+
+        match (current, style) {
+            (token::HeadingStyle::Atx, HeadingStyle::Setext) if level <= 2 => {
+                let text = atx_text(lines[num]);
+
+                if !text.is_empty() {
+                    let underline = if level == 1 { "=" } else { "-" };
+                    let width = text.chars().count().max(3);
+
+                    replaced.insert(num, Some(format!("{}\n{}", text, underline.repeat(width))));
+                }
+            }
+            (token::HeadingStyle::Setex, HeadingStyle::Atx) => {
+                replaced.insert(num, Some(format!("{} {}", "#".repeat(level), lines[num].trim())));
+                replaced.insert(num + 1, None);
+            }
+            _ => (),
+        }
+    }
+
+    let mut res = String::new();
+
+    for (num, line) in lines.iter().enumerate() {
+        match replaced.get(&num) {
+            Some(None) => continue,
+            Some(Some(x)) => res.push_str(x),
+            None => res.push_str(line),
+        }
+
+        res.push('\n');
+    }
+
+    res
+}
+
+/// The text of an ATX heading, without its opening and closing sequences.
+fn atx_text(line: &str) -> &str {
+    let text = line.trim().trim_start_matches('#').trim();
+    let open = text.trim_end_matches('#');
+
+    if open.is_empty() || open.ends_with([' ', '\t']) {
+        open.trim_end()
+    } else {
+        text
+    }
+}
+
+/// Splits the line at spaces into lines of at most `width` characters where
+/// possible.
 fn split_line(line: &str, width: usize) -> Vec<&str> {
-    if line.trim().len() <= width {
+    if line.trim().chars().count() <= width {
         return vec![line.trim()];
     }
 
@@ -69,8 +135,14 @@ fn split_line(line: &str, width: usize) -> Vec<&str> {
     }
 }
 
+/// The byte index of the last space within the first `width` characters,
+/// or of the first one after them.
 fn find_closest_space(line: &str, width: usize) -> Option<usize> {
-    line[..width].rfind(' ').or_else(|| line[width..].find(' ').map(|x| x + width))
+    let end = line.char_indices().nth(width).map_or(line.len(), |(i, _)| i);
+
+    line[..end]
+        .rfind(' ')
+        .or_else(|| line[end..].find(' ').map(|x| x + end))
 }
 
 #[cfg(test)]
@@ -94,4 +166,29 @@ mod test {
 
         assert_eq!(res, Some(34));
     }
+
+    #[test]
+    fn test_split_line_multibyte() {
+        let line = "Café déjà vu à la crème brûlée, 東京の日本語テキスト and more";
+
+        assert_eq!(
+            vec!["Café déjà vu à", "la crème brûlée,", "東京の日本語テキスト and", "more"],
+            split_line(line, 17)
+        );
+        assert_eq!(Some(20), find_closest_space("déjà déjà déjà déjà", 16));
+    }
+
+    #[test]
+    fn test_fix_heading_style() {
+        let atx = "# Title #\n\nText\n\n## Part\n\n### Detail\n\n    # code\n";
+        let setext = "Title\n=====\n\nText\n\nPart\n----\n\n### Detail\n\n    # code\n";
+
+        assert_eq!(setext, fix_heading_style(atx, HeadingStyle::Setext));
+        assert_eq!(
+            "# Title\n\nText\n\n## Part\n\n### Detail\n\n    # code\n",
+            fix_heading_style(setext, HeadingStyle::Atx)
+        );
+        assert_eq!(atx, fix_heading_style(atx, HeadingStyle::Atx));
+        assert_eq!("C#\n---\n", fix_heading_style("## C# #\n", HeadingStyle::Setext));
+    }
 }
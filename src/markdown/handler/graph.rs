@@ -0,0 +1,141 @@
+use crate::markdown::files::{self, Input, Selection};
+use crate::markdown::graph::Graph;
+use crate::markdown::linter::{resolve, split};
+use crate::markdown::parser::token::{self, Inline};
+use crate::markdown::Source;
+use crate::state::{Context, State};
+use crate::IoContext;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+pub struct Handler {}
+
+impl Handler {
+    /// Builds the graph of the links between the selected documents. Entry
+    /// points are taken from `entries` when given, relative to the working
+    /// directory, and must be among the documents. Otherwise they come from
+    /// the settings, see `configured_entries`.
+    pub fn handle(
+        self,
+        ctx: &Context,
+        selection: &Selection,
+        entries: &[PathBuf],
+    ) -> crate::Result<Graph> {
+        let mut paths = Vec::new();
+
+        for input in files::find(ctx, selection)? {
+            match input {
+                Input::File(x) => paths.push(x),
+                Input::Stdin => Err(crate::Error::Invalid(
+                    "the graph can't be built from stdin".to_owned(),
+                ))?,
+            }
+        }
+
+        let index: HashMap<PathBuf, usize> = paths
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (x.clone(), i))
+            .collect();
+
+        let links = paths
+            .par_iter()
+            .map(|x| links(ctx, x))
+            .collect::<crate::Result<Vec<Vec<PathBuf>>>>()?;
+
+        let mut graph = Graph {
+            nodes: paths
+                .iter()
+                .map(|x| Input::File(x.clone()).name(ctx))
+                .collect(),
+            ..Graph::default()
+        };
+
+        for (from, targets) in links.iter().enumerate() {
+            for target in targets {
+                match index.get(target).copied() {
+                    Some(to) if to != from => {
+                        graph.edges.insert((from, to));
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        graph.entries = match entries.is_empty() {
+            true => configured_entries(ctx, &paths, &index)?,
+            false => {
+                let mut res = Vec::new();
+
+                for entry in entries.iter().map(|x| ctx.resolve(x)) {
+                    match position(&index, &entry) {
+                        Some(x) => res.push(x),
+                        None => Err(crate::Error::NotFound(format!(
+                            "the entry point {} is not among the documents",
+                            entry.display()
+                        )))?,
+                    }
+                }
+
+                res
+            }
+        };
+
+        Ok(graph)
+    }
+}
+
+/// The entry points in the settings, relative to the project root, that
+/// are among the documents. When none is, as when only part of a project is
+/// selected, the selected document nearest to the top with the file name of
+/// one of them is used instead, e.g. `docs/README.md`. There may be none.
+fn configured_entries(
+    ctx: &Context,
+    paths: &[PathBuf],
+    index: &HashMap<PathBuf, usize>,
+) -> crate::Result<Vec<usize>> {
+    let root = ctx.project_root().unwrap_or_else(|| ctx.cwd.clone());
+    let configured = State::load(ctx)?.markdown.graph.entry;
+
+    let res: Vec<usize> = configured
+        .iter()
+        .filter_map(|x| position(index, &root.join(x)))
+        .collect();
+
+    if !res.is_empty() {
+        return Ok(res);
+    }
+
+    let names: Vec<&std::ffi::OsStr> = configured.iter().filter_map(|x| x.file_name()).collect();
+
+    Ok((0..paths.len())
+        .filter(|x| paths[*x].file_name().is_some_and(|y| names.contains(&y)))
+        .min_by_key(|x| paths[*x].components().count())
+        .into_iter()
+        .collect())
+}
+
+fn position(index: &HashMap<PathBuf, usize>, entry: &Path) -> Option<usize> {
+    let path = entry.canonicalize().unwrap_or_else(|_| entry.to_path_buf());
+    index.get(&path).copied()
+}
+
+/// The files the links of a document point to.
+fn links(ctx: &Context, path: &Path) -> crate::Result<Vec<PathBuf>> {
+    let content = std::fs::read_to_string(path).with_path(path)?;
+    let root = ctx.at(path.parent().unwrap_or(path)).project_root();
+    let source = Source::new(&content).at(Some(path.to_path_buf()), root);
+
+    Ok(token::inlines(&source.document)
+        .into_iter()
+        .filter_map(|x| match &x.token {
+            Inline::Link {
+                link: Some(link), ..
+            } => split(link),
+            _ => None,
+        })
+        .filter_map(|(x, _)| resolve(&source, x))
+        .map(|x| x.canonicalize().unwrap_or(x))
+        .collect())
+}
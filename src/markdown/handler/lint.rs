@@ -1,10 +1,68 @@
-use std::fmt::Display;
+use crate::markdown::files::{self, Input, Selection};
+use crate::markdown::parser::token::Document;
+use crate::markdown::state::{Options, Severity};
+use crate::markdown::{assets, linter, suppress};
+use crate::state::{Context, State};
+use crate::IoContext;
 use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
 
 pub trait Linter: Sync {
+    /// The ID the rule is configured by in `.docula`. It never changes.
+    fn id(&self) -> &'static str;
     fn name(&self) -> &'static str;
     fn description(&self) -> &'static str;
-    fn lint(&self, content: &str) -> Vec<Issue>;
+    /// How serious the rule's issues are unless configured otherwise.
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+    /// The options the rule is using, with their values.
+    fn options(&self) -> Options {
+        Options::new()
+    }
+    /// Returns the rule with options from `.docula` applied.
+    fn configure(&self, options: &Options) -> Result<Box<dyn Linter>, String>;
+    /// Whether the rule suggests edits for its issues.
+    fn fixable(&self) -> bool {
+        false
+    }
+    fn lint(&self, source: &Source) -> Vec<Issue>;
+}
+
+/// A document as lint rules get it: parsed, along with the text it was
+/// parsed from, which the positions of issues refer to.
+pub struct Source<'a> {
+    pub text: &'a str,
+    pub document: Document,
+    /// The file the document was read from, unless it came from stdin.
+    pub path: Option<PathBuf>,
+    /// The directory links starting with `/` are relative to.
+    pub root: Option<PathBuf>,
+    lines: Vec<&'a str>,
+}
+
+impl<'a> Source<'a> {
+    pub fn new(text: &'a str) -> Source<'a> {
+        Source {
+            text,
+            document: crate::markdown::parse(text),
+            path: None,
+            root: None,
+            lines: text.lines().collect(),
+        }
+    }
+
+    /// The same document, read from the file at `path`.
+    pub fn at(self, path: Option<PathBuf>, root: Option<PathBuf>) -> Source<'a> {
+        Source { path, root, ..self }
+    }
+
+    /// A line of the text, empty past the end.
+    pub fn line(&self, num: usize) -> &'a str {
+        self.lines.get(num).copied().unwrap_or("")
+    }
 }
 
 #[derive(Debug,PartialEq)]
@@ -14,7 +72,43 @@ pub struct Issue {
     pub col_start: usize,
     pub col_end: usize,
     pub content: String,
-    pub msg: String
+    pub msg: String,
+    /// Edits that fix the issue, empty when it needs a person to fix it.
+    pub edits: Vec<Edit>,
+}
+
+/// Replaces the text between two positions, which are zero based lines and
+/// byte columns like those of an `Issue`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edit {
+    pub line_start: usize,
+    pub col_start: usize,
+    pub line_end: usize,
+    pub col_end: usize,
+    pub text: String,
+}
+
+impl Issue {
+    /// Turns a zero based byte column on `line` into the one based character
+    /// column that editors and code scanning tools show. The issue only knows
+    /// the text of its first line, its `content`; columns on other lines are
+    /// kept in bytes, `Report::char_column` knows those lines as well.
+    pub fn char_column(&self, line: usize, col: usize) -> usize {
+        match line == self.line_start {
+            true => char_column(&self.content, col),
+            false => col + 1,
+        }
+    }
+}
+
+/// The one based character column of a zero based byte column in the line.
+fn char_column(line: &str, col: usize) -> usize {
+    let end = col.min(line.len());
+
+    match line.is_char_boundary(end) {
+        true => line[..end].chars().count() + (col - end) + 1,
+        false => col + 1,
+    }
 }
 
 impl Display for Issue {
@@ -23,36 +117,185 @@ impl Display for Issue {
     }
 }
 
-pub struct Handler {
-    linters: Vec<Box<dyn Linter>>
+/// An issue in a file, along with the rule that found it.
+#[derive(Debug, PartialEq)]
+pub struct Finding {
+    pub path: PathBuf,
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub issue: Issue,
 }
 
-impl Handler {
-    pub fn handle(self, path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
-        let path = std::env::current_dir()?.join(path).canonicalize()?;
+impl Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}[{}]: {}",
+            self.path.display(),
+            self.issue.line_start + 1,
+            self.issue.char_column(self.issue.line_start, self.issue.col_start),
+            self.severity,
+            self.rule,
+            self.issue
+        )
+    }
+}
 
-        if path.is_dir() {
-            return self.scan_dir();
-        }
+pub struct Handler {
+    linters: Vec<Box<dyn Linter>>,
+}
+
+/// What linting found, and what it fixed.
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Every file that was linted, with or without findings.
+    pub files: Vec<PathBuf>,
+    /// The issues that are left.
+    pub findings: Vec<Finding>,
+    /// The files that were changed, with the number of issues fixed in each.
+    pub fixed: Vec<(PathBuf, usize)>,
+    /// The lines of every linted file, as the findings refer to them.
+    pub lines: HashMap<PathBuf, Vec<String>>,
+}
 
-        match path.extension().and_then(|x| x.to_str()) {
-            Some("md") => self.lint_file(&path),
-            _ => Err("invalid file")?,
+impl Report {
+    /// Like `Issue::char_column`, but for any line of the finding's file.
+    pub fn char_column(&self, finding: &Finding, line: usize, col: usize) -> usize {
+        match self.lines.get(&finding.path).and_then(|x| x.get(line)) {
+            Some(x) => char_column(x, col),
+            None => finding.issue.char_column(line, col),
         }
     }
+}
 
-    fn scan_dir(&self) -> Result<(), Box<dyn std::error::Error>> {
-        Ok(())
+/// Fixing stops after this many rounds, in case fixes keep undoing each other.
+const MAX_FIX_ROUNDS: usize = 10;
+
+impl Handler {
+    pub fn new(linters: Vec<Box<dyn Linter>>) -> Handler {
+        Handler { linters }
     }
 
-    fn lint_file(&self, path: &std::path::PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-        let content = std::fs::read_to_string(path)?;
-        let issues: Vec<Issue> = self.linters.par_iter().flat_map(|x| x.lint(&content)).collect();
+    /// Lints every selected document, fixing what can be fixed when `fix`
+    /// is set, and reports selected images that no document in the project
+    /// refers to when `unused_assets` is set. Paths in the findings are
+    /// relative to the working directory where possible.
+    pub fn handle(
+        self,
+        ctx: &Context,
+        selection: &Selection,
+        fix: bool,
+        unused_assets: bool,
+    ) -> crate::Result<Report> {
+        let mut res = Report::default();
+        let mut references = HashSet::new();
+        let mut linted = HashSet::new();
+
+        for input in files::find(ctx, selection)? {
+            if fix && input == Input::Stdin {
+                Err(crate::Error::Invalid(
+                    "--fix can't be used when reading stdin".to_owned(),
+                ))?;
+            }
+
+            let mut content = input.read()?;
+            let dir = ctx.at(&input.dir(ctx));
+            let state = State::load(&dir)?;
+            let rules = linter::configure(&self.linters, &state.markdown.lint)?;
+            let name = input.name(ctx);
+            res.files.push(name.clone());
+
+            let path = match &input {
+                Input::File(x) => Some(x.clone()),
+                Input::Stdin => None,
+            };
+            let root = dir.project_root();
+
+            let source = Source::new(&content).at(path.clone(), root.clone());
+            let mut findings = lint(&rules, &name, &source);
+            let mut fixed = 0;
+
+            let rounds = if fix { MAX_FIX_ROUNDS } else { 0 };
+
+            for _ in 0..rounds {
+                let issues: Vec<&Issue> = findings.iter().map(|x| &x.issue).collect();
+                let (changed, count) = crate::markdown::fix::apply(&content, &issues);
 
-        for issue in issues {
-           println!("{}", issue)
+                if count == 0 || changed == content {
+                    break;
+                }
+
+                content = changed;
+                fixed += count;
+                let source = Source::new(&content).at(path.clone(), root.clone());
+                findings = lint(&rules, &name, &source);
+            }
+
+            if unused_assets {
+                let source = Source::new(&content).at(path.clone(), root.clone());
+                references.extend(assets::references(&source));
+                linted.extend(path);
+            }
+
+            res.lines
+                .insert(name.clone(), content.lines().map(String::from).collect());
+
+            if let (Input::File(path), true) = (&input, fixed > 0) {
+                std::fs::write(path, &content).with_path(path)?;
+                res.fixed.push((name, fixed));
+            }
+
+            res.findings.append(&mut findings);
         }
 
-        Ok(())
+        if unused_assets {
+            // The documents that weren't selected may still show an image.
+            let root = ctx.project_root().unwrap_or_else(|| ctx.cwd.clone());
+            let root = root.canonicalize().with_path(&root)?;
+
+            for path in files::documents(&root)? {
+                if linted.contains(&path) {
+                    continue;
+                }
+
+                let content = std::fs::read_to_string(&path).with_path(&path)?;
+                let project = ctx.at(path.parent().unwrap_or(&root)).project_root();
+                let source = Source::new(&content).at(Some(path), project);
+                references.extend(assets::references(&source));
+            }
+
+            let images = files::images(ctx, selection)?;
+            let name = |x: &PathBuf| Input::File(x.clone()).name(ctx);
+
+            res.findings
+                .append(&mut assets::unused(images, &references, name));
+        }
+
+        Ok(res)
     }
 }
+
+/// Runs the rules on the document and applies its suppressions, sorting the
+/// findings by where they are.
+pub(crate) fn lint(rules: &[linter::Rule], name: &Path, source: &Source) -> Vec<Finding> {
+    let findings = rules
+        .par_iter()
+        .flat_map(|x| {
+            x.linter
+                .lint(source)
+                .into_iter()
+                .map(|issue| Finding {
+                    path: name.to_path_buf(),
+                    rule: x.linter.id(),
+                    severity: x.severity,
+                    issue,
+                })
+                .collect::<Vec<Finding>>()
+        })
+        .collect();
+
+    let mut res = suppress::apply(name, source.text, findings);
+    res.sort_by_key(|x| (x.issue.line_start, x.issue.col_start));
+
+    res
+}
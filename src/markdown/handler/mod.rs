@@ -1,2 +1,3 @@
 pub mod fmt;
+pub mod graph;
 pub mod lint;
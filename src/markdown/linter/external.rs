@@ -0,0 +1,450 @@
+//! Requests links to other sites to see whether they still work. Results
+//! are kept in a cache file for a while, as sites are slow to answer and
+//! don't like being asked the same thing over and over.
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use url::Url;
+
+/// How a site answered.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Ok,
+    /// Moved elsewhere, which the link should point to instead.
+    Redirect(String),
+    /// An HTTP error status, like 404.
+    Http(u16),
+    /// No answer at all, e.g. because of a timeout or an unknown host.
+    Failed(String),
+}
+
+impl Status {
+    /// What is wrong with the link to `url`, if anything.
+    pub fn problem(&self, url: &str) -> Option<String> {
+        match self {
+            Status::Ok => None,
+            Status::Redirect(x) => Some(format!("`{}` redirects to `{}`", url, x)),
+            Status::Http(x) => Some(format!("`{}` returned HTTP {}", url, x)),
+            Status::Failed(x) => Some(format!("`{}` could not be reached: {}", url, x)),
+        }
+    }
+
+    /// Whether asking again later could give another answer.
+    fn is_transient(&self) -> bool {
+        matches!(self, Status::Failed(_) | Status::Http(429 | 500..=599))
+    }
+}
+
+/// How links to other sites are checked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Settings {
+    pub concurrency: usize,
+    pub timeout: Duration,
+    pub retries: u32,
+    pub retry_delay: Duration,
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+    pub cache_ttl: Duration,
+    pub cache: Option<PathBuf>,
+}
+
+/// Checks links for a whole run: every URL is requested at most once, and
+/// the cache is read when the checker is made and written when it is
+/// dropped.
+pub struct Checker {
+    settings: Settings,
+    agent: ureq::Agent,
+    pool: Option<rayon::ThreadPool>,
+    cache: Mutex<Cache>,
+    /// Every answer of this run, including transient ones the cache leaves
+    /// out.
+    checked: Mutex<HashMap<String, Status>>,
+}
+
+impl Checker {
+    pub fn new(settings: Settings) -> Checker {
+        let agent = ureq::AgentBuilder::new()
+            .timeout(settings.timeout)
+            .redirects(0)
+            .user_agent(concat!("docula/", env!("CARGO_PKG_VERSION")))
+            .build();
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(settings.concurrency)
+            .build()
+            .ok();
+
+        let cache = Cache::load(cache_path(&settings), settings.cache_ttl);
+
+        Checker {
+            settings,
+            agent,
+            pool,
+            cache: Mutex::new(cache),
+            checked: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Checks every `http` and `https` URL the settings allow, at most
+    /// `concurrency` at a time. URLs that weren't checked are left out.
+    pub fn check(&self, urls: &[&str]) -> HashMap<String, Status> {
+        let mut res = HashMap::new();
+        let mut todo = Vec::new();
+
+        {
+            let checked = self.checked.lock().unwrap();
+            let cache = self.cache.lock().unwrap();
+
+            for url in urls.iter().copied().filter(|x| self.is_allowed(x)) {
+                match checked.get(url).or_else(|| cache.get(url)) {
+                    Some(x) => {
+                        res.insert(url.to_owned(), x.clone());
+                    }
+                    None if !todo.contains(&url) => todo.push(url),
+                    None => (),
+                }
+            }
+        }
+
+        if todo.is_empty() {
+            return res;
+        }
+
+        let request = |x: &&str| (x.to_string(), self.request(x));
+        let answers: Vec<(String, Status)> = match &self.pool {
+            Some(pool) => pool.install(|| todo.par_iter().map(request).collect()),
+            None => todo.iter().map(request).collect(),
+        };
+
+        let mut checked = self.checked.lock().unwrap();
+        let mut cache = self.cache.lock().unwrap();
+
+        for (url, status) in answers {
+            if !status.is_transient() {
+                cache.insert(&url, &status);
+            }
+
+            checked.insert(url.clone(), status.clone());
+            res.insert(url, status);
+        }
+
+        res
+    }
+
+    /// Whether the URL is on the web, and its domain may be checked. Domains
+    /// include their subdomains.
+    fn is_allowed(&self, url: &str) -> bool {
+        let host = match Url::parse(url) {
+            Ok(x) if ["http", "https"].contains(&x.scheme()) => match x.host_str() {
+                Some(x) => x.to_lowercase(),
+                None => return false,
+            },
+            _ => return false,
+        };
+
+        let matches = |domain: &String| {
+            let domain = domain.to_lowercase();
+            host == domain || host.ends_with(&format!(".{}", domain))
+        };
+
+        (self.settings.allow.is_empty() || self.settings.allow.iter().any(matches))
+            && !self.settings.deny.iter().any(matches)
+    }
+
+    /// Requests the URL, trying again with growing delays while the answer
+    /// may be transient.
+    fn request(&self, url: &str) -> Status {
+        let mut delay = self.settings.retry_delay;
+        let mut res = self.request_once(url);
+
+        for _ in 0..self.settings.retries {
+            if !res.is_transient() {
+                break;
+            }
+
+            std::thread::sleep(delay);
+            delay *= 2;
+            res = self.request_once(url);
+        }
+
+        res
+    }
+
+    /// Asks with `HEAD` first, which saves downloading the page, and with
+    /// `GET` when the server says it doesn't support `HEAD`.
+    fn request_once(&self, url: &str) -> Status {
+        let res = match self.agent.head(url).call() {
+            Err(ureq::Error::Status(405 | 501, _)) => self.agent.get(url).call(),
+            x => x,
+        };
+
+        match res {
+            Ok(x) if (300..400).contains(&x.status()) => {
+                let to = x.header("Location").unwrap_or_default();
+
+                match Url::parse(url).and_then(|x| x.join(to)) {
+                    Ok(x) => Status::Redirect(x.to_string()),
+                    Err(_) => Status::Redirect(to.to_owned()),
+                }
+            }
+            Ok(_) => Status::Ok,
+            Err(ureq::Error::Status(x, _)) => Status::Http(x),
+            Err(ureq::Error::Transport(x)) => Status::Failed(x.to_string()),
+        }
+    }
+}
+
+impl Drop for Checker {
+    fn drop(&mut self) {
+        if let Ok(x) = self.cache.get_mut() {
+            x.save();
+        }
+    }
+}
+
+fn cache_path(settings: &Settings) -> Option<PathBuf> {
+    if settings.cache_ttl.is_zero() {
+        return None;
+    }
+
+    settings.cache.clone().or_else(|| {
+        let base = match std::env::var_os("XDG_CACHE_HOME").filter(|x| !x.is_empty()) {
+            Some(x) => PathBuf::from(x),
+            None => PathBuf::from(std::env::var_os("HOME")?).join(".cache"),
+        };
+
+        Some(base.join("docula").join("links.json"))
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    /// When the URL was checked, in seconds since the epoch.
+    checked: u64,
+    status: Status,
+}
+
+/// Results of earlier checks. A cache that can't be read is started over,
+/// and one that can't be written is given up on, as it only saves time.
+struct Cache {
+    path: Option<PathBuf>,
+    ttl: Duration,
+    entries: BTreeMap<String, Entry>,
+    changed: bool,
+}
+
+impl Cache {
+    fn load(path: Option<PathBuf>, ttl: Duration) -> Cache {
+        let entries = path
+            .as_deref()
+            .and_then(|x| std::fs::read_to_string(x).ok())
+            .and_then(|x| serde_json::from_str(&x).ok())
+            .unwrap_or_default();
+
+        Cache {
+            path,
+            ttl,
+            entries,
+            changed: false,
+        }
+    }
+
+    fn get(&self, url: &str) -> Option<&Status> {
+        let entry = self.entries.get(url)?;
+
+        match now().saturating_sub(entry.checked) < self.ttl.as_secs() {
+            true => Some(&entry.status),
+            false => None,
+        }
+    }
+
+    fn insert(&mut self, url: &str, status: &Status) {
+        if self.path.is_none() {
+            return;
+        }
+
+        self.entries.insert(
+            url.to_owned(),
+            Entry {
+                checked: now(),
+                status: status.clone(),
+            },
+        );
+        self.changed = true;
+    }
+
+    fn save(&mut self) {
+        let path = match (&self.path, self.changed) {
+            (Some(x), true) => x,
+            _ => return,
+        };
+
+        let ttl = self.ttl.as_secs();
+        self.entries
+            .retain(|_, x| now().saturating_sub(x.checked) < ttl);
+
+        let _ = write(
+            path,
+            &serde_json::to_string(&self.entries).unwrap_or_default(),
+        );
+    }
+}
+
+/// Writes the file through a temporary one, so that other runs never read
+/// half of it.
+fn write(path: &Path, content: &str) -> std::io::Result<()> {
+    if let Some(x) = path.parent() {
+        std::fs::create_dir_all(x)?;
+    }
+
+    let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+    std::fs::write(&tmp, content)?;
+    std::fs::rename(&tmp, path)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Serves a few pages on a free port, counting the requests it gets.
+    fn serve() -> (String, Arc<AtomicUsize>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", server.server_addr());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        let flaky = AtomicUsize::new(0);
+
+        std::thread::spawn(move || {
+            for request in server.incoming_requests() {
+                counter.fetch_add(1, Ordering::SeqCst);
+
+                let head = request.method() == &tiny_http::Method::Head;
+                let (status, location) = match request.url() {
+                    "/ok" => (200, None),
+                    "/moved" => (301, Some("/ok")),
+                    "/no-head" if head => (405, None),
+                    "/no-head" => (200, None),
+                    "/flaky" if flaky.fetch_add(1, Ordering::SeqCst) < 1 => (503, None),
+                    "/flaky" => (200, None),
+                    _ => (404, None),
+                };
+
+                let mut response = tiny_http::Response::empty(status);
+
+                if let Some(x) = location {
+                    response.add_header(tiny_http::Header::from_bytes("Location", x).unwrap());
+                }
+
+                let _ = request.respond(response);
+            }
+        });
+
+        (base, requests)
+    }
+
+    fn settings(cache: Option<PathBuf>) -> Settings {
+        Settings {
+            concurrency: 4,
+            timeout: Duration::from_secs(5),
+            retries: 1,
+            retry_delay: Duration::from_millis(10),
+            allow: Vec::new(),
+            deny: Vec::new(),
+            cache_ttl: Duration::from_secs(60),
+            cache,
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let (base, _) = serve();
+        let checker = Checker::new(settings(None));
+
+        let urls: Vec<String> = ["ok", "moved", "no-head", "flaky", "missing"]
+            .iter()
+            .map(|x| format!("{}/{}", base, x))
+            .collect();
+        let urls: Vec<&str> = urls.iter().map(String::as_str).collect();
+        let res = checker.check(&urls);
+
+        assert_eq!(Status::Ok, res[urls[0]]);
+        assert_eq!(Status::Redirect(urls[0].to_owned()), res[urls[1]]);
+        assert_eq!(Status::Ok, res[urls[2]]);
+        assert_eq!(Status::Ok, res[urls[3]]);
+        assert_eq!(Status::Http(404), res[urls[4]]);
+    }
+
+    #[test]
+    fn test_allow_and_deny() {
+        let mut settings = settings(None);
+        settings.allow = vec!["example.com".to_owned()];
+        settings.deny = vec!["private.example.com".to_owned()];
+
+        let checker = Checker::new(settings);
+
+        assert!(checker.is_allowed("https://example.com/a"));
+        assert!(checker.is_allowed("http://docs.Example.com/a"));
+        assert!(!checker.is_allowed("https://private.example.com/a"));
+        assert!(!checker.is_allowed("https://notexample.com/a"));
+        assert!(!checker.is_allowed("ftp://example.com/a"));
+    }
+
+    #[test]
+    fn test_cache() {
+        let tmp = tempdir::TempDir::new("external_test").unwrap();
+        let (base, requests) = serve();
+        let settings = settings(Some(tmp.path().join("cache/links.json")));
+        let url = format!("{}/missing", base);
+
+        let first = Checker::new(settings.clone()).check(&[&url]);
+        let count = requests.load(Ordering::SeqCst);
+        let second = Checker::new(settings.clone()).check(&[&url]);
+
+        assert_eq!(first, second);
+        assert_eq!(count, requests.load(Ordering::SeqCst));
+        assert!(tmp.path().join("cache/links.json").exists());
+
+        let mut expired = settings.clone();
+        expired.cache_ttl = Duration::ZERO;
+        Checker::new(expired).check(&[&url]);
+
+        assert!(requests.load(Ordering::SeqCst) > count);
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn test_checked_once() {
+        let (base, requests) = serve();
+        let checker = Checker::new(settings(None));
+        let missing = format!("{}/missing", base);
+        let no_head = format!("{}/no-head", base);
+
+        // A 404 is an answer, only a `HEAD` the server refuses needs a `GET`.
+        checker.check(&[&missing]);
+        assert_eq!(1, requests.load(Ordering::SeqCst));
+
+        checker.check(&[&no_head]);
+        assert_eq!(3, requests.load(Ordering::SeqCst));
+
+        checker.check(&[&missing, &no_head]);
+        assert_eq!(3, requests.load(Ordering::SeqCst));
+    }
+}
@@ -1,9 +1,40 @@
-use super::handler::lint::{Linter, Issue};
+use super::handler::lint::{Edit, Issue, Linter, Source};
+use super::Options;
+use crate::markdown::parser::token::{Block, HeadingStyle};
 use ordinal::Ordinal;
+use serde::{Deserialize, Serialize};
 
-pub struct HeadingLevels {}
+#[derive(Default)]
+pub struct HeadingLevels {
+    options: HeadingLevelsOptions,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HeadingLevelsOptions {
+    /// The level documents start at, e.g. 2 when the title is added by
+    /// whatever renders them.
+    #[serde(default = "default_first_level")]
+    first_level: usize,
+}
+
+impl Default for HeadingLevelsOptions {
+    fn default() -> HeadingLevelsOptions {
+        HeadingLevelsOptions {
+            first_level: default_first_level(),
+        }
+    }
+}
+
+fn default_first_level() -> usize {
+    1
+}
 
 impl Linter for HeadingLevels {
+    fn id(&self) -> &'static str {
+        "heading-levels"
+    }
+
     fn name(&self) -> &'static str {
         "Heading Levels"
     }
@@ -12,45 +43,92 @@ impl Linter for HeadingLevels {
         "Checks that no heading levels are skipped in a document."
     }
 
-    fn lint(&self, content: &str) -> Vec<Issue> {
-        let mut current_depth = 0;
-        let mut res = Vec::new();
+    fn options(&self) -> Options {
+        super::to_options(&self.options)
+    }
+
+    fn configure(&self, options: &Options) -> Result<Box<dyn Linter>, String> {
+        let options: HeadingLevelsOptions = super::options(options)?;
 
-        for (num, line) in content.lines().enumerate() {
-           if let Some(depth) = heading_count(line) {
-               let delta = depth - current_depth;
-               if delta > 1 {
-                   let start = line.find('#').expect("A # should exist for us to get here");
-                   let end = line.len();
-                   let msg = format!(
-                       "Skipped {} level header", Ordinal(current_depth + 1).to_string(),
-                   );
-
-                   res.push(Issue{
-                       line_start: num,
-                       line_end: num,
-                       col_start: start,
-                       col_end: end,
-                       content: line.to_owned(),
-                       msg,
-                   });
-               }
-
-               current_depth = depth;
-           }
+        if !(1..=6).contains(&options.first_level) {
+            return Err("first_level must be between 1 and 6".to_owned());
         }
 
-        res
+        Ok(Box::new(HeadingLevels { options }))
     }
-}
 
-fn heading_count(line: &str) -> Option<usize> {
-    match line.trim_start().chars().take_while(|x| x == &'#').collect::<Vec<char>>().len() {
-        0 => None,
-        x => match x > 6 {
-            true => None,
-            false => Some(x),
+    fn fixable(&self) -> bool {
+        true
+    }
+
+    /// Only headings outside of block quotes count, quoted ones belong to
+    /// whatever they were quoted from.
+    fn lint(&self, source: &Source) -> Vec<Issue> {
+        let mut current_depth = self.options.first_level - 1;
+        let mut res = Vec::new();
+
+        for block in source.document.iter() {
+            let (depth, style) = match &block.token {
+                Block::Heading { level, style, .. } => (*level, style),
+                _ => continue,
+            };
+
+            if depth > current_depth + 1 {
+                let num = block.line_start;
+                let line = source.line(num);
+                let msg = format!("Skipped {} level header", Ordinal(current_depth + 1));
+
+                // Promotes the heading to the level that was skipped. A Setext
+                // heading can only have skipped the first level, so its
+                // underline becomes the one for that.
+                let (start, fix) = match style {
+                    HeadingStyle::Atx => {
+                        let start = line.find('#').expect("A # should exist for us to get here");
+
+                        (
+                            start,
+                            Edit {
+                                line_start: num,
+                                col_start: start,
+                                line_end: num,
+                                col_end: start + depth,
+                                text: "#".repeat(current_depth + 1),
+                            },
+                        )
+                    }
+                    HeadingStyle::Setex => {
+                        let underline = source.line(num + 1);
+                        let col_start = underline.len() - underline.trim_start().len();
+                        let col_end = underline.trim_end().len();
+
+                        (
+                            line.len() - line.trim_start().len(),
+                            Edit {
+                                line_start: num + 1,
+                                col_start,
+                                line_end: num + 1,
+                                col_end,
+                                text: "=".repeat(col_end - col_start),
+                            },
+                        )
+                    }
+                };
+
+                res.push(Issue {
+                    line_start: num,
+                    line_end: num,
+                    col_start: start,
+                    col_end: line.len(),
+                    content: line.to_owned(),
+                    msg,
+                    edits: vec![fix],
+                });
+            }
+
+            current_depth = depth;
         }
+
+        res
     }
 }
 
@@ -63,10 +141,10 @@ mod test {
             $(
                 #[test]
                 fn $name() {
-                    let linter = HeadingLevels{};
+                    let linter = HeadingLevels::default();
                     let (input, expected) = $value;
 
-                    assert_eq!(expected, linter.lint(input))
+                    assert_eq!(expected, linter.lint(&Source::new(input)))
                 }
             )*
         }
@@ -86,6 +164,13 @@ mod test {
                 col_end: 13,
                 content: "### Heading 2".to_owned(),
                 msg: "Skipped 2nd level header".to_owned(),
+                edits: vec![Edit {
+                    line_start: 3,
+                    col_start: 0,
+                    line_end: 3,
+                    col_end: 3,
+                    text: "##".to_owned(),
+                }],
             }]
         ),
 
@@ -96,13 +181,20 @@ mod test {
             col_end: 6,
             content: "## Foo".to_string(),
             msg: "Skipped 1st level header".to_owned(),
+            edits: vec![Edit {
+                line_start: 0,
+                col_start: 0,
+                line_end: 0,
+                col_end: 2,
+                text: "#".to_owned(),
+            }],
         }]),
 
         space_before_happy: (
             " # Foo\n     ## Bar\n\nHello, world\n    ### Zoom",
             Vec::<Issue>::new()
         ),
-        
+
         hashes_per_line: (
             "# Foo ##### Foo\n## Bar ######Bar", Vec::<Issue>::new()
         ),
@@ -110,5 +202,51 @@ mod test {
         non_normal_header: (
             "# Intro\n####### Not a header\n## Foo", Vec::<Issue>::new(),
         ),
+
+        back_to_higher_level: (
+            "# Foo\n## Bar\n### Baz\n# Qux\n## Quux", Vec::<Issue>::new(),
+        ),
+
+        in_code_blocks: (
+            "# Foo\n```rust\n### Not a header\n```\n~~~\n#### Nor this\n~~~", Vec::<Issue>::new(),
+        ),
+
+        in_block_quotes: (
+            "# Foo\n\n> ### Quoted", Vec::<Issue>::new(),
+        ),
+
+        setext_headings: (
+            "Foo\n===\n\nBar\n---\n\n#### Baz", vec![Issue{
+                line_start: 6,
+                line_end: 6,
+                col_start: 0,
+                col_end: 8,
+                content: "#### Baz".to_owned(),
+                msg: "Skipped 3rd level header".to_owned(),
+                edits: vec![Edit {
+                    line_start: 6,
+                    col_start: 0,
+                    line_end: 6,
+                    col_end: 4,
+                    text: "###".to_owned(),
+                }],
+            }]
+        ),
+
+        bad_first_setext_heading: ("Foo\n---", vec![Issue{
+            line_start: 0,
+            line_end: 0,
+            col_start: 0,
+            col_end: 3,
+            content: "Foo".to_string(),
+            msg: "Skipped 1st level header".to_owned(),
+            edits: vec![Edit {
+                line_start: 1,
+                col_start: 0,
+                line_end: 1,
+                col_end: 3,
+                text: "===".to_owned(),
+            }],
+        }]),
     }
 }
@@ -0,0 +1,187 @@
+use super::handler::lint::{Issue, Linter, Source};
+use super::link::{resolve, split};
+use super::{NoOptions, Options};
+use crate::markdown::parser::token::{self, Inline, InlineToken, Text};
+use crate::markdown::state::Severity;
+use serde::{Deserialize, Serialize};
+
+/// Checks that images in the project exist, and aren't too large to be
+/// shown quickly. Images on other sites are left alone.
+#[derive(Default)]
+pub struct Images {
+    options: ImagesOptions,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+struct ImagesOptions {
+    /// The largest an image may be in bytes, 0 for no limit.
+    max_size: u64,
+}
+
+impl Linter for Images {
+    fn id(&self) -> &'static str {
+        "images"
+    }
+
+    fn name(&self) -> &'static str {
+        "Images"
+    }
+
+    fn description(&self) -> &'static str {
+        "Checks that images exist and are no larger than allowed."
+    }
+
+    fn options(&self) -> Options {
+        super::to_options(&self.options)
+    }
+
+    fn configure(&self, options: &Options) -> Result<Box<dyn Linter>, String> {
+        Ok(Box::new(Images {
+            options: super::options(options)?,
+        }))
+    }
+
+    fn lint(&self, source: &Source) -> Vec<Issue> {
+        images(source)
+            .into_iter()
+            .filter_map(|(token, link, _)| {
+                let (path, _) = split(link)?;
+                let target = resolve(source, path)?;
+
+                let msg = match std::fs::metadata(&target) {
+                    Err(_) => format!("Image `{}` does not exist", path),
+                    Ok(x) if self.options.max_size > 0 && x.len() > self.options.max_size => {
+                        format!(
+                            "Image `{}` is {} bytes, more than the {} allowed",
+                            path,
+                            x.len(),
+                            self.options.max_size
+                        )
+                    }
+                    Ok(_) => return None,
+                };
+
+                Some(issue(source, token, msg))
+            })
+            .collect()
+    }
+}
+
+/// Checks that every image has alt text, which is what screen readers read
+/// out and what is shown when the image can't be.
+#[derive(Default)]
+pub struct ImageAlt {}
+
+impl Linter for ImageAlt {
+    fn id(&self) -> &'static str {
+        "image-alt"
+    }
+
+    fn name(&self) -> &'static str {
+        "Image Alt Text"
+    }
+
+    fn description(&self) -> &'static str {
+        "Checks that images have alt text."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn configure(&self, options: &Options) -> Result<Box<dyn Linter>, String> {
+        super::options::<NoOptions>(options)?;
+        Ok(Box::new(ImageAlt {}))
+    }
+
+    fn lint(&self, source: &Source) -> Vec<Issue> {
+        images(source)
+            .into_iter()
+            .filter(|(_, _, alt)| token::plain_text(alt).trim().is_empty())
+            .map(|(token, _, _)| issue(source, token, "Image has no alt text".to_owned()))
+            .collect()
+    }
+}
+
+/// The images of a document with a link, along with their alt text.
+fn images<'a>(source: &'a Source) -> Vec<(&'a InlineToken, &'a str, &'a Text)> {
+    token::inlines(&source.document)
+        .into_iter()
+        .filter_map(|x| match &x.token {
+            Inline::Image {
+                alt,
+                link: Some(link),
+                ..
+            } => Some((x, link.as_str(), alt)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn issue(source: &Source, token: &InlineToken, msg: String) -> Issue {
+    Issue {
+        line_start: token.line_start,
+        line_end: token.line_start,
+        col_start: token.position,
+        col_end: token.end,
+        content: source.line(token.line_start).to_owned(),
+        msg,
+        edits: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn messages(issues: Vec<Issue>) -> Vec<(usize, String)> {
+        issues.into_iter().map(|x| (x.col_start, x.msg)).collect()
+    }
+
+    #[test]
+    fn test_images() {
+        let tmp = tempdir::TempDir::new("images_test").unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+
+        std::fs::create_dir(root.join("img")).unwrap();
+        std::fs::write(root.join("img/small.png"), [0; 10]).unwrap();
+        std::fs::write(root.join("img/large.png"), [0; 100]).unwrap();
+
+        let text =
+            "![a](img/small.png) ![b](img/large.png) ![c](img/gone.png) ![d](https://a.io/x.png)";
+        let source = Source::new(text).at(Some(root.join("a.md")), None);
+
+        let mut options = Options::new();
+        options.insert("max_size".to_owned(), 50.into());
+
+        let linter = Images::default().configure(&options).unwrap();
+
+        assert_eq!(
+            vec![
+                (
+                    20,
+                    "Image `img/large.png` is 100 bytes, more than the 50 allowed".to_owned()
+                ),
+                (40, "Image `img/gone.png` does not exist".to_owned()),
+            ],
+            messages(linter.lint(&source))
+        );
+        assert_eq!(1, messages(Images::default().lint(&source)).len());
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn test_image_alt() {
+        let source = Source::new("![Logo](a.png) ![](b.png) ![ ](c.png)");
+
+        assert_eq!(
+            vec![
+                (15, "Image has no alt text".to_owned()),
+                (26, "Image has no alt text".to_owned()),
+            ],
+            messages(ImageAlt::default().lint(&source))
+        );
+    }
+}
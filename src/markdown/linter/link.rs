@@ -1,94 +1,457 @@
+use super::external::{Checker, Settings};
+use super::handler::lint::{Issue, Linter, Source};
+use super::Options;
+use crate::markdown::parser::token::{self, Block, Document, Inline, Text};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use url::Url;
 
-#[derive(Debug, PartialEq)]
-pub struct FoundLink<'a> {
-    start: usize,
-    end: usize,
-    link: LinkType,
-    content: ContentType<'a>,
+lazy_static! {
+    static ref SCHEME_REGEX: Regex = Regex::new(r"^[A-Za-z][A-Za-z0-9+.\-]*:").unwrap();
+    static ref HTML_ANCHOR_REGEX: Regex =
+        Regex::new(r#"<[A-Za-z][^>]*?\s(?:id|name)\s*=\s*["']([^"']+)["']"#).unwrap();
 }
 
-#[derive(Debug, PartialEq)]
-pub enum ContentType<'a> {
-    Image(&'a str),
-    HyperLink(&'a str),
-    Text,
+/// Checks links to other files of the project, and to headings in them.
+/// Links with a scheme, like `https:` or `mailto:`, are left alone so the
+/// check works offline, unless checking external links is turned on.
+#[derive(Default)]
+pub struct BrokenLinks {
+    options: BrokenLinksOptions,
+    /// The checkers of external links, one for each set of settings, kept
+    /// by the rule the handler was given so every document of a run shares
+    /// them.
+    checkers: Arc<Mutex<Vec<Arc<Checker>>>>,
 }
 
-#[derive(Debug, PartialEq)]
-pub enum LinkType {
-    File(std::path::PathBuf),
-    Url(Url),
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+struct BrokenLinksOptions {
+    /// Whether to request `http` and `https` links too, which
+    /// `--check-external` turns on as well.
+    check_external: bool,
+    /// How many requests are made at once.
+    concurrency: usize,
+    /// Seconds to wait for a site to answer.
+    timeout: u64,
+    /// How often a request is tried again when the answer may be transient,
+    /// like a timeout or HTTP 503.
+    retries: u32,
+    /// Milliseconds to wait before trying again, doubled on every try.
+    retry_delay: u64,
+    /// Domains to check, leaving out all others. Empty checks every domain.
+    allow: Vec<String>,
+    /// Domains never to check, like those that turn away bots.
+    deny: Vec<String>,
+    /// Seconds results are kept for, 0 turns the cache off.
+    cache_ttl: u64,
+    /// The cache file, by default `docula/links.json` in the user's cache
+    /// directory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache: Option<PathBuf>,
 }
 
-pub fn find_links(content: &str) -> Vec<FoundLink> {
-    let re = regex::Regex::new(r"!?\[\s*(.*)\s*\]\(\s*(.*)\s*\)").unwrap();
-    let mut res = Vec::new();
+impl Default for BrokenLinksOptions {
+    fn default() -> BrokenLinksOptions {
+        BrokenLinksOptions {
+            check_external: false,
+            concurrency: 8,
+            timeout: 10,
+            retries: 2,
+            retry_delay: 500,
+            allow: Vec::new(),
+            deny: Vec::new(),
+            cache_ttl: 24 * 60 * 60,
+            cache: None,
+        }
+    }
+}
+
+impl BrokenLinks {
+    /// The rule with external links checked, whatever the settings say.
+    pub fn external() -> BrokenLinks {
+        BrokenLinks {
+            options: BrokenLinksOptions {
+                check_external: true,
+                ..BrokenLinksOptions::default()
+            },
+            checkers: Arc::default(),
+        }
+    }
+
+    fn checker(&self) -> Arc<Checker> {
+        let settings = self.settings();
+        let mut checkers = self.checkers.lock().unwrap();
+
+        if let Some(x) = checkers.iter().find(|x| *x.settings() == settings) {
+            return x.clone();
+        }
+
+        let res = Arc::new(Checker::new(settings));
+        checkers.push(res.clone());
+
+        res
+    }
 
-    for m in re.find_iter(content) {
-        let text = m.as_str();
+    fn settings(&self) -> Settings {
+        let options = &self.options;
+
+        Settings {
+            concurrency: options.concurrency,
+            timeout: Duration::from_secs(options.timeout),
+            retries: options.retries,
+            retry_delay: Duration::from_millis(options.retry_delay),
+            allow: options.allow.clone(),
+            deny: options.deny.clone(),
+            cache_ttl: Duration::from_secs(options.cache_ttl),
+            cache: options.cache.clone(),
+        }
+    }
+}
+
+impl Linter for BrokenLinks {
+    fn id(&self) -> &'static str {
+        "broken-links"
+    }
+
+    fn name(&self) -> &'static str {
+        "Broken Links"
+    }
+
+    fn description(&self) -> &'static str {
+        "Checks that relative links point to files and headings that exist."
+    }
+
+    fn options(&self) -> Options {
+        super::to_options(&self.options)
+    }
 
-        let caps = re.captures(text);
+    fn configure(&self, options: &Options) -> Result<Box<dyn Linter>, String> {
+        let mut options: BrokenLinksOptions = super::options(options)?;
 
-        if caps.is_none() {
-            continue;
+        if options.concurrency == 0 {
+            return Err("concurrency must be at least 1".to_owned());
         }
 
-        let start = m.start();
-        let end = m.end();
+        options.check_external |= self.options.check_external;
 
-        let caps = caps.unwrap();
-        let inner = caps.get(1).unwrap();
-        let url = Url::parse(caps.get(2).unwrap().as_str()).unwrap();
+        Ok(Box::new(BrokenLinks {
+            options,
+            checkers: self.checkers.clone(),
+        }))
+    }
 
-        let content = match text.get(0..1).unwrap() {
-            "!" => ContentType::Image(inner.as_str()),
-            _ => ContentType::HyperLink(inner.as_str()),
+    fn lint(&self, source: &Source) -> Vec<Issue> {
+        let mut targets = Targets {
+            source,
+            own: anchors(&source.document),
+            files: HashMap::new(),
         };
 
-        res.push(FoundLink{
-            start,
-            end,
-            link: LinkType::Url(url),
-            content,
+        let links: Vec<_> = token::inlines(&source.document)
+            .into_iter()
+            .filter_map(|x| match &x.token {
+                Inline::Link {
+                    link: Some(link), ..
+                } => Some((x, link)),
+                _ => None,
+            })
+            .collect();
+
+        let external = match self.options.check_external {
+            true => {
+                let urls: Vec<&str> = links.iter().map(|(_, x)| x.as_str()).collect();
+
+                self.checker().check(&urls)
+            }
+            false => HashMap::new(),
+        };
+
+        links
+            .into_iter()
+            .filter_map(|(x, link)| {
+                let msg = match external.get(link.as_str()) {
+                    Some(status) => status.problem(link)?,
+                    None => targets.check(link)?,
+                };
+
+                Some(Issue {
+                    line_start: x.line_start,
+                    line_end: x.line_start,
+                    col_start: x.position,
+                    col_end: x.end,
+                    content: source.line(x.line_start).to_owned(),
+                    msg,
+                    edits: Vec::new(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// What links of a document can point to, read as they are needed.
+struct Targets<'a> {
+    source: &'a Source<'a>,
+    own: Vec<String>,
+    files: HashMap<PathBuf, Option<Vec<String>>>,
+}
+
+impl<'a> Targets<'a> {
+    /// Checks a link, returning what is wrong with it.
+    fn check(&mut self, link: &str) -> Option<String> {
+        let (path, fragment) = split(link)?;
+
+        if path.is_empty() {
+            return match fragment {
+                Some(x) if !has_anchor(&self.own, &x) => {
+                    Some(format!("No heading for `#{}` in this document", x))
+                }
+                _ => None,
+            };
+        }
+
+        let target = resolve(self.source, path)?;
+
+        if !target.exists() {
+            return Some(format!("Link target `{}` does not exist", path));
+        }
+
+        let fragment = fragment?;
+        let anchors = self
+            .files
+            .entry(target.clone())
+            .or_insert_with(|| read_anchors(&target))
+            .as_ref()?;
+
+        match has_anchor(anchors, &fragment) {
+            true => None,
+            false => Some(format!("No heading for `#{}` in `{}`", fragment, path)),
+        }
+    }
+}
+
+/// Splits a link to something in the project into its path and fragment,
+/// leaving out any query. Links with a scheme or to another host give `None`.
+pub fn split(link: &str) -> Option<(&str, Option<String>)> {
+    if SCHEME_REGEX.is_match(link) || link.starts_with("//") {
+        return None;
+    }
+
+    let (path, fragment) = match link.split_once('#') {
+        Some((path, fragment)) => (path, Some(fragment)),
+        None => (link, None),
+    };
+    let path = path.split('?').next().unwrap_or_default();
+
+    Some((path, fragment.filter(|x| !x.is_empty()).map(decode)))
+}
+
+/// The file the path of a link points to. Paths starting with `/` are
+/// relative to the root of the project, others to the document. Neither can
+/// be resolved for documents read from stdin.
+pub fn resolve(source: &Source, path: &str) -> Option<PathBuf> {
+    let (base, path) = match path.strip_prefix('/') {
+        Some(x) => (source.root.as_deref()?, x),
+        None => (source.path.as_deref()?.parent()?, path),
+    };
+
+    Url::from_directory_path(base)
+        .ok()?
+        .join(path)
+        .ok()?
+        .to_file_path()
+        .ok()
+}
+
+/// The anchors of a Markdown file, or `None` for any other file, where
+/// fragments mean something else, like lines.
+fn read_anchors(path: &Path) -> Option<Vec<String>> {
+    let markdown = path
+        .extension()
+        .and_then(|x| x.to_str())
+        .is_some_and(|x| ["md", "markdown"].contains(&x));
+
+    if !markdown || !path.is_file() {
+        return None;
+    }
+
+    let text = std::fs::read_to_string(path).ok()?;
+
+    Some(anchors(&crate::markdown::parse(&text)))
+}
+
+/// Whether the fragment names an anchor. Slugs are lowercase, so a fragment
+/// in another case does not match, the same as in a browser.
+fn has_anchor(anchors: &[String], fragment: &str) -> bool {
+    fragment == "top" || anchors.iter().any(|x| x == fragment)
+}
+
+/// The anchors GitHub gives the headings of a document, along with those of
+/// HTML elements with an `id` or `name`. HTML is only looked for in text, so
+/// tags shown in code spans and code blocks don't count.
+pub fn anchors(document: &Document) -> Vec<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut res = Vec::new();
+
+    for heading in headings(document) {
+        let slug = slug(&token::plain_text(heading));
+        let count = counts.entry(slug.clone()).or_insert(0);
+
+        res.push(match *count {
+            0 => slug,
+            x => format!("{}-{}", slug, x),
+        });
+
+        *count += 1;
+    }
+
+    // Emphasis can split a tag into several chunks, other inlines end it.
+    let html: String = token::inlines(document)
+        .into_iter()
+        .map(|x| match &x.token {
+            Inline::Chunk(s) => s.as_str(),
+            _ => "\n",
         })
+        .collect();
+
+    res.extend(
+        HTML_ANCHOR_REGEX
+            .captures_iter(&html)
+            .map(|x| x[1].to_string()),
+    );
+
+    res
+}
+
+fn headings(document: &Document) -> Vec<&Text> {
+    let mut res = Vec::new();
+
+    for block in document.iter() {
+        match &block.token {
+            Block::Heading { content, .. } => res.push(content),
+            Block::BlockQuote(x) => res.extend(headings(x)),
+            Block::OrderedList { items, .. } | Block::UnorderedList { items } => {
+                items.iter().for_each(|x| res.extend(headings(x)))
+            }
+            _ => (),
+        }
     }
 
     res
 }
 
+/// Turns heading text into an anchor the way GitHub does: lowercase, without
+/// punctuation, and with hyphens for spaces.
+pub fn slug(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|x| match x {
+            ' ' => Some('-'),
+            '-' | '_' => Some(x),
+            x if x.is_alphanumeric() => Some(x),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Decodes the percent-escapes of a fragment, like `%20`.
+fn decode(fragment: &str) -> String {
+    let bytes = fragment.as_bytes();
+    let mut res = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|x| std::str::from_utf8(x).ok())
+            .and_then(|x| u8::from_str_radix(x, 16).ok());
+
+        match (bytes[i], hex) {
+            (b'%', Some(x)) => {
+                res.push(x);
+                i += 3;
+            }
+            (x, _) => {
+                res.push(x);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&res).into_owned()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
-    macro_rules! find_links_tests {
-        ($($name:ident: $value:expr,)*) => {
-            $(
-                #[test]
-                fn $name() {
-                    let (input, expected) = $value;
-                    assert_eq!(expected, find_links(input))
-                }
-            )*
-        }
+    #[test]
+    fn test_slug() {
+        assert_eq!("hello-world", slug("Hello, World!"));
+        assert_eq!("use-docula-init--fast", slug(" Use `docula init` & fast "));
+        assert_eq!("überblick_2", slug("Überblick_2"));
     }
 
-    find_links_tests! {
-        single_hyperlink: ("Hello, world [foo](https://bar.com)", vec![FoundLink{
-            start: 13,
-            end: 35,
-            link: LinkType::Url(Url::parse("https://bar.com").unwrap()),
-            content: ContentType::HyperLink("foo"),
-        }]),
-        single_imagelink: (
-            "Hey, this image is great! ![Alt text](https://imgy.io/image.png) Cool!",
-            vec![FoundLink{
-                start: 26,
-                end: 64,
-                link: LinkType::Url(Url::parse("https://imgy.io/image.png").unwrap()),
-                content: ContentType::Image("Alt text"),
-            }]
-        ),
+    #[test]
+    fn test_anchors() {
+        let text = "# Intro\n\n## Intro\n\nSetup\n-----\n\n<a name=\"custom\"></a>\n\n\
+            `<a id=\"span\">` <span id=\"Mixed_Case\">x</span>\n\n```\n<a id=\"fence\">\n```\n";
+        let anchors = anchors(&crate::markdown::parse(text));
+
+        assert_eq!(
+            vec!["intro", "intro-1", "setup", "custom", "Mixed_Case"],
+            anchors
+        );
+        assert!(has_anchor(&anchors, "setup"));
+        assert!(!has_anchor(&anchors, "Setup"));
+        assert!(!has_anchor(&anchors, "mixed_case"));
     }
-}
 
+    #[test]
+    fn test_lint() {
+        let tmp = tempdir::TempDir::new("link_test").unwrap();
+        let root = tmp.path().canonicalize().unwrap();
+
+        std::fs::create_dir_all(root.join("docs/guides")).unwrap();
+        std::fs::write(
+            root.join("docs/guides/setup.md"),
+            "# Setup\n\n## Install it\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("docs/logo.png"), "").unwrap();
+        std::fs::write(root.join("docs/my file.md"), "").unwrap();
+
+        let text = "# Docs\n\n\
+                    [ok](guides/setup.md#install-it) [ok](../docs/logo.png#L1) [ok](#docs)\n\
+                    [ok](/docs/guides/) [ok](https://example.com/missing.md) [ok](my%20file.md)\n\
+                    [bad](guides/missing.md) [bad](guides/setup.md#nope)\n\
+                    `[ok](missing.md)` [bad](#nope)\n\n\
+                    ```\n[ok](missing.md)\n```\n";
+
+        let source = Source::new(text).at(Some(root.join("docs/index.md")), Some(root.clone()));
+        let issues = BrokenLinks::default().lint(&source);
+
+        assert_eq!(
+            vec![
+                (4, 0, 24, "Link target `guides/missing.md` does not exist"),
+                (4, 25, 52, "No heading for `#nope` in `guides/setup.md`"),
+                (5, 19, 31, "No heading for `#nope` in this document"),
+            ],
+            issues
+                .iter()
+                .map(|x| (x.line_start, x.col_start, x.col_end, x.msg.as_str()))
+                .collect::<Vec<_>>()
+        );
+
+        // Without a file, only links within the document can be checked.
+        assert_eq!(1, BrokenLinks::default().lint(&Source::new(text)).len());
+
+        tmp.close().unwrap();
+    }
+}
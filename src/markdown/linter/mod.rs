@@ -1,6 +1,89 @@
+mod external;
 mod hlevels;
-pub mod link;
+mod images;
+mod link;
+mod references;
 
 use super::handler;
+use super::state::{Lint, Options, Severity};
 
 pub use hlevels::HeadingLevels;
+pub use images::{ImageAlt, Images};
+pub use link::BrokenLinks;
+pub(crate) use link::{resolve, split};
+pub use references::{DuplicateDefinitions, UndefinedReferences, UnusedDefinitions};
+
+use handler::lint::Linter;
+
+/// Every lint rule docula knows about, with their default options.
+pub fn all() -> Vec<Box<dyn Linter>> {
+    vec![
+        Box::new(HeadingLevels::default()),
+        Box::new(BrokenLinks::default()),
+        Box::new(Images::default()),
+        Box::new(ImageAlt::default()),
+        Box::new(UndefinedReferences::default()),
+        Box::new(UnusedDefinitions::default()),
+        Box::new(DuplicateDefinitions::default()),
+    ]
+}
+
+/// A rule that is turned on, with its options applied.
+pub struct Rule {
+    pub linter: Box<dyn Linter>,
+    pub severity: Severity,
+}
+
+/// Applies the settings to the rules, leaving out those that are off.
+pub fn configure(linters: &[Box<dyn Linter>], settings: &Lint) -> crate::Result<Vec<Rule>> {
+    for id in settings.rules.keys() {
+        if !linters.iter().any(|x| x.id() == id) {
+            Err(crate::Error::Config(format!("unknown lint rule `{}`", id)))?;
+        }
+    }
+
+    let mut res = Vec::new();
+
+    for linter in linters.iter() {
+        let severity = settings
+            .severity(linter.id())
+            .unwrap_or_else(|| linter.default_severity());
+
+        if severity == Severity::Off {
+            continue;
+        }
+
+        let linter = linter
+            .configure(&settings.options(linter.id()))
+            .map_err(|e| {
+                crate::Error::Config(format!(
+                    "invalid options for lint rule `{}`: {}",
+                    linter.id(),
+                    e
+                ))
+            })?;
+
+        res.push(Rule { linter, severity });
+    }
+
+    Ok(res)
+}
+
+/// The options of a rule that has none, which turns away any that are given.
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NoOptions {}
+
+/// Reads the options of a rule into the type the rule keeps them in.
+fn options<T: serde::de::DeserializeOwned>(options: &Options) -> Result<T, String> {
+    let value = serde_json::Value::Object(options.clone().into_iter().collect());
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+/// Lists the options of a rule, the other way around.
+fn to_options<T: serde::Serialize>(options: &T) -> Options {
+    match serde_json::to_value(options) {
+        Ok(serde_json::Value::Object(x)) => x.into_iter().collect(),
+        _ => Options::new(),
+    }
+}
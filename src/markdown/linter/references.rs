@@ -0,0 +1,213 @@
+use super::handler::lint::{Issue, Linter, Source};
+use super::{NoOptions, Options};
+use crate::markdown::parser::reference::label;
+use crate::markdown::parser::token::{self, Block, BlockToken, Inline};
+use crate::markdown::state::Severity;
+use std::collections::{HashMap, HashSet};
+
+/// Checks that reference links like `[text][id]` have a definition. Without
+/// one they are shown as they were written, brackets and all.
+#[derive(Default)]
+pub struct UndefinedReferences {}
+
+impl Linter for UndefinedReferences {
+    fn id(&self) -> &'static str {
+        "undefined-references"
+    }
+
+    fn name(&self) -> &'static str {
+        "Undefined References"
+    }
+
+    fn description(&self) -> &'static str {
+        "Checks that reference links have a definition."
+    }
+
+    fn configure(&self, options: &Options) -> Result<Box<dyn Linter>, String> {
+        super::options::<NoOptions>(options)?;
+        Ok(Box::new(UndefinedReferences {}))
+    }
+
+    fn lint(&self, source: &Source) -> Vec<Issue> {
+        token::inlines(&source.document)
+            .into_iter()
+            .filter_map(|x| match &x.token {
+                Inline::Link {
+                    link: None,
+                    id: Some(id),
+                    ..
+                }
+                | Inline::Image {
+                    link: None,
+                    id: Some(id),
+                    ..
+                } => Some(Issue {
+                    line_start: x.line_start,
+                    line_end: x.line_start,
+                    col_start: x.position,
+                    col_end: x.end,
+                    content: source.line(x.line_start).to_owned(),
+                    msg: format!("No definition for reference `{}`", id),
+                    edits: Vec::new(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Checks that every link reference definition is used by some link.
+#[derive(Default)]
+pub struct UnusedDefinitions {}
+
+impl Linter for UnusedDefinitions {
+    fn id(&self) -> &'static str {
+        "unused-definitions"
+    }
+
+    fn name(&self) -> &'static str {
+        "Unused Definitions"
+    }
+
+    fn description(&self) -> &'static str {
+        "Checks that link reference definitions are used."
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn configure(&self, options: &Options) -> Result<Box<dyn Linter>, String> {
+        super::options::<NoOptions>(options)?;
+        Ok(Box::new(UnusedDefinitions {}))
+    }
+
+    fn lint(&self, source: &Source) -> Vec<Issue> {
+        let used: HashSet<String> = token::inlines(&source.document)
+            .into_iter()
+            .filter_map(|x| match &x.token {
+                Inline::Link { id: Some(id), .. } | Inline::Image { id: Some(id), .. } => {
+                    Some(label(id))
+                }
+                _ => None,
+            })
+            .collect();
+
+        definitions(source)
+            .into_iter()
+            .filter(|(_, id)| !used.contains(&label(id)))
+            .map(|(block, id)| issue(source, block, format!("Definition `{}` is not used", id)))
+            .collect()
+    }
+}
+
+/// Checks that no two link reference definitions have the same ID, as only
+/// the first one is ever used.
+#[derive(Default)]
+pub struct DuplicateDefinitions {}
+
+impl Linter for DuplicateDefinitions {
+    fn id(&self) -> &'static str {
+        "duplicate-definitions"
+    }
+
+    fn name(&self) -> &'static str {
+        "Duplicate Definitions"
+    }
+
+    fn description(&self) -> &'static str {
+        "Checks that link reference definitions have unique IDs."
+    }
+
+    fn configure(&self, options: &Options) -> Result<Box<dyn Linter>, String> {
+        super::options::<NoOptions>(options)?;
+        Ok(Box::new(DuplicateDefinitions {}))
+    }
+
+    fn lint(&self, source: &Source) -> Vec<Issue> {
+        let mut first = HashMap::new();
+
+        definitions(source)
+            .into_iter()
+            .filter_map(|(block, id)| match first.get(&label(id)) {
+                Some(line) => Some(issue(
+                    source,
+                    block,
+                    format!("`{}` is already defined on line {}", id, line + 1),
+                )),
+                None => {
+                    first.insert(label(id), block.line_start);
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// The definitions of the document with their IDs.
+fn definitions<'a>(source: &'a Source) -> Vec<(&'a BlockToken, &'a str)> {
+    token::blocks(&source.document)
+        .into_iter()
+        .filter_map(|x| match &x.token {
+            Block::Definition { id, .. } => Some((x, id.as_str())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// An issue spanning the line of a definition, without its indent.
+fn issue(source: &Source, block: &BlockToken, msg: String) -> Issue {
+    let line = source.line(block.line_start);
+
+    Issue {
+        line_start: block.line_start,
+        line_end: block.line_start,
+        col_start: line.len() - line.trim_start().len(),
+        col_end: line.trim_end().len(),
+        content: line.to_owned(),
+        msg,
+        edits: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEXT: &str = "See [a][one], [two] and [b][three].\n\n\
+                        [one]: a.md\n  [Two]: b.md\n[four]: d.md\n[ONE]: e.md\n";
+
+    fn messages(issues: Vec<Issue>) -> Vec<(usize, usize, String)> {
+        issues
+            .into_iter()
+            .map(|x| (x.line_start, x.col_start, x.msg))
+            .collect()
+    }
+
+    #[test]
+    fn test_undefined_references() {
+        assert_eq!(
+            vec![(0, 24, "No definition for reference `three`".to_owned())],
+            messages(UndefinedReferences::default().lint(&Source::new(TEXT)))
+        );
+    }
+
+    #[test]
+    fn test_unused_definitions() {
+        assert_eq!(
+            vec![(4, 0, "Definition `four` is not used".to_owned())],
+            messages(UnusedDefinitions::default().lint(&Source::new(TEXT)))
+        );
+    }
+
+    #[test]
+    fn test_duplicate_definitions() {
+        assert_eq!(
+            vec![(5, 0, "`ONE` is already defined on line 3".to_owned())],
+            messages(DuplicateDefinitions::default().lint(&Source::new(TEXT)))
+        );
+        assert!(DuplicateDefinitions::default()
+            .configure(&Options::from([("x".to_owned(), 1.into())]))
+            .is_err());
+    }
+}
@@ -1,3 +1,52 @@
+//! Parsing, linting and formatting of Markdown documents. The functions here
+//! work on text, so they can be used without any files:
+//!
+//! ```
+//! let state = docula::markdown::state::State::default();
+//! let issues = docula::markdown::lint("# Title\n\n### Detail\n", &state)?;
+//!
+//! assert_eq!(1, issues.len());
+//! # Ok::<(), docula::Error>(())
+//! ```
+
+pub(crate) mod assets;
+pub(crate) mod detect;
+pub mod files;
+pub mod fix;
+pub mod graph;
 pub mod handler;
 pub mod linter;
 pub mod parser;
+pub mod report;
+pub mod state;
+pub(crate) mod suppress;
+
+pub use handler::lint::{Finding, Issue, Linter, Source};
+
+/// Splits a document into its blocks.
+pub fn parse(content: &str) -> parser::token::Document {
+    let mut document = parser::lexer::lex_analysis(content);
+    parser::reference::resolve(&mut document);
+
+    document
+}
+
+/// Runs every lint rule that is not turned off in the settings, leaving
+/// out what `docula-disable` comments silence, like `markdown lint` does.
+/// The findings have an empty path.
+pub fn lint(content: &str, state: &state::State) -> crate::Result<Vec<Finding>> {
+    let rules = linter::configure(&linter::all(), &state.lint)?;
+
+    Ok(handler::lint::lint(
+        &rules,
+        std::path::Path::new(""),
+        &Source::new(content),
+    ))
+}
+
+/// Formats a document the way `markdown fmt` would.
+pub fn format(content: &str, state: &state::State) -> String {
+    let content = handler::fmt::fix_heading_style(content, state.fmt.heading_style);
+
+    handler::fmt::fix_line_length(&content, state.fmt.width)
+}
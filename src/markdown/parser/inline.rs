@@ -0,0 +1,413 @@
+//! Splits the text of a block into inline tokens: code spans, links, images
+//! and the plain text around them. Emphasis is left in the text.
+
+use super::token::{Inline, InlineToken, Text};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    static ref AUTOLINK_REGEX: Regex =
+        Regex::new(r"^<([A-Za-z][A-Za-z0-9+.\-]{1,31}:[^<>\s]*)>").unwrap();
+    static ref EMAIL_AUTOLINK_REGEX: Regex =
+        Regex::new(r"^<([A-Za-z0-9.!#$%&'*+/=?^_`{|}~\-]+@[A-Za-z0-9\-]+(?:\.[A-Za-z0-9\-]+)*)>")
+            .unwrap();
+}
+
+/// Parses the text of a line, which starts at `position` in line `line`.
+pub fn parse(s: &str, line: usize, position: usize) -> Text {
+    let mut res = Text::new();
+    let bytes = s.as_bytes();
+    let mut chunk = 0;
+    let mut i = 0;
+
+    while i < s.len() {
+        let found = match bytes[i] {
+            b'\\' => {
+                i = next(s, next(s, i));
+                continue;
+            }
+            b'`' => match code(s, i) {
+                Ok(x) => Some(x),
+                Err(end) => {
+                    i = end;
+                    continue;
+                }
+            },
+            b'!' if bytes.get(i + 1) == Some(&b'[') => image(s, i, line, position),
+            b'[' => link(s, i, line, position),
+            b'<' => autolink(s, i),
+            _ => None,
+        };
+
+        match found {
+            Some((token, end)) => {
+                if chunk < i {
+                    res.push(chunk_token(&s[chunk..i], line, position + chunk));
+                }
+
+                res.push(InlineToken {
+                    line_start: line,
+                    position: position + i,
+                    end: position + end,
+                    token,
+                });
+
+                i = end;
+                chunk = end;
+            }
+            None => i = next(s, i),
+        }
+    }
+
+    if chunk < s.len() || res.is_empty() {
+        res.push(chunk_token(&s[chunk..], line, position + chunk));
+    }
+
+    res
+}
+
+fn chunk_token(s: &str, line: usize, position: usize) -> InlineToken {
+    InlineToken {
+        line_start: line,
+        position,
+        end: position + s.len(),
+        token: Inline::Chunk(s.to_string()),
+    }
+}
+
+/// Where the character after the one at `i` starts.
+fn next(s: &str, i: usize) -> usize {
+    i + s[i..].chars().next().map_or(1, |x| x.len_utf8())
+}
+
+fn skip_whitespace(s: &str, i: usize) -> usize {
+    i + s[i..].len() - s[i..].trim_start().len()
+}
+
+/// A code span, closed by a run of backticks as long as the one that opens
+/// it. Without one, the opening run is plain text and its end is returned.
+fn code(s: &str, i: usize) -> Result<(Inline, usize), usize> {
+    let run = s[i..].bytes().take_while(|x| *x == b'`').count();
+    let start = i + run;
+    let mut j = start;
+
+    while j < s.len() {
+        if s.as_bytes()[j] != b'`' {
+            j += 1;
+            continue;
+        }
+
+        let len = s[j..].bytes().take_while(|x| *x == b'`').count();
+
+        if len == run {
+            let content = &s[start..j];
+            let content = match content.len() > 2
+                && content.starts_with(' ')
+                && content.ends_with(' ')
+                && !content.trim().is_empty()
+            {
+                true => &content[1..content.len() - 1],
+                false => content,
+            };
+
+            return Ok((Inline::Code(content.to_string()), j + len));
+        }
+
+        j += len;
+    }
+
+    Err(start)
+}
+
+/// Where the `]` that closes the `[` at `i` is.
+fn closing_bracket(s: &str, i: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut j = i;
+
+    while j < s.len() {
+        match s.as_bytes()[j] {
+            b'\\' => {
+                j = next(s, next(s, j));
+                continue;
+            }
+            b'[' => depth += 1,
+            b']' => {
+                depth -= 1;
+
+                if depth == 0 {
+                    return Some(j);
+                }
+            }
+            _ => (),
+        }
+
+        j += 1;
+    }
+
+    None
+}
+
+/// What follows the text of a link or image: `(destination "title")` or a
+/// reference like `[id]`, or `[]` when the text is the ID.
+fn target(s: &str, text: &str, i: usize) -> Option<(Target, usize)> {
+    match s.as_bytes().get(i)? {
+        b'(' => {
+            let (link, title, end) = destination(s, i + 1)?;
+            Some((Target::Inline(link, title), end))
+        }
+        b'[' => {
+            let close = i + 1 + s[i + 1..].find(']')?;
+            let id = &s[i + 1..close];
+
+            if id.contains('[') {
+                return None;
+            }
+
+            let id = if id.trim().is_empty() { text } else { id };
+            Some((Target::Reference(id.to_string()), close + 1))
+        }
+        _ => None,
+    }
+}
+
+enum Target {
+    Inline(String, Option<String>),
+    Reference(String),
+}
+
+fn destination(s: &str, i: usize) -> Option<(String, Option<String>, usize)> {
+    let bytes = s.as_bytes();
+    let mut i = skip_whitespace(s, i);
+
+    let link = if bytes.get(i) == Some(&b'<') {
+        let close = i + 1 + s[i + 1..].find('>')?;
+        let link = &s[i + 1..close];
+
+        if link.contains('<') {
+            return None;
+        }
+
+        i = close + 1;
+        link
+    } else {
+        let start = i;
+        let mut depth = 0;
+
+        while i < s.len() {
+            match bytes[i] {
+                b'\\' => {
+                    i = next(s, next(s, i));
+                    continue;
+                }
+                b'(' => depth += 1,
+                b')' if depth == 0 => break,
+                b')' => depth -= 1,
+                x if x.is_ascii_whitespace() => break,
+                _ => (),
+            }
+
+            i += 1;
+        }
+
+        &s[start..i.min(s.len())]
+    };
+
+    i = skip_whitespace(s, i);
+
+    let title = match bytes.get(i) {
+        Some(&open @ (b'"' | b'\'' | b'(')) => {
+            let close = if open == b'(' { ')' } else { open as char };
+            let end = i + 1 + s[i + 1..].find(close)?;
+            let title = &s[i + 1..end];
+
+            i = skip_whitespace(s, end + 1);
+            Some(title.to_string())
+        }
+        _ => None,
+    };
+
+    match bytes.get(i) {
+        Some(b')') => Some((link.to_string(), title, i + 1)),
+        _ => None,
+    }
+}
+
+fn link(s: &str, i: usize, line: usize, position: usize) -> Option<(Inline, usize)> {
+    let close = closing_bracket(s, i)?;
+    let raw = &s[i + 1..close];
+    let text = Some(parse(raw, line, position + i + 1));
+
+    Some(match target(s, raw, close + 1)? {
+        (Target::Inline(link, title), end) => (
+            Inline::Link {
+                text,
+                link: Some(link),
+                title,
+                id: None,
+            },
+            end,
+        ),
+        (Target::Reference(id), end) => (
+            Inline::Link {
+                text,
+                link: None,
+                title: None,
+                id: Some(id),
+            },
+            end,
+        ),
+    })
+}
+
+fn image(s: &str, i: usize, line: usize, position: usize) -> Option<(Inline, usize)> {
+    let close = closing_bracket(s, i + 1)?;
+    let raw = &s[i + 2..close];
+    let alt = parse(raw, line, position + i + 2);
+
+    Some(match target(s, raw, close + 1)? {
+        (Target::Inline(link, title), end) => (
+            Inline::Image {
+                alt,
+                link: Some(link),
+                title,
+                id: None,
+            },
+            end,
+        ),
+        (Target::Reference(id), end) => (
+            Inline::Image {
+                alt,
+                link: None,
+                title: None,
+                id: Some(id),
+            },
+            end,
+        ),
+    })
+}
+
+fn autolink(s: &str, i: usize) -> Option<(Inline, usize)> {
+    let (link, end) = match AUTOLINK_REGEX.captures(&s[i..]) {
+        Some(caps) => (caps[1].to_string(), i + caps[0].len()),
+        None => {
+            let caps = EMAIL_AUTOLINK_REGEX.captures(&s[i..])?;
+            (format!("mailto:{}", &caps[1]), i + caps[0].len())
+        }
+    };
+
+    Some((
+        Inline::Link {
+            text: None,
+            link: Some(link),
+            title: None,
+            id: None,
+        },
+        end,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tokens(input: &str) -> Vec<Inline> {
+        parse(input, 0, 0).into_iter().map(|x| x.token).collect()
+    }
+
+    fn chunk(s: &str) -> Inline {
+        Inline::Chunk(s.to_string())
+    }
+
+    #[test]
+    fn test_plain_text() {
+        assert_eq!(vec![chunk("Hello, world")], tokens("Hello, world"));
+        assert_eq!(vec![chunk("")], tokens(""));
+    }
+
+    #[test]
+    fn test_links() {
+        let res = parse(
+            r#"See [the *setup*](./setup.md#install "Setup") and <https://a.io>."#,
+            3,
+            2,
+        );
+
+        assert_eq!(
+            InlineToken {
+                line_start: 3,
+                position: 6,
+                end: 47,
+                token: Inline::Link {
+                    text: Some(vec![InlineToken {
+                        line_start: 3,
+                        position: 7,
+                        end: 18,
+                        token: chunk("the *setup*"),
+                    }]),
+                    link: Some("./setup.md#install".to_string()),
+                    title: Some("Setup".to_string()),
+                    id: None,
+                },
+            },
+            res[1]
+        );
+        assert_eq!(
+            Inline::Link {
+                text: None,
+                link: Some("https://a.io".to_string()),
+                title: None,
+                id: None,
+            },
+            res[3].token
+        );
+        assert_eq!(
+            vec![(2, 6), (6, 47), (47, 52), (52, 66), (66, 67)],
+            res.iter().map(|x| (x.position, x.end)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_images_and_references() {
+        assert_eq!(
+            vec![
+                Inline::Image {
+                    alt: vec![InlineToken {
+                        line_start: 0,
+                        position: 2,
+                        end: 5,
+                        token: chunk("Alt"),
+                    }],
+                    link: Some("a b.png".to_string()),
+                    title: None,
+                    id: None,
+                },
+                chunk(" "),
+                Inline::Link {
+                    text: Some(vec![InlineToken {
+                        line_start: 0,
+                        position: 19,
+                        end: 22,
+                        token: chunk("Foo"),
+                    }]),
+                    link: None,
+                    title: None,
+                    id: Some("Foo".to_string()),
+                },
+            ],
+            tokens("![Alt](<a b.png>) [Foo][]")
+        );
+    }
+
+    #[test]
+    fn test_not_links() {
+        assert_eq!(
+            vec![
+                chunk(r"\[a](b) "),
+                Inline::Code("[c](d)".to_string()),
+                chunk(" [e] (f) [g](h"),
+            ],
+            tokens(r"\[a](b) `[c](d)` [e] (f) [g](h")
+        );
+        assert_eq!(vec![chunk("``a`")], tokens("``a`"));
+    }
+}
@@ -1,19 +1,33 @@
-use super::token;
+use super::{inline, token};
 use lazy_static::lazy_static;
 use regex::Regex;
 
 lazy_static! {
-    static ref ATX_HEADING_REGEX: Regex = Regex::new(r"^\s*(#{1,6})\s*(.*?)\s*#*$").unwrap();
+    static ref ATX_HEADING_REGEX: Regex = Regex::new(r"^ {0,3}(#{1,6})(?:[ \t]+(.*?))??(?:[ \t]+#+)?[ \t]*$").unwrap();
     static ref SETEX_HEADING_1_REGEX: Regex = Regex::new(r"^\s*={2,}\s*$").unwrap();
     static ref SETEX_HEADING_2_REGEX: Regex = Regex::new(r"^\s*-{2,}\s*$").unwrap();
-    static ref CODE_BLOCK_REGEX: Regex = Regex::new(r"^\s*```\s*$").unwrap();
+    static ref CODE_BLOCK_REGEX: Regex = Regex::new(r"^ {0,3}(`{3,}|~{3,})\s*([^`\s]*)[^`]*$").unwrap();
+    static ref CODE_BLOCK_END_REGEX: Regex = Regex::new(r"^ {0,3}(`{3,}|~{3,})\s*$").unwrap();
     static ref CODE_BLOCK_INDENT_REGEX: Regex = Regex::new("r^$").unwrap();
     static ref QUOTE_BLOCK_REGEX: Regex = Regex::new(r"^\s{0,3}>\s?(.*?)\s*$").unwrap();
     static ref QUOTE_BLOCK_CLEAN_REGEX: Regex = Regex::new(r"^\s{0,3}(>\s?)?(.*?)\s*$").unwrap();
+    static ref DEFINITION_REGEX: Regex = Regex::new(r#"^ {0,3}\[((?:[^\[\]\\]|\\.)+)\]:[ \t]*(?:<([^<>]*)>|(\S+))(?:[ \t]+(?:"([^"]*)"|'([^']*)'|\(([^()]*)\)))?[ \t]*$"#).unwrap();
+    static ref HORIZONTAL_RULE_REGEX: Regex = Regex::new(r"^ {0,3}(?:(?:\*[ \t]*){3,}|(?:-[ \t]*){3,}|(?:_[ \t]*){3,})$").unwrap();
 }
 
 pub fn lex_analysis(input: &str) -> token::Document {
+    lex(input).0
+}
+
+/// The lines of every fenced code block at the top of the document, fences
+/// included. Their text is never read as Markdown.
+pub fn fenced_lines(input: &str) -> Vec<std::ops::Range<usize>> {
+    lex(input).1
+}
+
+fn lex(input: &str) -> (token::Document, Vec<std::ops::Range<usize>>) {
     let mut document = token::Document::new();
+    let mut fenced = Vec::new();
     let lines: Vec<&str> = input.lines().collect();
     let mut skip = 0;
 
@@ -31,6 +45,7 @@ pub fn lex_analysis(input: &str) -> token::Document {
         }
 
         if let Some(x) = code_block(line, num, &lines) {
+            fenced.push(num..num + x.1 + 2);
             document.push(x.0);
             skip += x.1 + 1;
             continue
@@ -38,7 +53,7 @@ pub fn lex_analysis(input: &str) -> token::Document {
 
         if let Some(x) = quote_block(line, num, &lines) {
             document.push(x.0);
-            skip += x.1 + 1;
+            skip += x.1 - 1;
             continue
         }
 
@@ -49,28 +64,56 @@ pub fn lex_analysis(input: &str) -> token::Document {
                 continue;
             }
         }
+
+        if HORIZONTAL_RULE_REGEX.is_match(line) {
+            document.push(token::BlockToken{
+                line_start: num,
+                token: token::Block::HorizontalRule,
+            });
+            continue
+        }
+
+        if let Some(x) = indented_code_block(line, num, &lines) {
+            document.push(x.0);
+            skip += x.1 - 1;
+            continue
+        }
+
+        if let Some(x) = definition(line, num) {
+            document.push(x);
+            continue
+        }
+
+        if let Some(x) = paragraph(line, num, &lines) {
+            document.push(x.0);
+            skip += x.1 - 1;
+            continue
+        }
     }
 
-    document
+    (document, fenced)
 }
 
 fn atx_header_from_line(line: &str, num: usize) -> Option<token::BlockToken> {
     let caps = ATX_HEADING_REGEX.captures(line)?;
     let depth = caps.get(1)?.as_str().len();
-    let cap = caps.get(2)?;
+    let (text, position) = caps.get(2).map_or(("", line.len()), |x| (x.as_str(), x.start()));
 
     Some(token::BlockToken{
         line_start: num,
         token: token::Block::Heading {
             level: depth,
-            content: get_content(cap.as_str(), num, cap.start()),
+            content: get_content(text, num, position),
             style: token::HeadingStyle::Atx,
         }
     })
 }
 
 fn setex_header_from_line(line: &str, next: &str, num: usize) -> Option<token::BlockToken> {
-    if SETEX_HEADING_1_REGEX.is_match(next) {
+    // Without text above it, the underline is a rule or nothing at all.
+    if line.trim().is_empty() {
+        None
+    } else if SETEX_HEADING_1_REGEX.is_match(next) {
         Some(token::BlockToken{
             line_start: num,
             token: token::Block::Heading {
@@ -93,39 +136,42 @@ fn setex_header_from_line(line: &str, next: &str, num: usize) -> Option<token::B
     }
 }
 
-fn code_block(line: &str, num: usize, lines: &Vec<&str>) -> Option<(token::BlockToken, usize)> {
-    if !CODE_BLOCK_REGEX.is_match(line) {
-        return None
-    }
+/// A fenced code block. It is closed by a fence of the same kind that is at
+/// least as long, or else runs to the end of the document.
+fn code_block(line: &str, num: usize, lines: &[&str]) -> Option<(token::BlockToken, usize)> {
+    let caps = CODE_BLOCK_REGEX.captures(line)?;
+    let fence = caps.get(1)?.as_str();
+    let tag = caps.get(2).map(|x| x.as_str()).filter(|x| !x.is_empty());
+
+    let closes = |x: &str| match CODE_BLOCK_END_REGEX.captures(x) {
+        Some(caps) => {
+            let end = &caps[1];
+            end.starts_with(&fence[..1]) && end.len() >= fence.len()
+        },
+        None => false,
+    };
 
-    if lines.get(num + 1).is_none() {
-        return None
-    }
-    
     let lines = lines[num+1..].iter()
-        .take_while(|x| !CODE_BLOCK_REGEX.is_match(x))
-        .map(|x| x.clone().to_string())
+        .take_while(|x| !closes(x))
+        .map(|x| x.to_string())
         .collect::<Vec<String>>();
 
     Some((token::BlockToken{
         line_start: num,
         token: token::Block::BlockCode { 
-            tag: None,
+            tag: tag.map(String::from),
             content: lines.join("\n"), 
         }
     }, lines.len()))
 }
 
-fn fix_indent(doc: &mut token::Document, line_num: usize, indents: &Vec<usize>) {
+fn fix_indent(doc: &mut token::Document, line_num: usize, indents: &[usize]) {
     doc.iter_mut().for_each(|x| {
         x.line_start += line_num;
 
         match &mut x.token {
-            token::Block::Heading { content, .. } => {
-                content.iter_mut().for_each(|x| {
-                    x.position += indents[x.line_start];
-                    x.line_start += line_num;
-                })
+            token::Block::Heading { content, .. } | token::Block::Paragraph(content) => {
+                fix_text_indent(content, line_num, indents)
             },
             token::Block::BlockQuote(x) => {
                 fix_indent(x, line_num, indents)
@@ -135,18 +181,126 @@ fn fix_indent(doc: &mut token::Document, line_num: usize, indents: &Vec<usize>)
     });
 }
 
-fn quote_block(line: &str, num: usize, lines: &Vec<&str>) -> Option<(token::BlockToken, usize)> {
+fn fix_text_indent(text: &mut token::Text, line_num: usize, indents: &[usize]) {
+    text.iter_mut().for_each(|x| {
+        x.position += indents[x.line_start];
+        x.end += indents[x.line_start];
+
+        match &mut x.token {
+            token::Inline::Emphasis(text)
+            | token::Inline::MoreEmphasis(text)
+            | token::Inline::Link { text: Some(text), .. }
+            | token::Inline::Image { alt: text, .. } => fix_text_indent(text, line_num, indents),
+            _ => (),
+        }
+
+        x.line_start += line_num;
+    })
+}
+
+/// Lines indented by four spaces or a tab, which don't continue a paragraph.
+fn indented_code_block(line: &str, num: usize, lines: &[&str]) -> Option<(token::BlockToken, usize)> {
+    let indented = |x: &str| x.starts_with("    ") || x.starts_with('\t');
+
+    if !indented(line) {
+        return None
+    }
+
+    let mut len = lines[num..].iter()
+        .take_while(|x| x.trim().is_empty() || indented(x))
+        .count();
+
+    while lines[num + len - 1].trim().is_empty() {
+        len -= 1;
+    }
+
+    let content = lines[num..num + len].iter()
+        .map(|x| x.strip_prefix("    ").or_else(|| x.strip_prefix('\t')).unwrap_or(""))
+        .collect::<Vec<&str>>();
+
+    Some((token::BlockToken{
+        line_start: num,
+        token: token::Block::BlockCode {
+            tag: None,
+            content: content.join("\n"),
+        }
+    }, len))
+}
+
+/// A link reference definition on a line of its own. Definitions can't
+/// interrupt a paragraph, so the lines of one are never checked.
+fn definition(line: &str, num: usize) -> Option<token::BlockToken> {
+    let caps = DEFINITION_REGEX.captures(line)?;
+    let id = caps.get(1)?.as_str();
+
+    if id.trim().is_empty() {
+        return None
+    }
+
+    let link = caps.get(2).or_else(|| caps.get(3))?.as_str();
+    let title = caps.get(4).or_else(|| caps.get(5)).or_else(|| caps.get(6));
+
+    Some(token::BlockToken{
+        line_start: num,
+        token: token::Block::Definition {
+            id: id.to_string(),
+            link: link.to_string(),
+            title: title.map(|x| x.as_str().to_string()),
+        },
+    })
+}
+
+/// Lines of text up to a blank line or the start of another block. A line
+/// that is underlined is left for the heading it makes.
+fn paragraph(line: &str, num: usize, lines: &[&str]) -> Option<(token::BlockToken, usize)> {
+    if line.trim().is_empty() {
+        return None
+    }
+
+    let mut len = 1;
+
+    while let Some(x) = lines.get(num + len) {
+        let starts_block = x.trim().is_empty()
+            || ATX_HEADING_REGEX.is_match(x)
+            || CODE_BLOCK_REGEX.is_match(x)
+            || QUOTE_BLOCK_REGEX.is_match(x)
+            || HORIZONTAL_RULE_REGEX.is_match(x)
+            || lines.get(num + len + 1).is_some_and(|next| {
+                SETEX_HEADING_1_REGEX.is_match(next) || SETEX_HEADING_2_REGEX.is_match(next)
+            });
+
+        if starts_block {
+            break
+        }
+
+        len += 1;
+    }
+
+    let content = lines[num..num + len].iter().enumerate()
+        .flat_map(|(i, x)| {
+            let trimmed = x.trim_start();
+            inline::parse(trimmed.trim_end(), num + i, x.len() - trimmed.len())
+        })
+        .collect();
+
+    Some((token::BlockToken{
+        line_start: num,
+        token: token::Block::Paragraph(content),
+    }, len))
+}
+
+fn quote_block(line: &str, num: usize, lines: &[&str]) -> Option<(token::BlockToken, usize)> {
     if !QUOTE_BLOCK_REGEX.is_match(line) {
         return None
     }
 
     let lines = lines[num..].iter()
         .take_while(|x| !x.is_empty())
-        .map(|x| x.clone().to_string())
+        .map(|x| x.to_string())
         .collect::<Vec<String>>();
 
     let fixed_lines = lines.iter().map(|x| {
-        match QUOTE_BLOCK_CLEAN_REGEX.captures(&x) {
+        match QUOTE_BLOCK_CLEAN_REGEX.captures(x) {
             None => x.clone(),
             Some(caps) => caps.get(2).map_or(x.clone(), |x| x.as_str().to_string()),
         }
@@ -162,7 +316,7 @@ fn quote_block(line: &str, num: usize, lines: &Vec<&str>) -> Option<(token::Bloc
     fix_indent(&mut document, num, &indents);
 
 
-    return Some((
+    Some((
         token::BlockToken{
             line_start: num,
             token: token::Block::BlockQuote(document),
@@ -171,13 +325,7 @@ fn quote_block(line: &str, num: usize, lines: &Vec<&str>) -> Option<(token::Bloc
 }
 
 fn get_content(s: &str, line: usize, position: usize) -> token::Text {
-    vec![
-        token::InlineToken{
-            line_start: line,
-            position,
-            token: token::Inline::Chunk(s.to_string()),
-        }
-    ]
+    inline::parse(s, line, position)
 }
 
 #[cfg(test)]
@@ -206,6 +354,7 @@ mod test {
                         token::InlineToken {
                             line_start: 0,
                             position: 2,
+                            end: 10,
                             token: token::Inline::Chunk("Header 1".to_owned()),
                         },
                     ],
@@ -230,6 +379,7 @@ mod test {
                         token::InlineToken {
                             line_start: 0,
                             position: 2,
+                            end: 10,
                             token: token::Inline::Chunk("Header 1".to_owned()),
                         },
                     ],
@@ -254,6 +404,7 @@ mod test {
                         token::InlineToken {
                             line_start: 0,
                             position: 0,
+                            end: 8,
                             token: token::Inline::Chunk("Header 1".to_owned()),
                         },
                     ],
@@ -278,6 +429,7 @@ mod test {
                         token::InlineToken {
                             line_start: 0,
                             position: 0,
+                            end: 8,
                             token: token::Inline::Chunk("Header 2".to_owned()),
                         },
                     ],
@@ -328,6 +480,7 @@ mod test {
                     content: vec![token::InlineToken{
                         line_start: 4,
                         position: 3,
+                        end: 6,
                         token: token::Inline::Chunk("Foo".to_string()),
                     }],
                     style: token::HeadingStyle::Atx,
@@ -355,6 +508,7 @@ mod test {
                                 content: vec![token::InlineToken{
                                     line_start: 0,
                                     position: 4,
+                                    end: 7,
                                     token: token::Inline::Chunk("Foo".to_string()),
                                 }],
                                 style: token::HeadingStyle::Atx,
@@ -388,6 +542,7 @@ mod test {
                                             content: vec![token::InlineToken{
                                                 line_start: 3,
                                                 position: 6,
+                                                end: 9,
                                                 token: token::Inline::Chunk("Foo".to_string()),
                                             }],
                                             style: token::HeadingStyle::Atx,
@@ -404,4 +559,108 @@ mod test {
         let result = lex_analysis(input);
         assert_eq!(expected, result);
     }
+
+    #[test]
+    fn test_code_block_with_info_string() {
+        let input = "~~~~rust ignore\n# Foo\n~~~\n```\n~~~~\n## Bar";
+
+        let expected : token::Document = vec![
+            token::BlockToken{
+                line_start: 0,
+                token: token::Block::BlockCode {
+                    tag: Some("rust".to_string()),
+                    content: "# Foo\n~~~\n```".to_string(),
+                }
+            },
+            token::BlockToken{
+                line_start: 5,
+                token: token::Block::Heading {
+                    level: 2,
+                    content: vec![token::InlineToken{
+                        line_start: 5,
+                        position: 3,
+                        end: 6,
+                        token: token::Inline::Chunk("Bar".to_string()),
+                    }],
+                    style: token::HeadingStyle::Atx,
+                }
+            }
+        ];
+
+        let result = lex_analysis(input);
+
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_unclosed_code_block() {
+        let input = "```\n# Foo";
+
+        let expected : token::Document = vec![
+            token::BlockToken{
+                line_start: 0,
+                token: token::Block::BlockCode {
+                    tag: None,
+                    content: "# Foo".to_string(),
+                }
+            }
+        ];
+
+        let result = lex_analysis(input);
+
+        assert_eq!(expected, result);
+    }
+
+    #[test]
+    fn test_not_headings() {
+        let input = "#hashtag\n####### Seven\n\n    # Indented\n\n---";
+        let result = lex_analysis(input);
+
+        assert!(!result.iter().any(|x| matches!(x.token, token::Block::Heading { .. })));
+        assert_eq!(token::Block::HorizontalRule, result[2].token);
+    }
+
+    #[test]
+    fn test_paragraph() {
+        let input = "> Foo\n\nSee [bar](bar.md)\n  and baz.\n# Baz";
+        let result = lex_analysis(input);
+
+        let expected = token::BlockToken{
+            line_start: 2,
+            token: token::Block::Paragraph(vec![
+                token::InlineToken{
+                    line_start: 2,
+                    position: 0,
+                    end: 4,
+                    token: token::Inline::Chunk("See ".to_string()),
+                },
+                token::InlineToken{
+                    line_start: 2,
+                    position: 4,
+                    end: 17,
+                    token: token::Inline::Link{
+                        text: Some(vec![token::InlineToken{
+                            line_start: 2,
+                            position: 5,
+                            end: 8,
+                            token: token::Inline::Chunk("bar".to_string()),
+                        }]),
+                        link: Some("bar.md".to_string()),
+                        title: None,
+                        id: None,
+                    },
+                },
+                token::InlineToken{
+                    line_start: 3,
+                    position: 2,
+                    end: 10,
+                    token: token::Inline::Chunk("and baz.".to_string()),
+                },
+            ]),
+        };
+
+        assert_eq!(3, result.len());
+        assert_eq!(expected, result[1]);
+        assert_eq!(3, token::inlines(&result).iter().filter(|x| x.line_start == 2).count());
+    }
 }
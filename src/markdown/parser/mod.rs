@@ -1,2 +1,4 @@
 pub mod token;
 pub mod lexer;
+pub mod inline;
+pub mod reference;
@@ -0,0 +1,221 @@
+//! Resolves reference links, `[text][id]`, `[id][]` and `[id]`, with the
+//! link reference definitions of the document. Links keep their ID, so it
+//! can still be told which ones were written as references.
+
+use super::inline;
+use super::token::{self, Block, Document, Inline, InlineToken, Text};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashMap;
+
+lazy_static! {
+    static ref SHORTCUT_REGEX: Regex = Regex::new(r"(!?)\[((?:[^\[\]\\]|\\.)+)\]").unwrap();
+}
+
+/// The normalized form of a reference ID, which IDs are matched by: case
+/// is ignored and runs of whitespace count as one space.
+pub fn label(id: &str) -> String {
+    id.split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Fills in the link and title of every reference link that has a
+/// definition. The first definition of an ID is the one that counts.
+pub fn resolve(document: &mut Document) {
+    let mut definitions = HashMap::new();
+
+    for block in token::blocks(document) {
+        if let Block::Definition { id, link, title } = &block.token {
+            definitions
+                .entry(label(id))
+                .or_insert_with(|| (link.clone(), title.clone()));
+        }
+    }
+
+    if !definitions.is_empty() {
+        resolve_blocks(document, &definitions);
+    }
+}
+
+type Definitions = HashMap<String, (String, Option<String>)>;
+
+fn resolve_blocks(document: &mut Document, definitions: &Definitions) {
+    for block in document.iter_mut() {
+        match &mut block.token {
+            Block::Heading { content, .. } | Block::Paragraph(content) => {
+                resolve_text(content, definitions)
+            }
+            Block::BlockQuote(x) => resolve_blocks(x, definitions),
+            Block::OrderedList { items, .. } | Block::UnorderedList { items } => items
+                .iter_mut()
+                .for_each(|x| resolve_blocks(x, definitions)),
+            _ => (),
+        }
+    }
+}
+
+fn resolve_text(text: &mut Text, definitions: &Definitions) {
+    let tokens = std::mem::take(text);
+
+    for mut token in tokens {
+        match &mut token.token {
+            Inline::Link {
+                link: link @ None,
+                title,
+                id: Some(id),
+                ..
+            }
+            | Inline::Image {
+                link: link @ None,
+                title,
+                id: Some(id),
+                ..
+            } => {
+                if let Some((x, y)) = definitions.get(&label(id)) {
+                    *link = Some(x.clone());
+                    *title = y.clone();
+                }
+            }
+            Inline::Emphasis(x) | Inline::MoreEmphasis(x) => resolve_text(x, definitions),
+            Inline::Chunk(x) => {
+                let x = std::mem::take(x);
+                text.extend(shortcuts(&x, &token, definitions));
+                continue;
+            }
+            _ => (),
+        }
+
+        text.push(token);
+    }
+}
+
+/// Splits the links out of a chunk of text that were written as `[id]` or
+/// `![id]`. Brackets without a definition are left as they are.
+fn shortcuts(s: &str, chunk: &InlineToken, definitions: &Definitions) -> Text {
+    let mut res = Text::new();
+    let mut start = 0;
+
+    for caps in SHORTCUT_REGEX.captures_iter(s) {
+        let all = caps.get(0).unwrap();
+        let bracket = caps.get(2).unwrap().start() - 1;
+
+        if s[..bracket].ends_with('\\') {
+            continue;
+        }
+
+        let raw = caps.get(2).unwrap();
+        let (link, title) = match definitions.get(&label(raw.as_str())) {
+            Some(x) => x.clone(),
+            None => continue,
+        };
+
+        let (is_image, position) = match caps[1].is_empty() || s[..all.start()].ends_with('\\') {
+            true => (false, bracket),
+            false => (true, all.start()),
+        };
+
+        if start < position {
+            res.push(chunk_token(&s[start..position], chunk, start));
+        }
+
+        let text = inline::parse(raw.as_str(), chunk.line_start, chunk.position + raw.start());
+        let id = Some(raw.as_str().to_string());
+        let link = Some(link);
+
+        res.push(InlineToken {
+            line_start: chunk.line_start,
+            position: chunk.position + position,
+            end: chunk.position + all.end(),
+            token: match is_image {
+                true => Inline::Image {
+                    alt: text,
+                    link,
+                    title,
+                    id,
+                },
+                false => Inline::Link {
+                    text: Some(text),
+                    link,
+                    title,
+                    id,
+                },
+            },
+        });
+
+        start = all.end();
+    }
+
+    if start < s.len() || res.is_empty() {
+        res.push(chunk_token(&s[start..], chunk, start));
+    }
+
+    res
+}
+
+fn chunk_token(s: &str, chunk: &InlineToken, offset: usize) -> InlineToken {
+    InlineToken {
+        line_start: chunk.line_start,
+        position: chunk.position + offset,
+        end: chunk.position + offset + s.len(),
+        token: Inline::Chunk(s.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::markdown::parse;
+
+    fn links(document: &Document) -> Vec<(usize, Option<String>, Option<String>)> {
+        token::inlines(document)
+            .into_iter()
+            .filter_map(|x| match &x.token {
+                Inline::Link { link, id, .. } | Inline::Image { link, id, .. } => {
+                    Some((x.position, link.clone(), id.clone()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_label() {
+        assert_eq!("foo bar", label("  Foo\t BAR "));
+    }
+
+    #[test]
+    fn test_resolve() {
+        let document = parse(
+            "See [a][Setup], [setup][], [SETUP], ![logo] and [none][x] \\[setup].\n\n\
+             [setup]: ./setup.md \"Setup\"\n[logo]: <img/logo.png>\n[setup]: ./other.md\n",
+        );
+
+        let setup = Some("./setup.md".to_string());
+
+        assert_eq!(
+            vec![
+                (4, setup.clone(), Some("Setup".to_string())),
+                (16, setup.clone(), Some("setup".to_string())),
+                (27, setup, Some("SETUP".to_string())),
+                (
+                    36,
+                    Some("img/logo.png".to_string()),
+                    Some("logo".to_string())
+                ),
+                (48, None, Some("x".to_string())),
+            ],
+            links(&document)
+        );
+        assert_eq!(
+            Block::Definition {
+                id: "setup".to_string(),
+                link: "./setup.md".to_string(),
+                title: Some("Setup".to_string()),
+            },
+            document[1].token
+        );
+        assert_eq!(4, token::blocks(&document).len());
+    }
+}
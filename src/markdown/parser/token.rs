@@ -12,6 +12,8 @@ pub type Text = Vec<InlineToken>;
 pub struct InlineToken {
     pub line_start: usize,
     pub position: usize,
+    /// Where the token ends in its line, just after its last character.
+    pub end: usize,
     pub token: Inline
 }
 
@@ -42,6 +44,14 @@ pub enum Block {
     Paragraph(Text),
 
     HorizontalRule,
+
+    /// A link reference definition, `[id]: link "title"`, which reference
+    /// links like `[text][id]` take their link from.
+    Definition {
+        id: String,
+        link: String,
+        title: Option<String>,
+    },
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -76,3 +86,71 @@ pub enum HeadingStyle {
     Atx,
     Setex,
 }
+
+/// Every inline token of the document, including those nested in other
+/// blocks and tokens, in the order they appear.
+pub fn inlines(document: &Document) -> Vec<&InlineToken> {
+    let mut res = Vec::new();
+
+    for block in document.iter() {
+        match &block.token {
+            Block::Heading { content, .. } | Block::Paragraph(content) => {
+                inlines_of(content, &mut res)
+            }
+            Block::BlockQuote(x) => res.extend(inlines(x)),
+            Block::OrderedList { items, .. } | Block::UnorderedList { items } => {
+                items.iter().for_each(|x| res.extend(inlines(x)))
+            }
+            Block::BlockCode { .. } | Block::HorizontalRule | Block::Definition { .. } => (),
+        }
+    }
+
+    res
+}
+
+/// Every block of the document, including those nested in block quotes and
+/// lists, in the order they appear.
+pub fn blocks(document: &Document) -> Vec<&BlockToken> {
+    let mut res = Vec::new();
+
+    for block in document.iter() {
+        res.push(block);
+
+        match &block.token {
+            Block::BlockQuote(x) => res.extend(blocks(x)),
+            Block::OrderedList { items, .. } | Block::UnorderedList { items } => {
+                items.iter().for_each(|x| res.extend(blocks(x)))
+            }
+            _ => (),
+        }
+    }
+
+    res
+}
+
+fn inlines_of<'a>(text: &'a Text, res: &mut Vec<&'a InlineToken>) {
+    for token in text.iter() {
+        res.push(token);
+
+        match &token.token {
+            Inline::Emphasis(x) | Inline::MoreEmphasis(x) => inlines_of(x, res),
+            Inline::Link { text: Some(x), .. } | Inline::Image { alt: x, .. } => {
+                inlines_of(x, res)
+            }
+            _ => (),
+        }
+    }
+}
+
+/// The text without any markup, like a heading reads when rendered.
+pub fn plain_text(text: &Text) -> String {
+    text.iter()
+        .map(|x| match &x.token {
+            Inline::LineBreak => " ".to_string(),
+            Inline::Chunk(x) | Inline::Code(x) => x.clone(),
+            Inline::Emphasis(x) | Inline::MoreEmphasis(x) => plain_text(x),
+            Inline::Link { text: Some(x), .. } | Inline::Image { alt: x, .. } => plain_text(x),
+            Inline::Link { text: None, link, .. } => link.clone().unwrap_or_default(),
+        })
+        .collect()
+}
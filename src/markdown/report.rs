@@ -0,0 +1,614 @@
+//! Renders lint results for people and for other tools: code scanning
+//! dashboards (SARIF), CI servers (checkstyle, JUnit) and pull request
+//! annotations (GitHub workflow commands).
+
+use super::handler::lint::{Finding, Linter, Report};
+use super::state::Severity;
+use super::{assets, suppress};
+use serde_json::{json, Value};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Pretty,
+    Json,
+    Sarif,
+    Checkstyle,
+    Junit,
+    Github,
+}
+
+/// Renders the report. `linters` describe the rules in formats that list
+/// them, like SARIF.
+pub fn render(format: Format, report: &Report, linters: &[Box<dyn Linter>]) -> String {
+    match format {
+        Format::Pretty => pretty(report),
+        Format::Json => to_json(&json(report)),
+        Format::Sarif => to_json(&sarif(report, linters)),
+        Format::Checkstyle => checkstyle(report),
+        Format::Junit => junit(report),
+        Format::Github => github(report),
+    }
+}
+
+fn pretty(report: &Report) -> String {
+    let mut res = String::new();
+
+    for finding in report.findings.iter() {
+        writeln!(res, "{}", finding).unwrap();
+
+        // Findings about whole files, like unused images, have no line.
+        if !finding.issue.content.is_empty() {
+            writeln!(res, "    {}", finding.issue.content.trim_end()).unwrap();
+        }
+    }
+
+    res
+}
+
+fn to_json(value: &Value) -> String {
+    format!("{}\n", serde_json::to_string_pretty(value).unwrap())
+}
+
+/// The span of a finding, with 1-based lines and columns. Columns count
+/// characters, not bytes, and the end column is the first one after the
+/// issue.
+fn span(report: &Report, finding: &Finding) -> (usize, usize, usize, usize) {
+    let issue = &finding.issue;
+
+    (
+        issue.line_start + 1,
+        report.char_column(finding, issue.line_start, issue.col_start),
+        issue.line_end + 1,
+        report.char_column(finding, issue.line_end, issue.col_end),
+    )
+}
+
+fn json(report: &Report) -> Value {
+    let findings: Vec<Value> = report
+        .findings
+        .iter()
+        .map(|x| {
+            let (line_start, col_start, line_end, col_end) = span(report, x);
+
+            json!({
+                "path": x.path,
+                "rule": x.rule,
+                "severity": x.severity,
+                "line_start": line_start,
+                "col_start": col_start,
+                "line_end": line_end,
+                "col_end": col_end,
+                "message": x.issue.msg,
+                "content": x.issue.content,
+                "fixable": !x.issue.edits.is_empty(),
+            })
+        })
+        .collect();
+
+    Value::Array(findings)
+}
+
+fn sarif(report: &Report, linters: &[Box<dyn Linter>]) -> Value {
+    let mut rules: Vec<(&str, &str, &str, Severity)> = linters
+        .iter()
+        .map(|x| (x.id(), x.name(), x.description(), x.default_severity()))
+        .collect();
+
+    rules.push((
+        suppress::UNUSED,
+        "Unused suppression",
+        "Comments that silence lint rules without anything to silence",
+        Severity::Warning,
+    ));
+    rules.push((
+        assets::UNUSED,
+        "Unused assets",
+        "Images no Markdown document refers to",
+        Severity::Warning,
+    ));
+
+    let results: Vec<Value> = report
+        .findings
+        .iter()
+        .map(|x| {
+            let (line_start, col_start, line_end, col_end) = span(report, x);
+            let mut res = json!({
+                "ruleId": x.rule,
+                "level": sarif_level(x.severity),
+                "message": { "text": x.issue.msg },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": uri(x) },
+                        "region": {
+                            "startLine": line_start,
+                            "startColumn": col_start,
+                            "endLine": line_end,
+                            "endColumn": col_end,
+                        },
+                    },
+                }],
+            });
+
+            if let Some(i) = rules.iter().position(|(id, ..)| *id == x.rule) {
+                res["ruleIndex"] = json!(i);
+            }
+
+            if !x.issue.edits.is_empty() {
+                let replacements: Vec<Value> = x
+                    .issue
+                    .edits
+                    .iter()
+                    .map(|e| {
+                        json!({
+                            "deletedRegion": {
+                                "startLine": e.line_start + 1,
+                                "startColumn": report.char_column(x, e.line_start, e.col_start),
+                                "endLine": e.line_end + 1,
+                                "endColumn": report.char_column(x, e.line_end, e.col_end),
+                            },
+                            "insertedContent": { "text": e.text },
+                        })
+                    })
+                    .collect();
+
+                res["fixes"] = json!([{
+                    "artifactChanges": [{
+                        "artifactLocation": { "uri": uri(x) },
+                        "replacements": replacements,
+                    }],
+                }]);
+            }
+
+            res
+        })
+        .collect();
+
+    let rules: Vec<Value> = rules
+        .into_iter()
+        .map(|(id, name, description, severity)| {
+            json!({
+                "id": id,
+                "name": name,
+                "shortDescription": { "text": description },
+                "defaultConfiguration": { "level": sarif_level(severity) },
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "docula",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    })
+}
+
+fn sarif_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "note",
+        Severity::Off => "none",
+    }
+}
+
+/// The path of a finding as a relative URI, which uses forward slashes on
+/// every platform.
+fn uri(finding: &Finding) -> String {
+    finding
+        .path
+        .components()
+        .map(|x| x.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The linted files followed by any other paths findings are about, like
+/// unused images, each once.
+fn paths(report: &Report) -> Vec<&PathBuf> {
+    let mut res: Vec<&PathBuf> = report.files.iter().collect();
+
+    for finding in report.findings.iter() {
+        if !res.contains(&&finding.path) {
+            res.push(&finding.path);
+        }
+    }
+
+    res
+}
+
+fn checkstyle(report: &Report) -> String {
+    let mut res = String::new();
+
+    writeln!(res, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(res, r#"<checkstyle version="4.3">"#).unwrap();
+
+    for path in paths(report) {
+        let findings: Vec<&Finding> = report.findings.iter().filter(|x| &x.path == path).collect();
+
+        if findings.is_empty() {
+            writeln!(res, r#"  <file name="{}"/>"#, xml(&path.to_string_lossy())).unwrap();
+            continue;
+        }
+
+        writeln!(res, r#"  <file name="{}">"#, xml(&path.to_string_lossy())).unwrap();
+
+        for finding in findings {
+            let (line, col, ..) = span(report, finding);
+
+            writeln!(
+                res,
+                r#"    <error line="{}" column="{}" severity="{}" message="{}" source="docula.{}"/>"#,
+                line,
+                col,
+                finding.severity,
+                xml(&finding.issue.msg),
+                finding.rule
+            )
+            .unwrap();
+        }
+
+        writeln!(res, "  </file>").unwrap();
+    }
+
+    writeln!(res, "</checkstyle>").unwrap();
+
+    res
+}
+
+/// Every file is a test suite and every finding a test case, which fails
+/// for errors. Files without findings get a single passing test case.
+fn junit(report: &Report) -> String {
+    let errors = |findings: &[&Finding]| {
+        findings
+            .iter()
+            .filter(|x| x.severity == Severity::Error)
+            .count()
+    };
+
+    let all: Vec<&Finding> = report.findings.iter().collect();
+    let paths = paths(report);
+    let clean = paths
+        .iter()
+        .filter(|path| !all.iter().any(|x| &&x.path == *path))
+        .count();
+    let mut res = String::new();
+
+    writeln!(res, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(
+        res,
+        r#"<testsuites name="docula" tests="{}" failures="{}">"#,
+        all.len() + clean,
+        errors(&all)
+    )
+    .unwrap();
+
+    for path in paths {
+        let name = xml(&path.to_string_lossy());
+        let findings: Vec<&Finding> = all.iter().copied().filter(|x| &x.path == path).collect();
+
+        writeln!(
+            res,
+            r#"  <testsuite name="{}" tests="{}" failures="{}">"#,
+            name,
+            findings.len().max(1),
+            errors(&findings)
+        )
+        .unwrap();
+
+        if findings.is_empty() {
+            writeln!(res, r#"    <testcase name="{}" classname="docula"/>"#, name).unwrap();
+        }
+
+        for finding in findings {
+            let (line, col, ..) = span(report, finding);
+
+            writeln!(
+                res,
+                r#"    <testcase name="{}:{}:{} {}" classname="docula.{}">"#,
+                name, line, col, finding.rule, finding.rule
+            )
+            .unwrap();
+
+            match finding.severity {
+                Severity::Error => writeln!(
+                    res,
+                    r#"      <failure message="{}" type="{}">{}</failure>"#,
+                    xml(&finding.issue.msg),
+                    finding.rule,
+                    xml(&finding.to_string())
+                ),
+                _ => writeln!(
+                    res,
+                    "      <system-out>{}</system-out>",
+                    xml(&finding.to_string())
+                ),
+            }
+            .unwrap();
+
+            writeln!(res, "    </testcase>").unwrap();
+        }
+
+        writeln!(res, "  </testsuite>").unwrap();
+    }
+
+    writeln!(res, "</testsuites>").unwrap();
+
+    res
+}
+
+fn xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+        .replace('\n', "&#10;")
+}
+
+/// Workflow commands, which GitHub Actions shows as annotations on the
+/// changed lines of a pull request.
+fn github(report: &Report) -> String {
+    let mut res = String::new();
+
+    for finding in report.findings.iter() {
+        let (line_start, col_start, line_end, col_end) = span(report, finding);
+        let level = match finding.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            _ => "notice",
+        };
+
+        writeln!(
+            res,
+            "::{} file={},line={},endLine={},col={},endColumn={},title={}::{}",
+            level,
+            github_property(&uri(finding)),
+            line_start,
+            line_end,
+            col_start,
+            col_end,
+            github_property(finding.rule),
+            github_data(&finding.issue.msg)
+        )
+        .unwrap();
+    }
+
+    res
+}
+
+fn github_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn github_property(text: &str) -> String {
+    github_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::markdown::handler::lint::{Edit, Issue};
+    use std::collections::HashMap;
+
+    fn report() -> Report {
+        let issue = Issue {
+            line_start: 4,
+            line_end: 4,
+            col_start: 0,
+            col_end: 3,
+            content: "### Detail".to_owned(),
+            msg: "Skipped 2nd level header".to_owned(),
+            edits: vec![Edit {
+                line_start: 4,
+                col_start: 0,
+                line_end: 4,
+                col_end: 3,
+                text: "##".to_owned(),
+            }],
+        };
+
+        Report {
+            files: vec![PathBuf::from("docs/a.md"), PathBuf::from("docs/b.md")],
+            findings: vec![Finding {
+                path: PathBuf::from("docs/a.md"),
+                rule: "heading-levels",
+                severity: Severity::Error,
+                issue,
+            }],
+            fixed: Vec::new(),
+            lines: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_json() {
+        let res: Value = serde_json::from_str(&render(Format::Json, &report(), &[])).unwrap();
+
+        assert_eq!(
+            json!([{
+                "path": "docs/a.md",
+                "rule": "heading-levels",
+                "severity": "error",
+                "line_start": 5,
+                "col_start": 1,
+                "line_end": 5,
+                "col_end": 4,
+                "message": "Skipped 2nd level header",
+                "content": "### Detail",
+                "fixable": true,
+            }]),
+            res
+        );
+    }
+
+    #[test]
+    fn test_sarif() {
+        let linters = crate::markdown::linter::all();
+        let res: Value = serde_json::from_str(&render(Format::Sarif, &report(), &linters)).unwrap();
+        let result = &res["runs"][0]["results"][0];
+
+        assert_eq!("2.1.0", res["version"]);
+        assert_eq!(
+            "heading-levels",
+            res["runs"][0]["tool"]["driver"]["rules"][0]["id"]
+        );
+        assert_eq!(0, result["ruleIndex"]);
+        assert_eq!("error", result["level"]);
+        assert_eq!(
+            json!({ "startLine": 5, "startColumn": 1, "endLine": 5, "endColumn": 4 }),
+            result["locations"][0]["physicalLocation"]["region"]
+        );
+        assert_eq!(
+            "##",
+            result["fixes"][0]["artifactChanges"][0]["replacements"][0]["insertedContent"]["text"]
+        );
+    }
+
+    #[test]
+    fn test_checkstyle() {
+        assert_eq!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <checkstyle version=\"4.3\">\n  \
+             <file name=\"docs/a.md\">\n    \
+             <error line=\"5\" column=\"1\" severity=\"error\" \
+             message=\"Skipped 2nd level header\" source=\"docula.heading-levels\"/>\n  \
+             </file>\n  \
+             <file name=\"docs/b.md\"/>\n\
+             </checkstyle>\n",
+            render(Format::Checkstyle, &report(), &[])
+        );
+    }
+
+    #[test]
+    fn test_junit() {
+        let res = render(Format::Junit, &report(), &[]);
+
+        assert!(res.contains(r#"<testsuites name="docula" tests="2" failures="1">"#));
+        assert!(res.contains(r#"<testsuite name="docs/b.md" tests="1" failures="0">"#));
+        assert!(
+            res.contains(r#"<failure message="Skipped 2nd level header" type="heading-levels">"#)
+        );
+    }
+
+    #[test]
+    fn test_unused_assets() {
+        let mut report = report();
+        report.findings.push(Finding {
+            path: PathBuf::from("img/old.png"),
+            rule: crate::markdown::assets::UNUSED,
+            severity: Severity::Warning,
+            issue: Issue {
+                line_start: 0,
+                line_end: 0,
+                col_start: 0,
+                col_end: 0,
+                content: String::new(),
+                msg: "Not referenced by any Markdown file".to_owned(),
+                edits: Vec::new(),
+            },
+        });
+
+        let res = render(Format::Checkstyle, &report, &[]);
+
+        assert!(res.contains(
+            "  <file name=\"img/old.png\">\n    <error line=\"1\" column=\"1\" severity=\"warning\" \
+             message=\"Not referenced by any Markdown file\" source=\"docula.unused-assets\"/>\n"
+        ));
+
+        let res = render(Format::Junit, &report, &[]);
+
+        assert!(res.contains(r#"<testsuites name="docula" tests="3" failures="1">"#));
+        assert!(res.contains(r#"<testsuite name="img/old.png" tests="1" failures="0">"#));
+    }
+
+    #[test]
+    fn test_github() {
+        assert_eq!(
+            "::error file=docs/a.md,line=5,endLine=5,col=1,endColumn=4,\
+             title=heading-levels::Skipped 2nd level header\n",
+            render(Format::Github, &report(), &[])
+        );
+    }
+
+    #[test]
+    fn test_char_columns() {
+        let mut report = report();
+        let issue = &mut report.findings[0].issue;
+        issue.content = "## Café [x](missing.md)".to_owned();
+        issue.col_start = 9;
+        issue.col_end = 24;
+        issue.edits = vec![Edit {
+            line_start: 5,
+            col_start: 9,
+            line_end: 5,
+            col_end: 11,
+            text: String::new(),
+        }];
+
+        assert_eq!(
+            "::error file=docs/a.md,line=5,endLine=5,col=9,endColumn=24,\
+             title=heading-levels::Skipped 2nd level header\n",
+            render(Format::Github, &report, &[])
+        );
+        assert!(render(Format::Checkstyle, &report, &[]).contains(r#"line="5" column="9""#));
+
+        let res: Value = serde_json::from_str(&render(Format::Sarif, &report, &[])).unwrap();
+
+        assert_eq!("unicodeCodePoints", res["runs"][0]["columnKind"]);
+        assert_eq!(
+            json!({ "startLine": 5, "startColumn": 9, "endLine": 5, "endColumn": 24 }),
+            res["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["region"]
+        );
+
+        // Columns on other lines are only counted in characters when the
+        // report has the file's lines.
+        let replaced = |report: &Report| {
+            let res: Value = serde_json::from_str(&render(Format::Sarif, report, &[])).unwrap();
+            res["runs"][0]["results"][0]["fixes"][0]["artifactChanges"][0]["replacements"][0]
+                ["deletedRegion"]
+                .clone()
+        };
+
+        assert_eq!(
+            json!({ "startLine": 6, "startColumn": 10, "endLine": 6, "endColumn": 12 }),
+            replaced(&report)
+        );
+
+        report.lines.insert(
+            PathBuf::from("docs/a.md"),
+            vec![String::new(); 4]
+                .into_iter()
+                .chain([
+                    report.findings[0].issue.content.clone(),
+                    "Déjà vu: x".to_owned(),
+                ])
+                .collect(),
+        );
+
+        assert_eq!(
+            json!({ "startLine": 6, "startColumn": 8, "endLine": 6, "endColumn": 10 }),
+            replaced(&report)
+        );
+    }
+
+    #[test]
+    fn test_escaping() {
+        assert_eq!("a &lt;b&gt; &amp; &quot;c&quot;", xml(r#"a <b> & "c""#));
+        assert_eq!("50%25%0Adone", github_data("50%\ndone"));
+        assert_eq!("a%3Ab%2Cc", github_property("a:b,c"));
+    }
+}
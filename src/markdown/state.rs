@@ -0,0 +1,167 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[schemars(rename = "MarkdownState")]
+#[serde(deny_unknown_fields)]
+pub struct State {
+    #[serde(default)]
+    pub fmt: Fmt,
+    #[serde(default)]
+    pub lint: Lint,
+    #[serde(default)]
+    pub graph: Graph,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Fmt {
+    /// The column at which `markdown fmt` wraps lines.
+    #[serde(default = "default_width")]
+    pub width: usize,
+    /// The style `markdown fmt` writes the first two heading levels in, `atx`
+    /// (`# Title`) or `setext` (underlined).
+    #[serde(default)]
+    pub heading_style: HeadingStyle,
+}
+
+impl Default for Fmt {
+    fn default() -> Fmt {
+        Fmt {
+            width: default_width(),
+            heading_style: HeadingStyle::default(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HeadingStyle {
+    #[default]
+    Atx,
+    Setext,
+}
+
+impl std::fmt::Display for HeadingStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            HeadingStyle::Atx => write!(f, "atx"),
+            HeadingStyle::Setext => write!(f, "setext"),
+        }
+    }
+}
+
+fn default_width() -> usize {
+    80
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Graph {
+    /// The documents readers start from, relative to the project root.
+    /// `markdown graph` reports documents they can't reach as orphans.
+    #[serde(default = "default_entry")]
+    pub entry: Vec<PathBuf>,
+}
+
+impl Default for Graph {
+    fn default() -> Graph {
+        Graph {
+            entry: default_entry(),
+        }
+    }
+}
+
+fn default_entry() -> Vec<PathBuf> {
+    vec![PathBuf::from("README.md")]
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Lint {
+    /// Configures lint rules by their ID: `false` turns a rule off, a
+    /// severity changes how serious its issues are, and a mapping also sets
+    /// the rule's options.
+    #[serde(default)]
+    pub rules: BTreeMap<String, Rule>,
+}
+
+/// The options of a single rule, checked by the rule itself.
+pub type Options = BTreeMap<String, serde_json::Value>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum Rule {
+    Enabled(bool),
+    Severity(Severity),
+    Options {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        severity: Option<Severity>,
+        #[serde(flatten)]
+        options: Options,
+    },
+}
+
+impl Lint {
+    /// The severity a rule is configured with, if any.
+    pub fn severity(&self, id: &str) -> Option<Severity> {
+        match self.rules.get(id)? {
+            Rule::Enabled(true) => None,
+            Rule::Enabled(false) => Some(Severity::Off),
+            Rule::Severity(x) => Some(*x),
+            Rule::Options { severity, .. } => *severity,
+        }
+    }
+
+    pub fn options(&self, id: &str) -> Options {
+        match self.rules.get(id) {
+            Some(Rule::Options { options, .. }) => options.clone(),
+            _ => Options::new(),
+        }
+    }
+}
+
+/// How serious the issues of a rule are. Only errors make `lint` fail.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+    Off,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Info => write!(f, "info"),
+            Severity::Off => write!(f, "off"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rule_settings() {
+        let lint: Lint = serde_yaml::from_str(
+            "rules:\n  a: false\n  b: warning\n  c:\n    severity: info\n    max: 3\n  d: true\n",
+        )
+        .unwrap();
+
+        assert_eq!(Some(Severity::Off), lint.severity("a"));
+        assert_eq!(Some(Severity::Warning), lint.severity("b"));
+        assert_eq!(Some(Severity::Info), lint.severity("c"));
+        assert_eq!(None, lint.severity("d"));
+        assert_eq!(None, lint.severity("e"));
+
+        assert_eq!(Some(&serde_json::json!(3)), lint.options("c").get("max"));
+        assert!(lint.options("b").is_empty());
+    }
+}
@@ -0,0 +1,295 @@
+//! Inline comments that silence lint rules for part of a document:
+//!
+//! ```markdown
+//! <!-- docula-disable heading-levels -->
+//! <!-- docula-enable heading-levels -->
+//! <!-- docula-disable-next-line heading-levels -->
+//! <!-- docula-disable-file heading-levels -->
+//! ```
+//!
+//! Without rule IDs a comment applies to every rule. Comments that end up
+//! silencing nothing are reported, so they don't outlive their reason.
+
+use super::handler::lint::{Finding, Issue};
+use super::parser::lexer;
+use super::state::Severity;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// The rule unused suppressions are reported under.
+pub const UNUSED: &str = "unused-suppression";
+
+lazy_static! {
+    static ref DIRECTIVE: Regex = Regex::new(
+        r"<!--\s*docula-(disable-next-line|disable-file|disable|enable)\b([^>]*?)\s*-->"
+    )
+    .unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Disable,
+    Enable,
+    DisableNextLine,
+    DisableFile,
+}
+
+#[derive(Debug)]
+struct Directive {
+    kind: Kind,
+    line: usize,
+    col: usize,
+    col_end: usize,
+    text: String,
+    content: String,
+}
+
+/// Lines in which a rule, or every rule but some, is silenced.
+#[derive(Debug)]
+struct Range {
+    directive: usize,
+    rule: Option<String>,
+    except: Vec<String>,
+    start: usize,
+    end: usize,
+}
+
+impl Range {
+    fn covers(&self, finding: &Finding) -> bool {
+        let line = finding.issue.line_start;
+
+        let rule = match &self.rule {
+            Some(x) => x == finding.rule,
+            None => !self.except.iter().any(|x| x == finding.rule),
+        };
+
+        rule && self.start <= line && line < self.end
+    }
+}
+
+/// Drops the findings the document silences, and adds a warning for every
+/// comment that silenced nothing.
+pub fn apply(path: &Path, content: &str, findings: Vec<Finding>) -> Vec<Finding> {
+    let (directives, ranges) = parse(content);
+
+    if directives.is_empty() {
+        return findings;
+    }
+
+    let mut used = HashSet::new();
+
+    let mut res: Vec<Finding> = findings
+        .into_iter()
+        .filter(|x| match ranges.iter().find(|r| r.covers(x)) {
+            Some(r) => {
+                used.insert(r.directive);
+                false
+            }
+            None => true,
+        })
+        .collect();
+
+    for (i, directive) in directives.iter().enumerate() {
+        if directive.kind == Kind::Enable || used.contains(&i) {
+            continue;
+        }
+
+        res.push(Finding {
+            path: path.to_path_buf(),
+            rule: UNUSED,
+            severity: Severity::Warning,
+            issue: Issue {
+                line_start: directive.line,
+                line_end: directive.line,
+                col_start: directive.col,
+                col_end: directive.col_end,
+                content: directive.content.clone(),
+                msg: format!("`{}` does not silence anything", directive.text),
+                edits: Vec::new(),
+            },
+        });
+    }
+
+    res
+}
+
+fn parse(content: &str) -> (Vec<Directive>, Vec<Range>) {
+    let mut directives: Vec<Directive> = Vec::new();
+    let mut ranges: Vec<Range> = Vec::new();
+    let fenced = lexer::fenced_lines(content);
+
+    for (num, line) in content.lines().enumerate() {
+        if fenced.iter().any(|x| x.contains(&num)) {
+            continue;
+        }
+
+        for caps in DIRECTIVE.captures_iter(line) {
+            let whole = caps.get(0).unwrap();
+
+            let kind = match &caps[1] {
+                "disable" => Kind::Disable,
+                "enable" => Kind::Enable,
+                "disable-next-line" => Kind::DisableNextLine,
+                _ => Kind::DisableFile,
+            };
+
+            let rules: Vec<String> = caps[2]
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|x| !x.is_empty())
+                .map(String::from)
+                .collect();
+
+            let directive = directives.len();
+
+            let (start, end) = match kind {
+                Kind::Disable => (num + 1, usize::MAX),
+                Kind::DisableNextLine => (num + 1, num + 2),
+                Kind::DisableFile => (0, usize::MAX),
+                Kind::Enable => (0, 0),
+            };
+
+            if kind == Kind::Enable {
+                // File-wide comments can't be ended.
+                let open = ranges.iter_mut().filter(|x| {
+                    x.end == usize::MAX && directives[x.directive].kind == Kind::Disable
+                });
+
+                for range in open {
+                    match (&range.rule, rules.is_empty()) {
+                        (_, true) => range.end = num,
+                        (Some(x), false) if rules.contains(x) => range.end = num,
+                        (None, false) => range.except.extend(rules.iter().cloned()),
+                        _ => (),
+                    }
+                }
+            } else if rules.is_empty() {
+                ranges.push(Range {
+                    directive,
+                    rule: None,
+                    except: Vec::new(),
+                    start,
+                    end,
+                });
+            } else {
+                for rule in rules {
+                    ranges.push(Range {
+                        directive,
+                        rule: Some(rule),
+                        except: Vec::new(),
+                        start,
+                        end,
+                    });
+                }
+            }
+
+            directives.push(Directive {
+                kind,
+                line: num,
+                col: whole.start(),
+                col_end: whole.end(),
+                text: whole
+                    .as_str()
+                    .trim_start_matches("<!--")
+                    .trim_end_matches("-->")
+                    .trim()
+                    .to_owned(),
+                content: line.to_owned(),
+            });
+        }
+    }
+
+    (directives, ranges)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn path() -> PathBuf {
+        PathBuf::from("a.md")
+    }
+
+    fn finding(rule: &'static str, line: usize) -> Finding {
+        Finding {
+            path: path(),
+            rule,
+            severity: Severity::Error,
+            issue: Issue {
+                line_start: line,
+                line_end: line,
+                col_start: 0,
+                col_end: 0,
+                content: String::new(),
+                msg: String::new(),
+                edits: Vec::new(),
+            },
+        }
+    }
+
+    fn lines(findings: &[Finding]) -> Vec<(&str, usize)> {
+        findings
+            .iter()
+            .map(|x| (x.rule, x.issue.line_start))
+            .collect()
+    }
+
+    #[test]
+    fn test_disable_and_enable() {
+        let content = "<!-- docula-disable a -->\nx\n<!-- docula-enable a -->\nx\n";
+        let res = apply(
+            &path(),
+            content,
+            vec![finding("a", 1), finding("a", 3), finding("b", 1)],
+        );
+
+        assert_eq!(vec![("a", 3), ("b", 1)], lines(&res));
+    }
+
+    #[test]
+    fn test_disable_all_but_one() {
+        let content = "<!-- docula-disable -->\n<!-- docula-enable b -->\nx\n";
+        let res = apply(&path(), content, vec![finding("a", 2), finding("b", 2)]);
+
+        assert_eq!(vec![("b", 2)], lines(&res));
+    }
+
+    #[test]
+    fn test_next_line_and_file() {
+        let content =
+            "x\n<!-- docula-disable-next-line a, b -->\nx\nx\n<!-- docula-disable-file c -->\n";
+        let res = apply(
+            &path(),
+            content,
+            vec![finding("a", 2), finding("a", 3), finding("c", 0)],
+        );
+
+        assert_eq!(vec![("a", 3)], lines(&res));
+    }
+
+    #[test]
+    fn test_unused() {
+        let content =
+            "<!-- docula-disable-next-line a -->\nx\n```\n<!-- docula-disable b -->\n```\n";
+        let res = apply(&path(), content, vec![finding("b", 4)]);
+
+        assert_eq!(vec![("b", 4), (UNUSED, 0)], lines(&res));
+        assert_eq!(
+            "`docula-disable-next-line a` does not silence anything",
+            res[1].issue.msg
+        );
+    }
+
+    #[test]
+    fn test_fences_and_columns() {
+        // A shorter fence doesn't close a longer one.
+        let content = "````\n```\n<!-- docula-disable a -->\n````\nx <!--  docula-disable b  -->\n";
+        let res = apply(&path(), content, Vec::new());
+
+        assert_eq!(vec![(UNUSED, 4)], lines(&res));
+        assert_eq!(2, res[0].issue.col_start);
+        assert_eq!(content.lines().nth(4).unwrap().len(), res[0].issue.col_end);
+    }
+}
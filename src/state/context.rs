@@ -0,0 +1,158 @@
+//! Where docula runs and which `.docula` files it reads. By default that is
+//! decided by the working directory of the process; the global `--cwd`,
+//! `--root` and `--config` options change it without having to `cd`.
+
+use super::layers;
+use crate::IoContext;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Context {
+    /// The directory docula acts as if it was started in. Relative paths
+    /// given on the command line are resolved against it.
+    pub cwd: PathBuf,
+    /// The project root. Its `.docula` is the repository's file, and no file
+    /// above it is read.
+    pub root: Option<PathBuf>,
+    /// A settings file to use instead of searching for `.docula` files.
+    pub config: Option<PathBuf>,
+}
+
+impl Context {
+    pub fn new(cwd: PathBuf) -> Context {
+        Context {
+            cwd,
+            root: None,
+            config: None,
+        }
+    }
+
+    /// The context of the process itself.
+    pub fn current() -> crate::Result<Context> {
+        Ok(Context::new(std::env::current_dir()?))
+    }
+
+    /// Builds a context from the global options. `root` and `config` are
+    /// relative to `cwd`, which is relative to the working directory. The
+    /// config file itself need not exist yet, so `init` can create it.
+    pub fn from_options(
+        cwd: Option<&Path>,
+        root: Option<&Path>,
+        config: Option<&Path>,
+    ) -> crate::Result<Context> {
+        let mut res = Context::current()?;
+
+        if let Some(x) = cwd {
+            res.cwd = canonical(&res.resolve(x))?;
+        }
+
+        if let Some(x) = root {
+            res.root = Some(canonical(&res.resolve(x))?);
+        }
+
+        if let Some(x) = config {
+            let path = res.resolve(x);
+
+            res.config = match (path.parent(), path.file_name()) {
+                (Some(dir), Some(name)) => Some(canonical(dir)?.join(name)),
+                _ => Err(crate::Error::Invalid(format!(
+                    "{} is not a file",
+                    path.display()
+                )))?,
+            };
+        }
+
+        Ok(res)
+    }
+
+    /// The same context, as if started in `dir`.
+    pub fn at(&self, dir: &Path) -> Context {
+        Context {
+            cwd: self.resolve(dir),
+            ..self.clone()
+        }
+    }
+
+    pub fn resolve(&self, path: &Path) -> PathBuf {
+        self.cwd.join(path)
+    }
+
+    /// The directory the project is in: the root when one is given, or else
+    /// the git repository the working directory is in.
+    pub fn project_root(&self) -> Option<PathBuf> {
+        if let Some(x) = &self.root {
+            return Some(x.clone());
+        }
+
+        self.cwd
+            .ancestors()
+            .find(|x| x.join(".git").exists())
+            .map(Path::to_path_buf)
+    }
+
+    /// The settings files that apply, outermost first.
+    pub fn files(&self) -> Result<Vec<PathBuf>, std::io::Error> {
+        match &self.config {
+            Some(x) => Ok(vec![x.clone()]),
+            None => layers::find_files(&self.cwd, self.root.as_deref()),
+        }
+    }
+
+    /// The repository's settings file, or where it would be created.
+    pub fn repo_file(&self) -> Result<PathBuf, std::io::Error> {
+        Ok(match self.files()?.into_iter().next() {
+            Some(x) => x,
+            None => self.new_file(),
+        })
+    }
+
+    /// Where a settings file is created when there is none yet.
+    pub fn new_file(&self) -> PathBuf {
+        match (&self.config, &self.root) {
+            (Some(x), _) => x.clone(),
+            (None, Some(x)) => x.join(".docula"),
+            (None, None) => self.cwd.join(".docula"),
+        }
+    }
+}
+
+fn canonical(path: &Path) -> crate::Result<PathBuf> {
+    path.canonicalize().with_path(path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_files() {
+        let tmp = tempdir::TempDir::new("context_test").unwrap();
+        let nested = tmp.path().join("a/b");
+
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::create_dir(tmp.path().join(".git")).unwrap();
+        std::fs::write(tmp.path().join(".docula"), "").unwrap();
+        std::fs::write(tmp.path().join("a/.docula"), "").unwrap();
+
+        let ctx = Context::new(nested.clone());
+
+        assert_eq!(2, ctx.files().unwrap().len());
+        assert_eq!(Some(tmp.path().to_path_buf()), ctx.project_root());
+
+        let ctx = Context {
+            root: Some(tmp.path().join("a")),
+            ..Context::new(nested.clone())
+        };
+
+        assert_eq!(vec![tmp.path().join("a/.docula")], ctx.files().unwrap());
+        assert_eq!(Some(tmp.path().join("a")), ctx.project_root());
+
+        let ctx = Context {
+            config: Some(tmp.path().join("other.yaml")),
+            ..Context::new(nested)
+        };
+
+        assert_eq!(vec![tmp.path().join("other.yaml")], ctx.files().unwrap());
+        assert_eq!(tmp.path().join("other.yaml"), ctx.repo_file().unwrap());
+    }
+}
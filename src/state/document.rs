@@ -0,0 +1,188 @@
+use super::migrate::{self, CURRENT_VERSION};
+use super::yaml::{self, Segment};
+use super::{keys, Context, State};
+use crate::{Error, IoContext};
+use serde_yaml::Value;
+use std::path::PathBuf;
+
+/// The `.docula` file as text, for changes that keep its comments and the
+/// order of its keys. Every change is checked against the state model before
+/// it is accepted.
+pub struct Document {
+    pub path: PathBuf,
+    /// Set once an edit could not keep the comments of the file.
+    pub rewritten: bool,
+    text: String,
+    value: Value,
+    nested: bool,
+}
+
+impl Document {
+    /// Opens the `.docula` file that `key` should be written to, or starts a
+    /// new one. ADR directories always live in the repository's file,
+    /// anything else goes to the nearest one.
+    pub fn open(ctx: &Context, key: &str) -> crate::Result<Document> {
+        let repo = State::find_repo(ctx)?;
+
+        let found = match key.split('.').next() == Some("adr") {
+            true => repo.clone(),
+            false => State::find(ctx)?,
+        };
+
+        let path = match found {
+            Some(x) => x,
+            None => ctx.new_file(),
+        };
+
+        let nested = repo.is_some_and(|x| x != path);
+
+        let text = match path.exists() {
+            true => std::fs::read_to_string(&path).with_path(&path)?,
+            false => String::new(),
+        };
+
+        let text = match text.trim().is_empty() {
+            true => format!("version: {}\n", CURRENT_VERSION),
+            false => text,
+        };
+
+        // Make sure the file is valid before changing anything.
+        State::parse(&text, &path)?;
+
+        let value: Value = serde_yaml::from_str(&text)?;
+
+        if migrate::version(&value) != Some(CURRENT_VERSION) {
+            Err(Error::Config(format!(
+                "{} uses an older layout, run `docula config migrate` first",
+                path.display()
+            )))?;
+        }
+
+        Ok(Document {
+            path,
+            rewritten: false,
+            text,
+            value,
+            nested,
+        })
+    }
+
+    pub fn set(&mut self, key: &str, input: &str) -> crate::Result<()> {
+        let segments = keys::segments(&self.value, key).map_err(Error::Invalid)?;
+        let candidates = keys::candidates(input);
+
+        if candidates[0].is_mapping() || candidates[0].is_sequence() {
+            Err(Error::Invalid(format!(
+                "`{}` must be set to a single value",
+                key
+            )))?;
+        }
+
+        let mut last_err = None;
+
+        for candidate in candidates {
+            let mut value = self.value.clone();
+            keys::set(&mut value, &segments, candidate.clone()).map_err(Error::Invalid)?;
+
+            match self.check(&value) {
+                Ok(_) => {
+                    let text = yaml::set(&self.text, &segments, &keys::encode(&candidate))
+                        .map_err(Error::Invalid)?;
+                    return self.apply(text, value);
+                }
+                Err(e) => last_err = Some(e),
+            }
+        }
+
+        Err(Error::Invalid(format!(
+            "invalid value for `{}`: {}",
+            key,
+            last_err.unwrap()
+        )))
+    }
+
+    /// Appends an item to the list at `key`, e.g. a directory to `adr.dirs`,
+    /// creating the list when there is none.
+    pub fn push(&mut self, key: &str, item: Value) -> crate::Result<()> {
+        let segments = keys::segments(&self.value, key).map_err(Error::Invalid)?;
+
+        let mut items = match keys::get(&self.value, &segments) {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Sequence(x)) => x.clone(),
+            Some(_) => Err(Error::Invalid(format!("`{}` is not a list", key)))?,
+        };
+
+        items.push(item.clone());
+
+        let mut value = self.value.clone();
+        keys::set(&mut value, &segments, Value::Sequence(items)).map_err(Error::Invalid)?;
+        self.check(&value)?;
+
+        let text = yaml::push(&self.text, &segments, &serde_yaml::to_string(&item)?)
+            .map_err(Error::Invalid)?;
+        self.apply(text, value)
+    }
+
+    pub fn unset(&mut self, key: &str) -> crate::Result<()> {
+        let segments = keys::segments(&self.value, key).map_err(Error::Invalid)?;
+
+        if keys::get(&self.value, &segments).is_none() {
+            return Ok(());
+        }
+
+        let mut value = self.value.clone();
+        keys::unset(&mut value, &segments);
+
+        if let Err(e) = self.check(&value) {
+            Err(Error::Invalid(format!("`{}` can't be removed: {}", key, e)))?;
+        }
+
+        let text = yaml::unset(&self.text, &segments).map_err(Error::Invalid)?;
+        self.apply(text, value)
+    }
+
+    pub fn save(&self) -> crate::Result<()> {
+        std::fs::write(&self.path, &self.text).with_path(&self.path)
+    }
+
+    fn check(&self, value: &Value) -> crate::Result<State> {
+        if self.nested && value.get("adr").is_some() {
+            Err(Error::Invalid(
+                "adr directories can only be set in the repository's .docula".to_owned(),
+            ))?;
+        }
+
+        serde_yaml::from_value(value.clone()).map_err(|e| Error::Invalid(e.to_string()))
+    }
+
+    /// Accepts an edit of the text, as long as it means the same as the
+    /// intended value. Otherwise the whole file is rewritten, which loses its
+    /// comments but never its settings.
+    fn apply(&mut self, text: String, value: Value) -> crate::Result<()> {
+        let edited: Option<Value> = serde_yaml::from_str(&text).ok();
+
+        self.text = match edited.as_ref() == Some(&value) {
+            true => text,
+            false => {
+                self.rewritten = true;
+                serde_yaml::to_string(&value)?
+            }
+        };
+
+        State::parse(&self.text, &self.path)?;
+        self.value = value;
+
+        Ok(())
+    }
+}
+
+/// Resolves a key for reading, against the effective settings including
+/// defaults.
+pub fn effective(state: &State) -> Result<Value, serde_yaml::Error> {
+    serde_yaml::to_value(state)
+}
+
+pub fn lookup<'a>(value: &'a Value, key: &str) -> Result<Option<&'a Value>, String> {
+    let segments: Vec<Segment> = keys::segments(value, key)?;
+    Ok(keys::get(value, &segments))
+}
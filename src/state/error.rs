@@ -0,0 +1,57 @@
+use std::fmt;
+use std::path::PathBuf;
+
+/// A `.docula` file that could not be parsed, with the position of the problem
+/// when it is known.
+#[derive(Debug)]
+pub struct ParseError {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub msg: String,
+}
+
+impl ParseError {
+    pub fn new(path: PathBuf, err: serde_yaml::Error) -> ParseError {
+        let location = err.location();
+        let msg = err.to_string();
+
+        // The position is reported separately, so drop it from the message.
+        let msg = match msg.rfind(" at line ") {
+            Some(x) if location.is_some() => msg[..x].to_owned(),
+            _ => msg,
+        };
+
+        ParseError {
+            path,
+            line: location.as_ref().map(|x| x.line()),
+            column: location.as_ref().map(|x| x.column()),
+            msg,
+        }
+    }
+}
+
+impl ParseError {
+    pub fn message(path: PathBuf, msg: String) -> ParseError {
+        ParseError {
+            path,
+            line: None,
+            column: None,
+            msg,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, ":{}:{}", line, column)?;
+        }
+
+        write!(f, ": {}", self.msg)
+    }
+}
+
+impl std::error::Error for ParseError {}
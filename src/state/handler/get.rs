@@ -0,0 +1,17 @@
+use crate::state::{document, Context, State};
+use crate::Error;
+use serde_yaml::Value;
+
+pub struct Handler {}
+
+impl Handler {
+    pub fn handle(self, ctx: &Context, key: &str) -> crate::Result<Value> {
+        let state = State::load(ctx)?;
+        let value = document::effective(&state)?;
+
+        match document::lookup(&value, key).map_err(Error::Invalid)? {
+            None => Err(Error::NotFound(format!("`{}` is not set", key))),
+            Some(x) => Ok(x.clone()),
+        }
+    }
+}
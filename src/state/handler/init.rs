@@ -0,0 +1,286 @@
+use crate::markdown::detect::{self, Conventions};
+use crate::markdown::state::Fmt;
+use crate::state::migrate::CURRENT_VERSION;
+use crate::state::{Context, State};
+use crate::{Error, IoContext};
+use std::path::{Path, PathBuf};
+
+const HOOK: &str = "#!/bin/sh
+# Installed by `docula init`. Checks the docula settings and every staged
+# Markdown file before a commit is made.
+
+docula config validate || exit 1
+
+# The names are separated by NUL so any file name works, and the linter is
+# not run at all when no Markdown file is staged.
+git diff --cached --name-only --diff-filter=ACM -z -- '*.md' '*.markdown' \\
+    | xargs -0 sh -c '[ $# -eq 0 ] || exec docula markdown lint \"$@\"' docula || exit 1
+";
+
+pub struct Handler {}
+
+/// What `init` did.
+#[derive(Debug, Default)]
+pub struct Setup {
+    /// The number of Markdown files the settings were based on.
+    pub detected: usize,
+    pub created: Vec<PathBuf>,
+    /// Files that already existed and were left alone.
+    pub kept: Vec<PathBuf>,
+}
+
+impl Handler {
+    pub fn handle(
+        self,
+        ctx: &Context,
+        adr: Option<PathBuf>,
+        adr_name: Option<String>,
+        readme: bool,
+        lint: bool,
+        hook: bool,
+    ) -> crate::Result<Setup> {
+        let path = ctx.new_file();
+        let root = path.parent().unwrap_or(&ctx.cwd).to_path_buf();
+
+        if path.exists() {
+            Err(Error::Invalid(format!(
+                "{} already exists, use `docula config` to change it",
+                path.display()
+            )))?;
+        }
+
+        if hook && !root.join(".git").is_dir() {
+            Err(Error::Config(
+                "a pre-commit hook can only be installed at the root of a git repository"
+                    .to_owned(),
+            ))?;
+        }
+
+        let adr = match adr {
+            None => None,
+            Some(dir) => {
+                let name = match adr_name {
+                    Some(x) => x,
+                    None => dir
+                        .file_name()
+                        .map(|x| x.to_string_lossy().into_owned())
+                        .ok_or_else(|| {
+                            Error::Invalid(
+                                "the adr directory needs a name, use --adr-name".to_owned(),
+                            )
+                        })?,
+                };
+
+                Some((dir, name))
+            }
+        };
+
+        let conventions = detect::detect(&root);
+        let mut res = Setup {
+            detected: conventions.files,
+            ..Setup::default()
+        };
+
+        let text = render(&conventions, adr.as_ref(), lint);
+
+        // The template must always produce a file that docula accepts.
+        State::parse(&text, &path)?;
+
+        // The directory comes first, so a failure never leaves settings that
+        // point at a directory which does not exist.
+        if let Some((dir, _)) = adr.as_ref() {
+            std::fs::create_dir_all(root.join(dir)).with_path(&root.join(dir))?;
+        }
+
+        std::fs::write(&path, text).with_path(&path)?;
+        res.created.push(path);
+
+        if let Some((_, name)) = adr.as_ref() {
+            let state = State::load(ctx)?;
+            let dir = state.adr.require_dir(&Some(name.clone()), &ctx.cwd)?;
+
+            crate::adr::handler::init::create_first_adr(dir)?;
+            res.created.push(dir.full_path.clone());
+        }
+
+        if readme {
+            let docs = root.join("docs");
+            let readme = docs.join("README.md");
+
+            match readme.exists() {
+                true => res.kept.push(readme),
+                false => {
+                    std::fs::create_dir_all(&docs).with_path(&docs)?;
+                    std::fs::write(&readme, render_readme(adr.as_ref().map(|x| x.0.as_path())))
+                        .with_path(&readme)?;
+                    res.created.push(readme);
+                }
+            }
+        }
+
+        if hook {
+            res.created.push(install_hook(&root)?);
+        }
+
+        Ok(res)
+    }
+}
+
+fn render(conventions: &Conventions, adr: Option<&(PathBuf, String)>, lint: bool) -> String {
+    let defaults = Fmt::default();
+    let mut res = String::new();
+
+    res.push_str("# Settings for docula. Change them with `docula config set`, or see\n");
+    res.push_str("# `docula config schema` for everything that can be set here.\n");
+    res.push_str(&format!("version: {}\n\n", CURRENT_VERSION));
+
+    res.push_str("# Directories of Architecture Decision Records, add more with\n");
+    res.push_str("# `docula adr init`.\n");
+    res.push_str("adr:\n");
+
+    match adr {
+        None => res.push_str("  dirs: []\n"),
+        Some((dir, name)) => {
+            res.push_str("  dirs:\n");
+            res.push_str(&format!("  - path: {}\n", scalar(&dir.to_string_lossy())));
+            res.push_str(&format!("    name: {}\n", scalar(name)));
+            res.push_str("    index: Timestamp\n");
+        }
+    }
+
+    let detected = |found: bool| match found {
+        true => " Detected from the existing documents.",
+        false => "",
+    };
+
+    res.push_str("\nmarkdown:\n  fmt:\n");
+    res.push_str(&format!(
+        "    # The column at which `markdown fmt` wraps lines.{}\n",
+        detected(conventions.width.is_some())
+    ));
+    res.push_str(&format!(
+        "    width: {}\n",
+        conventions.width.unwrap_or(defaults.width)
+    ));
+    res.push_str(&format!(
+        "    # The style `markdown fmt` writes headings in, `atx` (# Title) or\n    # `setext` (underlined).{}\n",
+        detected(conventions.heading_style.is_some())
+    ));
+    res.push_str(&format!(
+        "    heading_style: {}\n",
+        conventions.heading_style.unwrap_or(defaults.heading_style)
+    ));
+
+    res.push_str("  # Turn lint rules on or off by their ID, or set their severity.\n");
+
+    let prefix = match lint {
+        true => "",
+        false => "# ",
+    };
+
+    res.push_str(&format!("  {}lint:\n", prefix));
+    res.push_str(&format!("  {}  rules:\n", prefix));
+
+    for rule in crate::markdown::linter::all() {
+        res.push_str(&format!("  {}    {}: true\n", prefix, rule.id()));
+    }
+
+    res
+}
+
+/// The string as a YAML scalar, quoted where it would otherwise read as
+/// something else.
+fn scalar(value: &str) -> String {
+    serde_yaml::to_string(value)
+        .map(|x| x.trim_end().to_owned())
+        .unwrap_or_else(|_| format!("{:?}", value))
+}
+
+fn render_readme(adr: Option<&Path>) -> String {
+    let mut res = String::from(
+        "# Documentation\n\n\
+        This directory holds the documentation of the project, written in Markdown\n\
+        and checked with [docula](https://github.com/docula-io/docula).\n",
+    );
+
+    if let Some(dir) = adr {
+        let link = match dir.strip_prefix("docs") {
+            Ok(x) => x.to_path_buf(),
+            Err(_) => Path::new("..").join(dir),
+        };
+
+        res.push_str(&format!(
+            "\nArchitecture decisions are recorded in [{}]({}/).\n",
+            dir.to_string_lossy(),
+            link.to_string_lossy()
+        ));
+    }
+
+    res
+}
+
+fn install_hook(root: &Path) -> crate::Result<PathBuf> {
+    let hooks = root.join(".git").join("hooks");
+    let path = hooks.join("pre-commit");
+
+    if path.exists() {
+        Err(Error::Invalid(format!("{} already exists", path.display())))?;
+    }
+
+    std::fs::create_dir_all(&hooks).with_path(&hooks)?;
+    std::fs::write(&path, HOOK).with_path(&path)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).with_path(&path)?;
+    }
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::markdown::state::{HeadingStyle, Rule};
+
+    #[test]
+    fn test_render_is_valid() {
+        let path = Path::new("/tmp/.docula");
+        let adr = (PathBuf::from("docs/adr"), "main".to_owned());
+
+        let conventions = Conventions {
+            files: 3,
+            width: Some(100),
+            heading_style: Some(HeadingStyle::Setext),
+        };
+
+        let state = State::parse(&render(&conventions, Some(&adr), true), path).unwrap();
+
+        assert_eq!(100, state.markdown.fmt.width);
+        assert_eq!(HeadingStyle::Setext, state.markdown.fmt.heading_style);
+        assert_eq!("main", state.adr.dirs[0].name);
+        assert_eq!(
+            Some(&Rule::Enabled(true)),
+            state.markdown.lint.rules.get("heading-levels")
+        );
+
+        let adr = (PathBuf::from("docs/#adr: yes"), "1".to_owned());
+        let state = State::parse(&render(&conventions, Some(&adr), true), path).unwrap();
+
+        assert_eq!(PathBuf::from("docs/#adr: yes"), state.adr.dirs[0].path);
+        assert_eq!("1", state.adr.dirs[0].name);
+
+        let state = State::parse(&render(&Conventions::default(), None, false), path).unwrap();
+
+        assert_eq!(80, state.markdown.fmt.width);
+        assert!(state.adr.dirs.is_empty());
+        assert!(state.markdown.lint.rules.is_empty());
+    }
+
+    #[test]
+    fn test_render_readme() {
+        assert!(render_readme(Some(Path::new("docs/adr"))).contains("[docs/adr](adr/)"));
+        assert!(render_readme(Some(Path::new("adr"))).contains("[adr](../adr/)"));
+    }
+}
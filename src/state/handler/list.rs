@@ -0,0 +1,28 @@
+use crate::state::keys;
+use crate::state::layers::Layers;
+use crate::state::{Context, Origin};
+
+pub struct Handler {}
+
+/// A single value of the effective settings.
+pub struct Setting {
+    pub key: String,
+    pub value: String,
+    pub origin: Origin,
+}
+
+impl Handler {
+    pub fn handle(self, ctx: &Context) -> crate::Result<Vec<Setting>> {
+        let layers = Layers::load(ctx)?;
+        let value = serde_yaml::to_value(layers.state()?)?;
+
+        Ok(keys::flatten(&value)
+            .into_iter()
+            .map(|(key, value)| Setting {
+                origin: layers.origin(&key).clone(),
+                key,
+                value,
+            })
+            .collect())
+    }
+}
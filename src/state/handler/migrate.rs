@@ -0,0 +1,58 @@
+use crate::adr::diff::{self, Line};
+use crate::state::migrate::{self, CURRENT_VERSION};
+use crate::state::{Context, ParseError, State};
+use crate::{Error, IoContext};
+use std::path::PathBuf;
+
+pub struct Handler {}
+
+/// What a migration did, or would do on a dry run.
+pub struct Migration {
+    pub path: PathBuf,
+    pub version: u64,
+    /// The steps that were taken, empty if the file was already current.
+    pub steps: Vec<&'static str>,
+    pub changes: Vec<Line>,
+}
+
+impl Handler {
+    /// Upgrades the nearest `.docula` file. Only the file's own text is
+    /// changed, so settings from other layers and defaults never end up in
+    /// it.
+    pub fn handle(self, ctx: &Context, dry_run: bool) -> crate::Result<Migration> {
+        let path = match State::find(ctx)? {
+            None => Err(Error::NotFound("no .docula file could be found".to_owned()))?,
+            Some(x) => x,
+        };
+
+        let contents = std::fs::read_to_string(&path).with_path(&path)?;
+
+        // Make sure the file is valid before changing anything.
+        State::parse(&contents, &path)?;
+
+        let (migrated, steps) = match contents.trim().is_empty() {
+            true => (contents.clone(), Vec::new()),
+            false => migrate::migrate_text(&contents)
+                .map_err(|msg| ParseError::message(path.clone(), msg))?,
+        };
+
+        let mut res = Migration {
+            path,
+            version: CURRENT_VERSION,
+            steps,
+            changes: Vec::new(),
+        };
+
+        if res.steps.is_empty() {
+            return Ok(res);
+        }
+
+        res.changes = diff::diff_lines(&contents, &migrated);
+
+        if !dry_run {
+            std::fs::write(&res.path, migrated).with_path(&res.path)?;
+        }
+
+        Ok(res)
+    }
+}
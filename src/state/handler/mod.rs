@@ -0,0 +1,8 @@
+pub mod get;
+pub mod init;
+pub mod list;
+pub mod migrate;
+pub mod schema;
+pub mod set;
+pub mod unset;
+pub mod validate;
@@ -0,0 +1,14 @@
+use crate::state::State;
+
+pub struct Handler {}
+
+impl Handler {
+    pub fn handle(self) -> crate::Result<String> {
+        Ok(schema()?)
+    }
+}
+
+/// The JSON Schema of `.docula`, generated from the types it is loaded into.
+pub fn schema() -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(&schemars::schema_for!(State))
+}
@@ -0,0 +1,15 @@
+use crate::state::{Context, Document};
+
+pub struct Handler {}
+
+impl Handler {
+    /// Returns the file that was changed.
+    pub fn handle(self, ctx: &Context, key: &str) -> crate::Result<Document> {
+        let mut doc = Document::open(ctx, key)?;
+
+        doc.unset(key)?;
+        doc.save()?;
+
+        Ok(doc)
+    }
+}
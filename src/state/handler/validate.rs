@@ -0,0 +1,64 @@
+use crate::state::{Context, State};
+use crate::Error;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+pub struct Handler {}
+
+/// The `.docula` file that was checked and what is wrong with it.
+pub struct Validation {
+    pub path: PathBuf,
+    pub problems: Vec<String>,
+}
+
+impl Handler {
+    pub fn handle(self, ctx: &Context) -> crate::Result<Validation> {
+        let path = match State::find(ctx)? {
+            None => Err(Error::NotFound("no .docula file could be found".to_owned()))?,
+            Some(x) => x,
+        };
+
+        let state = State::load(ctx)?;
+
+        Ok(Validation {
+            problems: check(&state),
+            path,
+        })
+    }
+}
+
+/// Checks what the file format alone can't express, such as directories that
+/// no longer exist.
+fn check(state: &State) -> Vec<String> {
+    let mut res = Vec::new();
+    let mut names = HashSet::new();
+    let mut paths = HashSet::new();
+
+    for dir in state.adr.dirs.iter() {
+        if !names.insert(&dir.name) {
+            res.push(format!(
+                "adr directory name `{}` is used more than once",
+                dir.name
+            ));
+        }
+
+        if !paths.insert(&dir.path) {
+            res.push(format!(
+                "adr directory path {:?} is used more than once",
+                dir.path
+            ));
+        }
+
+        if !dir.full_path.is_dir() {
+            res.push(format!("adr directory {:?} does not exist", dir.path));
+        }
+    }
+
+    let linters = crate::markdown::linter::all();
+
+    if let Err(e) = crate::markdown::linter::configure(&linters, &state.markdown.lint) {
+        res.push(e.to_string());
+    }
+
+    res
+}
@@ -0,0 +1,294 @@
+//! Dotted keys such as `adr.dirs.main.index` for addressing values in
+//! `.docula`. List items are addressed by their `name` when they have one,
+//! otherwise by their position.
+
+use super::yaml::Segment;
+use super::State;
+use lazy_static::lazy_static;
+use serde_yaml::{Mapping, Value};
+
+lazy_static! {
+    /// The schema of `.docula`, which knows every key there is.
+    static ref SCHEMA: serde_json::Value =
+        serde_json::to_value(schemars::schema_for!(State)).unwrap_or_default();
+}
+
+/// Resolves a dotted key against a document. Keys that don't exist yet are
+/// returned as mapping keys so that they can be created.
+pub fn segments(value: &Value, key: &str) -> Result<Vec<Segment>, String> {
+    let mut res = Vec::new();
+    let mut node = Some(value);
+
+    for part in key.split('.') {
+        if part.is_empty() {
+            return Err(format!("`{}` is not a valid key", key));
+        }
+
+        match node {
+            Some(Value::Sequence(items)) => {
+                let index = part
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|x| *x < items.len())
+                    .or_else(|| {
+                        items
+                            .iter()
+                            .position(|x| x.get("name").and_then(|x| x.as_str()) == Some(part))
+                    });
+
+                match index {
+                    None => return Err(format!("nothing is named `{}` in `{}`", part, key)),
+                    Some(x) => {
+                        node = items.get(x);
+                        res.push(Segment::Index(x));
+                    }
+                }
+            }
+            Some(x) => {
+                node = x.get(part);
+                res.push(Segment::Key(part.to_owned()));
+            }
+            None => res.push(Segment::Key(part.to_owned())),
+        }
+    }
+
+    Ok(res)
+}
+
+pub fn get<'a>(value: &'a Value, segments: &[Segment]) -> Option<&'a Value> {
+    segments
+        .iter()
+        .try_fold(value, |node, segment| match segment {
+            Segment::Key(x) => node.get(x.as_str()),
+            Segment::Index(x) => node.get(x),
+        })
+}
+
+pub fn set(value: &mut Value, segments: &[Segment], new: Value) -> Result<(), String> {
+    let mut node = value;
+
+    for segment in segments.iter() {
+        node = match segment {
+            Segment::Index(x) => match node.get_mut(x) {
+                None => return Err(format!("there is no item {} in the list", x)),
+                Some(x) => x,
+            },
+            Segment::Key(x) => {
+                if !node.is_mapping() {
+                    *node = Value::Mapping(Mapping::new());
+                }
+
+                let map = node.as_mapping_mut().unwrap();
+                map.entry(Value::from(x.as_str())).or_insert(Value::Null)
+            }
+        };
+    }
+
+    *node = new;
+
+    Ok(())
+}
+
+pub fn unset(value: &mut Value, segments: &[Segment]) {
+    let (last, parents) = match segments.split_last() {
+        None => return,
+        Some(x) => x,
+    };
+
+    let parent = parents
+        .iter()
+        .try_fold(value, |node, segment| match segment {
+            Segment::Key(x) => node.get_mut(x.as_str()),
+            Segment::Index(x) => node.get_mut(x),
+        });
+
+    match (parent, last) {
+        (Some(Value::Mapping(x)), Segment::Key(k)) => {
+            x.remove(k.as_str());
+        }
+        (Some(Value::Sequence(x)), Segment::Index(i)) if *i < x.len() => {
+            x.remove(*i);
+        }
+        _ => (),
+    }
+}
+
+/// Builds the dotted key for resolved segments, naming list items the same
+/// way `segments` accepts them.
+pub fn dotted(value: &Value, segments: &[Segment]) -> String {
+    let mut res = Vec::new();
+    let mut node = Some(value);
+
+    for segment in segments.iter() {
+        match segment {
+            Segment::Key(x) => {
+                res.push(x.clone());
+                node = node.and_then(|n| n.get(x.as_str()));
+            }
+            Segment::Index(i) => {
+                node = node.and_then(|n| n.get(i));
+                let name = node.and_then(|n| n.get("name")).and_then(|x| x.as_str());
+                res.push(name.map_or(i.to_string(), String::from));
+            }
+        }
+    }
+
+    res.join(".")
+}
+
+/// The values a piece of user input could mean, in order of preference.
+/// Input such as `10` or `true` is also a valid string, so the string is
+/// offered as well for the model to pick from.
+pub fn candidates(input: &str) -> Vec<Value> {
+    let parsed: Value = serde_yaml::from_str(input).unwrap_or(Value::Null);
+
+    match parsed.is_string() {
+        true => vec![parsed],
+        false => vec![parsed, Value::String(input.to_owned())],
+    }
+}
+
+/// Flattens a document into dotted keys and their encoded scalar values.
+pub fn flatten(value: &Value) -> Vec<(String, String)> {
+    let mut res = Vec::new();
+    flatten_into(value, String::new(), &mut res);
+    res
+}
+
+fn flatten_into(value: &Value, prefix: String, res: &mut Vec<(String, String)>) {
+    let join = |key: &str| match prefix.is_empty() {
+        true => key.to_owned(),
+        false => format!("{}.{}", prefix, key),
+    };
+
+    match value {
+        Value::Mapping(map) => {
+            for (k, v) in map.iter() {
+                flatten_into(v, join(&encode(k)), res);
+            }
+        }
+        Value::Sequence(items) => {
+            for (i, item) in items.iter().enumerate() {
+                let name = item
+                    .get("name")
+                    .and_then(|x| x.as_str())
+                    .map_or(i.to_string(), String::from);
+
+                flatten_into(item, join(&name), res);
+            }
+        }
+        x => res.push((prefix, encode(x))),
+    }
+}
+
+/// Whether the schema has a setting at the path. Any key is taken where the
+/// schema allows any, like the options of a lint rule.
+pub fn is_known(segments: &[Segment]) -> bool {
+    known(&SCHEMA, segments)
+}
+
+fn known(node: &serde_json::Value, segments: &[Segment]) -> bool {
+    if let Some(x) = node.get("$ref").and_then(|x| x.as_str()) {
+        return x
+            .strip_prefix('#')
+            .and_then(|x| SCHEMA.pointer(x))
+            .is_some_and(|x| known(x, segments));
+    }
+
+    for combined in ["allOf", "anyOf", "oneOf"] {
+        if let Some(x) = node.get(combined).and_then(|x| x.as_array()) {
+            return x.iter().any(|x| known(x, segments));
+        }
+    }
+
+    let (first, rest) = match segments.split_first() {
+        Some(x) => x,
+        None => return true,
+    };
+
+    let child = match first {
+        Segment::Index(_) => node.get("items"),
+        Segment::Key(x) => node
+            .get("properties")
+            .and_then(|p| p.get(x.as_str()))
+            .or_else(|| node.get("additionalProperties")),
+    };
+
+    match child {
+        Some(serde_json::Value::Bool(x)) => *x,
+        Some(x) => known(x, rest),
+        None => false,
+    }
+}
+
+/// Encodes a value the way it would be written in YAML.
+pub fn encode(value: &Value) -> String {
+    match serde_yaml::to_string(value) {
+        Ok(x) => x.trim_end().to_owned(),
+        Err(_) => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEXT: &str = "adr:\n  dirs:\n  - path: foo\n    name: main\n    index: timestamp\n";
+
+    #[test]
+    fn test_segments() {
+        let value: Value = serde_yaml::from_str(TEXT).unwrap();
+
+        assert_eq!(
+            vec![
+                Segment::Key("adr".to_owned()),
+                Segment::Key("dirs".to_owned()),
+                Segment::Index(0),
+                Segment::Key("index".to_owned()),
+            ],
+            segments(&value, "adr.dirs.main.index").unwrap()
+        );
+
+        assert!(segments(&value, "adr.dirs.other.index").is_err());
+        assert!(segments(&value, "adr..dirs").is_err());
+    }
+
+    #[test]
+    fn test_set_get_unset() {
+        let mut value: Value = serde_yaml::from_str(TEXT).unwrap();
+        let segs = segments(&value, "markdown.fmt.width").unwrap();
+
+        set(&mut value, &segs, Value::from(100)).unwrap();
+        assert_eq!(Some(&Value::from(100)), get(&value, &segs));
+
+        unset(&mut value, &segs);
+        assert_eq!(None, get(&value, &segs));
+    }
+
+    #[test]
+    fn test_flatten() {
+        let value: Value = serde_yaml::from_str(TEXT).unwrap();
+
+        assert_eq!(
+            vec![
+                ("adr.dirs.main.path".to_owned(), "foo".to_owned()),
+                ("adr.dirs.main.name".to_owned(), "main".to_owned()),
+                ("adr.dirs.main.index".to_owned(), "timestamp".to_owned()),
+            ],
+            flatten(&value)
+        );
+    }
+
+    #[test]
+    fn test_is_known() {
+        let value: Value = serde_yaml::from_str(TEXT).unwrap();
+        let known = |key: &str| is_known(&segments(&value, key).unwrap());
+
+        assert!(known("markdown.fmt.width"));
+        assert!(known("markdown.lint.rules.broken-links.timeout"));
+        assert!(known("adr.dirs.main.locale.keywords.accepted"));
+        assert!(!known("markdown.fmt.heading-style"));
+        assert!(!known("markdown.fmt.width.more"));
+        assert!(!known("other"));
+    }
+}
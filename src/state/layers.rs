@@ -0,0 +1,404 @@
+//! Settings come from several places, each overriding the one before it:
+//!
+//! 1. the built-in defaults,
+//! 2. the user's `$XDG_CONFIG_HOME/docula/config.yaml`,
+//! 3. the repository's `.docula`, the outermost one found, or outside of a
+//!    repository the nearest one,
+//! 4. nested `.docula` files on the way down to the working directory, which
+//!    may only change Markdown settings for their subtree,
+//! 5. `DOCULA_*` environment variables, e.g. `DOCULA_MARKDOWN_FMT_WIDTH=100`.
+//!
+//! With `--config`, that file takes the place of 3 and 4.
+
+use super::keys;
+use super::yaml::Segment;
+use super::{Context, ParseError, State};
+use crate::{Error, IoContext};
+use serde_yaml::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+const ENV_PREFIX: &str = "DOCULA_";
+
+/// Where a setting came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    Default,
+    File(PathBuf),
+    Env(String),
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Origin::Default => write!(f, "default"),
+            Origin::File(x) => write!(f, "file:{}", x.display()),
+            Origin::Env(x) => write!(f, "env:{}", x),
+        }
+    }
+}
+
+pub struct Layers {
+    pub value: Value,
+    pub root: PathBuf,
+    context: Context,
+    origins: BTreeMap<String, Origin>,
+}
+
+impl Layers {
+    /// Collects every layer that applies in the context.
+    pub fn load(ctx: &Context) -> crate::Result<Layers> {
+        let files = ctx.files()?;
+
+        let root = match files.first().and_then(|x| x.parent()) {
+            Some(x) => x.to_path_buf(),
+            None => ctx.root.clone().unwrap_or_else(|| ctx.cwd.clone()),
+        };
+
+        let mut layers = Layers {
+            value: serde_yaml::to_value(State::empty(root.clone()))?,
+            root,
+            context: ctx.clone(),
+            origins: BTreeMap::new(),
+        };
+
+        if let Some(path) = global_path().filter(|x| x.is_file()) {
+            layers.add_file(&path, false)?;
+        }
+
+        for (i, path) in files.iter().enumerate() {
+            layers.add_file(path, i == 0)?;
+        }
+
+        let mut vars: Vec<(String, String)> = std::env::vars()
+            .filter(|(k, _)| k.starts_with(ENV_PREFIX))
+            .collect();
+
+        vars.sort();
+
+        for (name, value) in vars.iter() {
+            layers.add_env(name, value)?;
+        }
+
+        Ok(layers)
+    }
+
+    pub fn state(&self) -> crate::Result<State> {
+        let mut state: State = serde_yaml::from_value(self.value.clone())?;
+
+        state.path = self.root.clone();
+        state.context = self.context.clone();
+        state.adr.set_path(self.root.clone());
+
+        Ok(state)
+    }
+
+    pub fn origin(&self, key: &str) -> &Origin {
+        self.origins.get(key).unwrap_or(&Origin::Default)
+    }
+
+    fn add_file(&mut self, path: &Path, is_root: bool) -> crate::Result<()> {
+        let contents = std::fs::read_to_string(path).with_path(path)?;
+        let value = State::parse_layer(&contents, path)?;
+
+        if !is_root && value.get("adr").is_some() {
+            Err(ParseError::message(
+                path.to_path_buf(),
+                format!(
+                    "adr directories can only be set in {}",
+                    self.root.join(".docula").display()
+                ),
+            ))?;
+        }
+
+        for (key, _) in keys::flatten(&value) {
+            self.origins.insert(key, Origin::File(path.to_path_buf()));
+        }
+
+        merge(&mut self.value, value);
+
+        Ok(())
+    }
+
+    /// Applies a `DOCULA_*` variable. Names that match no setting are left
+    /// alone, as other tools may use the prefix too, but a bad value for a
+    /// setting is an error.
+    fn add_env(&mut self, name: &str, raw: &str) -> crate::Result<()> {
+        let rest = name[ENV_PREFIX.len()..].to_uppercase();
+        let mut last_err = None;
+
+        for segments in env_segments(Some(&self.value), &rest) {
+            if !keys::is_known(&segments) {
+                continue;
+            }
+
+            for candidate in keys::candidates(raw) {
+                let mut value = self.value.clone();
+
+                if keys::set(&mut value, &segments, candidate).is_err() {
+                    continue;
+                }
+
+                match serde_yaml::from_value::<State>(value.clone()) {
+                    Ok(_) => {
+                        let key = keys::dotted(&value, &segments);
+                        self.origins.insert(key, Origin::Env(name.to_owned()));
+                        self.value = value;
+                        return Ok(());
+                    }
+                    Err(e) => last_err = Some(e),
+                }
+            }
+        }
+
+        match last_err {
+            None => Ok(()),
+            Some(e) => Err(Error::Encoding {
+                msg: format!("{}={} is not a valid setting", name, raw),
+                source: Box::new(e),
+            }),
+        }
+    }
+}
+
+/// The user's global settings file.
+pub fn global_path() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME").filter(|x| !x.is_empty()) {
+        Some(x) => PathBuf::from(x),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".config"),
+    };
+
+    Some(base.join("docula").join("config.yaml"))
+}
+
+/// Finds the `.docula` files from `dir` upwards, outermost first. The search
+/// doesn't leave the git repository `dir` is in, nor `root` when one is given.
+/// Outside of a repository only the nearest file is used, so that one in a
+/// home directory doesn't take over the projects below it.
+pub fn find_files(dir: &Path, root: Option<&Path>) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut res = Vec::new();
+    let mut bounded = false;
+
+    // Outside of the root only the root's own file applies.
+    let dir = match root {
+        Some(x) if !dir.starts_with(x) => x,
+        _ => dir,
+    };
+
+    for path in dir.ancestors() {
+        let p = path.join(".docula");
+
+        if p.is_dir() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("{} is a directory", p.display()),
+            ));
+        }
+
+        if p.exists() {
+            res.push(p);
+        }
+
+        if path.join(".git").exists() || Some(path) == root {
+            bounded = true;
+            break;
+        }
+    }
+
+    if !bounded {
+        res.truncate(1);
+    }
+
+    res.reverse();
+
+    Ok(res)
+}
+
+fn merge(into: &mut Value, from: Value) {
+    match (into, from) {
+        (Value::Mapping(a), Value::Mapping(b)) => {
+            for (k, v) in b {
+                match a.get_mut(&k) {
+                    Some(x) => merge(x, v),
+                    None => {
+                        a.insert(k, v);
+                    }
+                }
+            }
+        }
+        (a, b) => *a = b,
+    }
+}
+
+/// Maps the rest of a variable name, e.g. `MARKDOWN_LINT_RULES_HEADING_LEVELS`,
+/// onto keys by matching it against the settings that already exist. What is
+/// left over becomes a single new key, spelled either with dashes or with
+/// underscores; the first spelling the model accepts wins.
+fn env_segments(node: Option<&Value>, rest: &str) -> Vec<Vec<Segment>> {
+    let descend = |segment: Segment, child: Option<&Value>, tail: Option<&str>| match tail {
+        None => vec![vec![segment]],
+        Some(tail) => env_segments(child, tail)
+            .into_iter()
+            .map(|mut x| {
+                x.insert(0, segment.clone());
+                x
+            })
+            .collect(),
+    };
+
+    match node {
+        Some(Value::Sequence(items)) => {
+            for (i, item) in items.iter().enumerate() {
+                let name = item
+                    .get("name")
+                    .and_then(|x| x.as_str())
+                    .map_or(i.to_string(), String::from);
+
+                if let Some(tail) = strip_key(rest, &name) {
+                    return descend(Segment::Index(i), Some(item), tail);
+                }
+            }
+
+            Vec::new()
+        }
+        Some(Value::Mapping(map)) => {
+            let best = map
+                .iter()
+                .filter_map(|(k, v)| Some((k.as_str()?, v)))
+                .filter_map(|(k, v)| Some((k, v, strip_key(rest, k)?)))
+                .max_by_key(|(k, _, _)| k.len());
+
+            match best {
+                Some((k, v, tail)) => descend(Segment::Key(k.to_owned()), Some(v), tail),
+                None => new_key(rest),
+            }
+        }
+        _ => new_key(rest),
+    }
+}
+
+fn new_key(rest: &str) -> Vec<Vec<Segment>> {
+    let lower = rest.to_lowercase();
+    let dashed = lower.replace('_', "-");
+
+    match dashed == lower {
+        true => vec![vec![Segment::Key(lower)]],
+        false => vec![vec![Segment::Key(dashed)], vec![Segment::Key(lower)]],
+    }
+}
+
+/// Returns what follows `key` in `rest`: `None` when it is the whole of it.
+fn strip_key<'a>(rest: &'a str, key: &str) -> Option<Option<&'a str>> {
+    let key = key.to_uppercase().replace('-', "_");
+
+    if rest == key {
+        return Some(None);
+    }
+
+    rest.strip_prefix(&key)
+        .and_then(|x| x.strip_prefix('_'))
+        .filter(|x| !x.is_empty())
+        .map(Some)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn key(x: &str) -> Segment {
+        Segment::Key(x.to_owned())
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut a: Value = serde_yaml::from_str("a:\n  b: 1\n  c: [1, 2]\nd: 1\n").unwrap();
+        let b: Value = serde_yaml::from_str("a:\n  c: [3]\n  e: 2\n").unwrap();
+
+        merge(&mut a, b);
+
+        let expected: Value = serde_yaml::from_str("a:\n  b: 1\n  c: [3]\n  e: 2\nd: 1\n").unwrap();
+        assert_eq!(expected, a);
+    }
+
+    #[test]
+    fn test_env_segments() {
+        let value: Value = serde_yaml::from_str(
+            "markdown:\n  fmt:\n    width: 80\n  lint:\n    rules: {}\nadr:\n  dirs:\n  - name: main-dir\n    index: Timestamp\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![vec![key("markdown"), key("fmt"), key("width")]],
+            env_segments(Some(&value), "MARKDOWN_FMT_WIDTH")
+        );
+
+        assert_eq!(
+            vec![
+                vec![
+                    key("markdown"),
+                    key("lint"),
+                    key("rules"),
+                    key("heading-levels")
+                ],
+                vec![
+                    key("markdown"),
+                    key("lint"),
+                    key("rules"),
+                    key("heading_levels")
+                ],
+            ],
+            env_segments(Some(&value), "MARKDOWN_LINT_RULES_HEADING_LEVELS")
+        );
+
+        assert_eq!(
+            vec![vec![
+                key("adr"),
+                key("dirs"),
+                Segment::Index(0),
+                key("index")
+            ]],
+            env_segments(Some(&value), "ADR_DIRS_MAIN_DIR_INDEX")
+        );
+
+        assert!(env_segments(Some(&value), "ADR_DIRS_OTHER_INDEX").is_empty());
+    }
+
+    #[test]
+    fn test_find_files_stops_at_repository() {
+        let tmp = tempdir::TempDir::new("layers_test").unwrap();
+        let repo = tmp.path().join("repo");
+        let nested = repo.join("docs");
+
+        std::fs::create_dir_all(repo.join(".git")).unwrap();
+        std::fs::create_dir_all(&nested).unwrap();
+
+        std::fs::write(tmp.path().join(".docula"), "").unwrap();
+        std::fs::write(repo.join(".docula"), "").unwrap();
+        std::fs::write(nested.join(".docula"), "").unwrap();
+
+        assert_eq!(
+            vec![repo.join(".docula"), nested.join(".docula")],
+            find_files(&nested, None).unwrap()
+        );
+
+        tmp.close().unwrap();
+    }
+
+    #[test]
+    fn test_find_files_without_repository() {
+        let tmp = tempdir::TempDir::new("layers_test").unwrap();
+        let project = tmp.path().join("project");
+
+        std::fs::create_dir_all(project.join("docs")).unwrap();
+        std::fs::write(tmp.path().join(".docula"), "").unwrap();
+        std::fs::write(project.join(".docula"), "").unwrap();
+
+        assert_eq!(
+            vec![project.join(".docula")],
+            find_files(&project.join("docs"), None).unwrap()
+        );
+
+        tmp.close().unwrap();
+    }
+}
@@ -0,0 +1,150 @@
+use super::yaml::{self, Segment};
+use serde_yaml::Value;
+
+/// The layout of `.docula` written by this version of docula. Files without a
+/// `version` key predate versioning and are treated as version 1.
+pub const CURRENT_VERSION: u64 = 2;
+
+struct Migration {
+    from: u64,
+    description: &'static str,
+    apply: fn(&mut Value),
+}
+
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: 1,
+    description: "record the layout version",
+    apply: add_version,
+}];
+
+pub fn version(value: &Value) -> Option<u64> {
+    match value.get("version") {
+        None => Some(1),
+        Some(x) => x.as_u64(),
+    }
+}
+
+/// Upgrades a parsed `.docula` to the current layout, returning a description
+/// of every step that changed something.
+pub fn migrate(value: &mut Value) -> Result<Vec<&'static str>, String> {
+    let mut version = match version(value) {
+        None => return Err("`version` must be a positive number".to_owned()),
+        Some(x) => x,
+    };
+
+    if version > CURRENT_VERSION {
+        return Err(format!(
+            "version {} was written by a newer docula, this one supports up to version {}",
+            version, CURRENT_VERSION
+        ));
+    }
+
+    let mut res = Vec::new();
+
+    while version < CURRENT_VERSION {
+        let migration = match MIGRATIONS.iter().find(|x| x.from == version) {
+            None => return Err(format!("no migration from version {}", version)),
+            Some(x) => x,
+        };
+
+        let before = value.clone();
+        (migration.apply)(value);
+
+        if *value != before {
+            res.push(migration.description);
+        }
+
+        version += 1;
+    }
+
+    if let Value::Mapping(x) = value {
+        x.insert(Value::from("version"), Value::from(version));
+    }
+
+    Ok(res)
+}
+
+/// Upgrades the text of a `.docula` file, returning it along with the steps
+/// that were applied. Only what the file sets is written, never defaults,
+/// and its comments are kept unless a step changes more than the version.
+pub fn migrate_text(text: &str) -> Result<(String, Vec<&'static str>), String> {
+    let original: Value = serde_yaml::from_str(text).map_err(|e| e.to_string())?;
+    let mut value = original.clone();
+    let steps = migrate(&mut value)?;
+
+    if steps.is_empty() {
+        return Ok((text.to_owned(), steps));
+    }
+
+    let version = CURRENT_VERSION.to_string();
+    let edited = match original.get("version") {
+        Some(_) => yaml::set(text, &[Segment::Key("version".to_owned())], &version)?,
+        None => format!("version: {}\n{}", version, text),
+    };
+
+    match serde_yaml::from_str::<Value>(&edited).ok() == Some(value.clone()) {
+        true => Ok((edited, steps)),
+        false => Ok((
+            serde_yaml::to_string(&value).map_err(|e| e.to_string())?,
+            steps,
+        )),
+    }
+}
+
+/// Version 2 only adds the `version` key itself.
+fn add_version(value: &mut Value) {
+    if let Value::Mapping(x) = value {
+        x.insert(Value::from("version"), Value::from(2));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_migrate_v1() {
+        let mut value: Value = serde_yaml::from_str(
+            "adr:\n  dirs:\n  - path: foo\n    name: foo\n    index: Timestamp\n",
+        )
+        .unwrap();
+
+        let steps = migrate(&mut value).unwrap();
+
+        assert_eq!(vec!["record the layout version"], steps);
+        assert_eq!(Some(CURRENT_VERSION), version(&value));
+        assert_eq!(Some("Timestamp"), value["adr"]["dirs"][0]["index"].as_str());
+    }
+
+    #[test]
+    fn test_migrate_text() {
+        let text = "# settings\nmarkdown:\n  fmt:\n    width: 100 # wide\n";
+        let (res, steps) = migrate_text(text).unwrap();
+
+        assert_eq!(vec!["record the layout version"], steps);
+        assert_eq!(format!("version: 2\n{}", text), res);
+
+        let (res, steps) = migrate_text("version: 1\n# empty\n").unwrap();
+
+        assert_eq!(1, steps.len());
+        assert_eq!("version: 2\n# empty\n", res);
+
+        let text = "version: 2\nmarkdown: {}\n";
+
+        assert_eq!((text.to_owned(), Vec::new()), migrate_text(text).unwrap());
+    }
+
+    #[test]
+    fn test_migrate_current() {
+        let mut value: Value = serde_yaml::from_str("version: 2\nadr:\n  dirs: []\n").unwrap();
+
+        assert!(migrate(&mut value).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_migrate_newer() {
+        let mut value: Value = serde_yaml::from_str("version: 99\n").unwrap();
+
+        assert!(migrate(&mut value).is_err());
+    }
+}
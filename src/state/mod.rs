@@ -1,3 +1,16 @@
+mod context;
+mod document;
+mod error;
+pub mod handler;
+mod keys;
+mod layers;
+pub mod migrate;
 mod model;
+mod yaml;
 
+pub use context::Context;
+pub use document::Document;
+pub use error::ParseError;
+pub use keys::encode;
+pub use layers::Origin;
 pub use model::State;
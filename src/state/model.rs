@@ -1,70 +1,188 @@
-use crate::adr;
+use super::layers::Layers;
+use super::migrate::{self, CURRENT_VERSION};
+use super::Context;
+use super::ParseError;
+use crate::{adr, markdown, IoContext};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(title = "docula configuration")]
 pub struct State {
+    /// The layout version of this file, used to upgrade older files.
+    #[serde(default = "current_version")]
+    pub version: u64,
+    #[serde(default)]
     pub adr: adr::state::State,
+    #[serde(default)]
+    pub markdown: markdown::state::State,
     #[serde(skip)]
     pub path: std::path::PathBuf,
+    /// Where the settings were loaded from.
+    #[serde(skip)]
+    pub context: Context,
 }
 
 impl State {
-    pub fn load() -> Result<State, Box<dyn std::error::Error>> {
-        let existing = find_state_path()?;
+    /// Loads the settings that apply in the context, with every layer of
+    /// configuration applied. See `state::layers` for the order.
+    pub fn load(ctx: &Context) -> crate::Result<State> {
+        Layers::load(ctx)?.state()
+    }
 
-        if existing.is_none() {
-            return Ok(State::new()?);
+    /// Loads the settings that apply to `dir`.
+    pub fn load_in(dir: &std::path::Path) -> crate::Result<State> {
+        State::load(&Context::new(dir.to_path_buf()))
+    }
+
+    /// Parses the contents of a `.docula` file, upgrading older layouts. An
+    /// empty file is a valid, empty configuration; anything that does not
+    /// match the model is an error rather than being silently replaced.
+    pub fn parse(contents: &str, path: &std::path::Path) -> Result<State, ParseError> {
+        Ok(State::parse_with_migrations(contents, path)?.0)
+    }
+
+    /// Like `parse`, also returning the migrations that had to be applied.
+    pub fn parse_with_migrations(
+        contents: &str,
+        path: &std::path::Path,
+    ) -> Result<(State, Vec<&'static str>), ParseError> {
+        let dir = path.parent().unwrap_or(path).to_path_buf();
+        let err = |e| ParseError::new(path.to_path_buf(), e);
+
+        if contents.trim().is_empty() {
+            return Ok((State::empty(dir), Vec::new()));
         }
 
-        let path = existing.unwrap();
+        let mut value: serde_yaml::Value = serde_yaml::from_str(contents).map_err(err)?;
 
-        let contents = std::fs::read_to_string(&path)?;
+        // Parse the text itself when no migration is needed, as that keeps the
+        // line and column of any error.
+        if migrate::version(&value) == Some(CURRENT_VERSION) {
+            return Ok((serde_yaml::from_str(contents).map_err(err)?, Vec::new()));
+        }
 
-        let mut state: State = match serde_yaml::from_str(&contents) {
-            Ok(x) => x,
-            _ => State::new()?,
-        };
+        let steps = migrate::migrate(&mut value)
+            .map_err(|msg| ParseError::message(path.to_path_buf(), msg))?;
 
-        state.path = path.parent().unwrap().to_path_buf();
+        Ok((serde_yaml::from_value(value).map_err(err)?, steps))
+    }
 
-        state.adr.set_path(state.path.clone());
+    /// Parses one file into just the values it sets, for layering on top of
+    /// the others.
+    pub(super) fn parse_layer(
+        contents: &str,
+        path: &std::path::Path,
+    ) -> Result<serde_yaml::Value, ParseError> {
+        State::parse(contents, path)?;
 
-        Ok(state)
+        if contents.trim().is_empty() {
+            return Ok(serde_yaml::Value::Mapping(serde_yaml::Mapping::new()));
+        }
+
+        let err = |e| ParseError::new(path.to_path_buf(), e);
+        let mut value: serde_yaml::Value = serde_yaml::from_str(contents).map_err(err)?;
+        let has_version = value.get("version").is_some();
+
+        migrate::migrate(&mut value).map_err(|msg| ParseError::message(path.to_path_buf(), msg))?;
+
+        // A file without a version doesn't set it, even though migrating
+        // fills it in.
+        if let (Some(x), false) = (value.as_mapping_mut(), has_version) {
+            x.remove("version");
+        }
+
+        Ok(value)
     }
 
-    pub fn new() -> Result<State, std::io::Error> {
-        let path = std::env::current_dir()?;
+    pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
+        serde_yaml::to_string(self)
+    }
 
-        Ok(State {
+    /// Finds the nearest `.docula` file.
+    pub fn find(ctx: &Context) -> Result<Option<std::path::PathBuf>, std::io::Error> {
+        Ok(ctx.files()?.pop())
+    }
+
+    /// Finds the repository's `.docula` file, the outermost one.
+    pub fn find_repo(ctx: &Context) -> Result<Option<std::path::PathBuf>, std::io::Error> {
+        Ok(ctx.files()?.into_iter().next())
+    }
+
+    pub(super) fn empty(path: std::path::PathBuf) -> State {
+        State {
+            version: CURRENT_VERSION,
             adr: adr::state::State::new(path.clone()),
+            markdown: markdown::state::State::default(),
+            context: Context::new(path.clone()),
             path,
-        })
+        }
     }
 
-    pub fn save(&self) -> Result<(), std::io::Error> {
-        let encoded = serde_yaml::to_string(self).unwrap();
-        let path = self.path.join(".docula");
+    /// Writes the state to `.docula`. An existing file that does not parse is
+    /// never overwritten, so a typo can't cost the rest of the configuration.
+    pub fn save(&self) -> crate::Result<()> {
+        self.save_to(&self.path.join(".docula"))
+    }
+
+    /// Like `save`, for a file that may have another name.
+    pub fn save_to(&self, path: &std::path::Path) -> crate::Result<()> {
+        let encoded = self.to_yaml()?;
+
+        if path.exists() {
+            State::parse(&std::fs::read_to_string(path).with_path(path)?, path)?;
+        }
+
+        std::fs::write(path, encoded).with_path(path)?;
 
-        std::fs::write(path, encoded)
+        Ok(())
     }
 }
 
-fn find_state_path() -> Result<Option<std::path::PathBuf>, std::io::Error> {
-    check_path(&std::env::current_dir()?)
+fn current_version() -> u64 {
+    CURRENT_VERSION
 }
 
-fn check_path(path: &std::path::Path) -> Result<Option<std::path::PathBuf>, std::io::Error> {
-    let p = path.join(".docula");
-    if p.exists() {
-        return Ok(Some(p));
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_empty() {
+        let state = State::parse("\n", std::path::Path::new("/tmp/.docula")).unwrap();
+
+        assert!(state.adr.dirs.is_empty());
+        assert_eq!(std::path::Path::new("/tmp"), state.path);
+    }
+
+    #[test]
+    fn test_parse_migrates() {
+        let contents = "adr:\n  dirs:\n  - path: foo\n    name: foo\n    index: Sequential\n";
+        let (state, steps) =
+            State::parse_with_migrations(contents, std::path::Path::new("/tmp/.docula")).unwrap();
+
+        assert_eq!(CURRENT_VERSION, state.version);
+        assert_eq!(1, steps.len());
+        assert_eq!(1, state.adr.dirs.len());
     }
 
-    if p.is_dir() {
-        return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, ""));
+    #[test]
+    fn test_default_version() {
+        let state: State = serde_yaml::from_str("{}").unwrap();
+
+        assert_eq!(CURRENT_VERSION, state.version);
     }
 
-    match path.parent() {
-        None => Ok(None),
-        Some(x) => check_path(x),
+    #[test]
+    fn test_parse_error_location() {
+        let contents =
+            "version: 2\nadr:\n  dirs:\n  - path: foo\n    nmae: foo\n    index: Timestamp\n";
+        let err = State::parse(contents, std::path::Path::new("/tmp/.docula")).unwrap_err();
+
+        assert_eq!(Some(5), err.line);
+        assert_eq!(Some(5), err.column);
+        assert!(err.to_string().starts_with("/tmp/.docula:5:5: "));
+        assert!(err.msg.contains("unknown field `nmae`"));
     }
 }
@@ -0,0 +1,490 @@
+//! Minimal editing of block style YAML that keeps comments, blank lines and
+//! the order of keys intact. Only the lines of the value being changed are
+//! rewritten; everything else is copied through untouched.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    static ref KEY_REGEX: Regex = Regex::new(
+        r#"^(\s*(?:-\s+)*)([^\s#'"{}\[\]:,-][^:#]*?|"[^"]*"|'[^']*'):(?:\s+(.*?))?\s*$"#
+    )
+    .unwrap();
+    static ref DASH_REGEX: Regex = Regex::new(r"^(\s*)-(?:\s|$)").unwrap();
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// The lines of a mapping entry, from its key line up to (not including) `end`.
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    line: usize,
+    column: usize,
+    end: usize,
+}
+
+/// Sets the value at `path` to an already encoded YAML scalar, creating any
+/// missing mapping keys along the way.
+pub fn set(text: &str, path: &[Segment], scalar: &str) -> Result<String, String> {
+    let mut lines: Vec<String> = text.lines().map(String::from).collect();
+
+    let (found, parent) = resolve(&lines, path)?;
+
+    match found {
+        Some(span) => {
+            let comment = trailing_comment(&lines[span.line]);
+            let key = key_prefix(&lines[span.line]);
+
+            lines[span.line] = format!("{} {}{}", key, scalar, comment);
+            lines.drain(span.line + 1..last_content_line(&lines, span) + 1);
+        }
+        None => {
+            let (at, column, missing) = parent;
+            let mut new_lines = Vec::new();
+
+            for (depth, segment) in path[path.len() - missing..].iter().enumerate() {
+                let key = match segment {
+                    Segment::Key(x) => x,
+                    Segment::Index(_) => return Err("list items can't be created".to_owned()),
+                };
+
+                let indent = " ".repeat(column + depth * 2);
+
+                match depth + 1 == missing {
+                    true => new_lines.push(format!("{}{}: {}", indent, key, scalar)),
+                    false => new_lines.push(format!("{}{}:", indent, key)),
+                }
+            }
+
+            // An empty inline value such as `rules: {}` gets replaced by the
+            // new block below it.
+            if at > 0 {
+                let previous = &lines[at - 1];
+                if let Some(caps) = KEY_REGEX.captures(previous) {
+                    if caps.get(3).map(|x| x.as_str()) == Some("{}") {
+                        lines[at - 1] = key_prefix(previous);
+                    }
+                }
+            }
+
+            for (i, line) in new_lines.into_iter().enumerate() {
+                lines.insert(at + i, line);
+            }
+        }
+    }
+
+    Ok(join(lines))
+}
+
+/// Appends an item, already encoded as block YAML, to the list at `path`,
+/// creating the list and any missing mapping keys along the way.
+pub fn push(text: &str, path: &[Segment], item: &str) -> Result<String, String> {
+    let text = match resolve(&lines_of(text), path)? {
+        (Some(_), _) => text.to_owned(),
+        (None, _) => set(text, path, "[]")?,
+    };
+
+    let mut lines = lines_of(&text);
+
+    let span = match resolve(&lines, path)? {
+        (Some(x), _) => x,
+        (None, _) => return Err("the list could not be created".to_owned()),
+    };
+
+    let (at, column) = match sequence_items(&lines, span.line + 1, span.end).last() {
+        Some(x) => (last_content_line(&lines, *x) + 1, x.column),
+        None => {
+            // An empty list, like `dirs: []`, becomes a block.
+            let line = &lines[span.line];
+            lines[span.line] = format!("{}{}", key_prefix(line), trailing_comment(line));
+            (span.line + 1, span.column)
+        }
+    };
+
+    for (i, line) in item.lines().enumerate() {
+        let dash = if i == 0 { "- " } else { "  " };
+        lines.insert(at + i, format!("{}{}{}", " ".repeat(column), dash, line));
+    }
+
+    Ok(join(lines))
+}
+
+fn lines_of(text: &str) -> Vec<String> {
+    text.lines().map(String::from).collect()
+}
+
+/// Removes the entry at `path` along with everything nested below it. Removing
+/// something that does not exist is not an error.
+pub fn unset(text: &str, path: &[Segment]) -> Result<String, String> {
+    let mut lines: Vec<String> = text.lines().map(String::from).collect();
+
+    if let (Some(span), _) = resolve(&lines, path)? {
+        let end = last_content_line(&lines, span) + 1;
+
+        match DASH_REGEX.is_match(&lines[span.line]) && !is_item(path) {
+            // The first key of a list item carries the dash, hand it over to
+            // the next key of the item.
+            true => {
+                let dash = key_prefix_dash(&lines[span.line]);
+                lines.drain(span.line..end);
+
+                match lines.get(span.line) {
+                    Some(next) if indent_of(next) == span.column => {
+                        lines[span.line] = format!("{}{}", dash, &next[span.column..]);
+                    }
+                    _ => lines.insert(span.line, format!("{}{{}}", dash)),
+                }
+            }
+            false => {
+                lines.drain(span.line..end);
+            }
+        }
+    }
+
+    Ok(join(lines))
+}
+
+fn is_item(path: &[Segment]) -> bool {
+    matches!(path.last(), Some(Segment::Index(_)))
+}
+
+/// Finds the span of `path`. When it does not exist, returns where the missing
+/// keys should be inserted, at which column, and how many segments are missing.
+#[allow(clippy::type_complexity)]
+fn resolve(
+    lines: &[String],
+    path: &[Segment],
+) -> Result<(Option<Span>, (usize, usize, usize)), String> {
+    let mut start = 0;
+    let mut end = lines.len();
+    let mut column = None;
+    let mut found = None;
+
+    for (i, segment) in path.iter().enumerate() {
+        match segment {
+            Segment::Key(key) => {
+                let entries = mapping_entries(lines, start, end, column);
+
+                match entries.iter().find(|(k, _)| k == key) {
+                    None => {
+                        let child_column =
+                            entries
+                                .first()
+                                .map(|(_, x)| x.column)
+                                .unwrap_or(match found {
+                                    None => 0,
+                                    Some(Span { column, .. }) => column + 2,
+                                });
+
+                        let at = match entries.last() {
+                            None => start,
+                            Some((_, x)) => last_content_line(lines, *x) + 1,
+                        };
+
+                        return Ok((None, (at, child_column, path.len() - i)));
+                    }
+                    Some((_, span)) => {
+                        found = Some(*span);
+                        start = span.line + 1;
+                        end = span.end;
+                        column = None;
+                    }
+                }
+            }
+            Segment::Index(index) => {
+                let items = sequence_items(lines, start, end);
+
+                match items.get(*index) {
+                    None => return Err(format!("there is no item {} in the list", index)),
+                    Some(span) => {
+                        // The item's own keys start on the dash line.
+                        found = Some(*span);
+                        start = span.line;
+                        end = span.end;
+                        column = Some(span.column + 2);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok((found, (0, 0, 0)))
+}
+
+fn mapping_entries(
+    lines: &[String],
+    start: usize,
+    end: usize,
+    column: Option<usize>,
+) -> Vec<(String, Span)> {
+    let mut res: Vec<(String, Span)> = Vec::new();
+    let mut column = column;
+
+    for (num, line) in lines.iter().enumerate().take(end).skip(start) {
+        let caps = match significant(line)
+            .then(|| KEY_REGEX.captures(line))
+            .flatten()
+        {
+            None => continue,
+            Some(x) => x,
+        };
+
+        let key_column = caps.get(1).map_or(0, |x| x.as_str().len());
+
+        if column.is_none() {
+            column = Some(key_column);
+        }
+
+        if Some(key_column) != column {
+            continue;
+        }
+
+        if let Some((_, last)) = res.last_mut() {
+            last.end = num;
+        }
+
+        let key = caps.get(2).unwrap().as_str();
+        let key = key.trim_matches(|x| x == '"' || x == '\'').to_owned();
+
+        res.push((
+            key,
+            Span {
+                line: num,
+                column: key_column,
+                end,
+            },
+        ));
+    }
+
+    res
+}
+
+fn sequence_items(lines: &[String], start: usize, end: usize) -> Vec<Span> {
+    let mut res: Vec<Span> = Vec::new();
+    let mut column = None;
+
+    for (num, line) in lines.iter().enumerate().take(end).skip(start) {
+        if !significant(line) {
+            continue;
+        }
+
+        let dash = match DASH_REGEX.captures(line) {
+            None => continue,
+            Some(x) => x.get(1).map_or(0, |x| x.as_str().len()),
+        };
+
+        if column.is_none() {
+            column = Some(dash);
+        }
+
+        if Some(dash) != column {
+            continue;
+        }
+
+        if let Some(last) = res.last_mut() {
+            last.end = num;
+        }
+
+        res.push(Span {
+            line: num,
+            column: dash,
+            end,
+        });
+    }
+
+    res
+}
+
+/// The last line that belongs to an entry, leaving any blank lines and
+/// comments that precede the next entry in place.
+fn last_content_line(lines: &[String], span: Span) -> usize {
+    (span.line..span.end)
+        .rev()
+        .find(|x| significant(&lines[*x]))
+        .unwrap_or(span.line)
+}
+
+fn significant(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty() && !trimmed.starts_with('#')
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Everything up to and including the colon of a key line.
+fn key_prefix(line: &str) -> String {
+    match KEY_REGEX.captures(line) {
+        None => line.to_owned(),
+        Some(caps) => {
+            let end = caps.get(2).unwrap().end();
+            format!("{}:", &line[..end])
+        }
+    }
+}
+
+fn key_prefix_dash(line: &str) -> String {
+    match DASH_REGEX.find(line) {
+        None => String::new(),
+        Some(x) => {
+            let dash = x.as_str().trim_end();
+            format!("{} ", dash)
+        }
+    }
+}
+
+fn trailing_comment(line: &str) -> String {
+    let value = match KEY_REGEX.captures(line).and_then(|x| x.get(3)) {
+        None => return String::new(),
+        Some(x) => x.as_str(),
+    };
+
+    if value.contains('"') || value.contains('\'') {
+        return String::new();
+    }
+
+    match line.find(" #") {
+        Some(x) => line[x..].to_owned(),
+        None => String::new(),
+    }
+}
+
+fn join(lines: Vec<String>) -> String {
+    match lines.is_empty() {
+        true => String::new(),
+        false => lines.join("\n") + "\n",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEXT: &str = "# docula settings\nversion: 2\nadr:\n  dirs:\n  # the main dir\n  - path: doc/adr\n    name: main\n    index: timestamp # fast\n  - path: other\n    name: other\n    index: sequential\n\n# markdown\nmarkdown:\n  fmt:\n    width: 80\n";
+
+    fn key(x: &str) -> Segment {
+        Segment::Key(x.to_owned())
+    }
+
+    #[test]
+    fn test_set_existing_keeps_comments() {
+        let path = [key("adr"), key("dirs"), Segment::Index(0), key("index")];
+        let res = set(TEXT, &path, "sequential").unwrap();
+
+        assert_eq!(
+            TEXT.replace("index: timestamp # fast", "index: sequential # fast"),
+            res
+        );
+    }
+
+    #[test]
+    fn test_set_first_key_of_item() {
+        let path = [key("adr"), key("dirs"), Segment::Index(1), key("path")];
+        let res = set(TEXT, &path, "elsewhere").unwrap();
+
+        assert_eq!(TEXT.replace("- path: other", "- path: elsewhere"), res);
+    }
+
+    #[test]
+    fn test_set_missing_keys() {
+        let path = [
+            key("markdown"),
+            key("lint"),
+            key("rules"),
+            key("heading-levels"),
+        ];
+        let res = set(TEXT, &path, "false").unwrap();
+
+        assert_eq!(
+            format!("{}  lint:\n    rules:\n      heading-levels: false\n", TEXT),
+            res
+        );
+    }
+
+    #[test]
+    fn test_set_missing_nested_in_item() {
+        let path = [
+            key("adr"),
+            key("dirs"),
+            Segment::Index(0),
+            key("locale"),
+            key("language"),
+        ];
+        let res = set(TEXT, &path, "de").unwrap();
+
+        assert_eq!(
+            TEXT.replace(
+                "index: timestamp # fast\n",
+                "index: timestamp # fast\n    locale:\n      language: de\n"
+            ),
+            res
+        );
+    }
+
+    #[test]
+    fn test_set_missing_top_level() {
+        let res = set(
+            "version: 2\n",
+            &[key("markdown"), key("fmt"), key("width")],
+            "100",
+        )
+        .unwrap();
+
+        assert_eq!("version: 2\nmarkdown:\n  fmt:\n    width: 100\n", res);
+    }
+
+    #[test]
+    fn test_push() {
+        let item = "path: new\nname: new\n";
+        let res = push(TEXT, &[key("adr"), key("dirs")], item).unwrap();
+
+        assert_eq!(
+            TEXT.replace(
+                "    index: sequential\n",
+                "    index: sequential\n  - path: new\n    name: new\n"
+            ),
+            res
+        );
+
+        let res = push(
+            "version: 2\nadr:\n  dirs: [] # none\n",
+            &[key("adr"), key("dirs")],
+            item,
+        );
+
+        assert_eq!(
+            "version: 2\nadr:\n  dirs: # none\n  - path: new\n    name: new\n",
+            res.unwrap()
+        );
+
+        let res = push("# settings\nversion: 2\n", &[key("adr"), key("dirs")], item);
+
+        assert_eq!(
+            "# settings\nversion: 2\nadr:\n  dirs:\n  - path: new\n    name: new\n",
+            res.unwrap()
+        );
+    }
+
+    #[test]
+    fn test_unset() {
+        let res = unset(TEXT, &[key("markdown"), key("fmt"), key("width")]).unwrap();
+        assert_eq!(TEXT.replace("    width: 80\n", ""), res);
+
+        let res = unset(TEXT, &[key("adr"), key("dirs"), Segment::Index(0)]).unwrap();
+        assert_eq!(
+            TEXT.replace(
+                "  - path: doc/adr\n    name: main\n    index: timestamp # fast\n",
+                ""
+            ),
+            res
+        );
+
+        let res = unset(TEXT, &[key("nothing")]).unwrap();
+        assert_eq!(TEXT, res);
+    }
+}
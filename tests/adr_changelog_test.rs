@@ -0,0 +1,131 @@
+use assert_cmd::Command;
+use chrono::Utc;
+use std::error::Error;
+
+fn git(dir: &std::path::Path, args: &[&str]) -> Result<(), Box<dyn Error>> {
+    let output = std::process::Command::new("git")
+        .current_dir(dir)
+        .args([
+            "-c",
+            "user.name=docula",
+            "-c",
+            "user.email=docula@example.com",
+        ])
+        .args(args)
+        .output()?;
+
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_adr_changelog() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("adr_test")?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["adr", "init", "foo/bar", "--name", "foobar"])
+        .assert()
+        .success();
+
+    let today = format!("{}", Utc::now().format("%Y-%m-%d"));
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["adr", "changelog", "--since", &today, "--no-git"])
+        .output()?;
+
+    let stdout = String::from_utf8(output.stdout)?;
+
+    assert!(output.status.success());
+    assert!(stdout.contains("### Created"));
+    assert!(stdout.contains("### Accepted"));
+    assert!(stdout.contains("Record Architecture Decisions](foo/bar/"));
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_adr_changelog_outside_window() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("adr_test")?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["adr", "init", "foo/bar", "--name", "foobar"])
+        .assert()
+        .success();
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args([
+            "adr",
+            "changelog",
+            "--since",
+            "2000-01-01",
+            "--until",
+            "2000-12-31",
+        ])
+        .output()?;
+
+    let stdout = String::from_utf8(output.stdout)?;
+
+    assert!(output.status.success());
+    assert!(stdout.contains("No architecture decisions changed"));
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_adr_changelog_git_ref() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("adr_test")?;
+
+    git(tmp.path(), &["init", "-q"])?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["adr", "init", "foo/bar", "--name", "foobar"])
+        .assert()
+        .success();
+
+    git(tmp.path(), &["add", "-A"])?;
+    git(tmp.path(), &["commit", "-q", "-m", "init"])?;
+    git(tmp.path(), &["tag", "v1.0.0"])?;
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args([
+            "adr",
+            "changelog",
+            "--since",
+            "v1.0.0",
+            "--group-by",
+            "release",
+        ])
+        .output()?;
+
+    let stdout = String::from_utf8(output.stdout)?;
+
+    assert!(output.status.success());
+    assert!(stdout.contains("## v1.0.0"));
+    assert!(stdout.contains("### Accepted"));
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["adr", "changelog", "--since", "not-a-ref"])
+        .assert()
+        .failure();
+
+    tmp.close()?;
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use assert_cmd::Command;
+use std::error::Error;
+
+fn git(dir: &std::path::Path, args: &[&str]) -> Result<(), Box<dyn Error>> {
+    let output = std::process::Command::new("git")
+        .current_dir(dir)
+        .args([
+            "-c",
+            "user.name=docula",
+            "-c",
+            "user.email=docula@example.com",
+        ])
+        .args(args)
+        .output()?;
+
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    Ok(())
+}
+
+fn setup() -> Result<(tempdir::TempDir, std::path::PathBuf), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("adr_test")?;
+
+    git(tmp.path(), &["init", "-q"])?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args([
+            "adr",
+            "init",
+            "adr",
+            "--name",
+            "foo",
+            "--index-type",
+            "sequential",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["adr", "new", "use postgres"])
+        .assert()
+        .success();
+
+    git(tmp.path(), &["add", "-A"])?;
+    git(tmp.path(), &["commit", "-q", "-m", "propose"])?;
+
+    let path = tmp.path().join("adr/00002-use-postgres.md");
+    let content = std::fs::read_to_string(&path)?
+        .replace("Proposed", "Accepted")
+        .replace("## Decision\n\n", "## Decision\n\nWe will use Postgres.\n\n");
+
+    std::fs::write(&path, content)?;
+
+    Ok((tmp, path))
+}
+
+#[test]
+fn test_adr_history() -> Result<(), Box<dyn Error>> {
+    let (tmp, _) = setup()?;
+
+    git(tmp.path(), &["commit", "-q", "-am", "accept"])?;
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["adr", "history", "2"])
+        .output()?;
+
+    let stdout = String::from_utf8(output.stdout)?;
+
+    assert!(output.status.success());
+    assert!(stdout.contains("created as Proposed"));
+    assert!(stdout.contains("Proposed -> Accepted; edited Decision"));
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["adr", "history", "42"])
+        .assert()
+        .failure();
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_adr_diff() -> Result<(), Box<dyn Error>> {
+    let (tmp, _) = setup()?;
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["adr", "diff", "00002"])
+        .output()?;
+
+    let stdout = String::from_utf8(output.stdout)?;
+
+    assert!(output.status.success());
+    assert!(stdout.contains("[Status] (changed)\n- Proposed\n+ Accepted"));
+    assert!(stdout.contains("[Decision] (changed)\n+ We will use Postgres."));
+    assert!(!stdout.contains("[Context]"));
+
+    tmp.close()?;
+
+    Ok(())
+}
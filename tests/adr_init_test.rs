@@ -162,3 +162,96 @@ fn test_creating_parent_dir() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_adr_init_language() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("adr_test")?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["adr", "init", "foo/bar", "--name", "foobar", "--language", "pt"])
+        .assert()
+        .success();
+
+    let docula = std::fs::read_to_string(tmp.path().join(".docula"))?;
+    assert!(docula.contains("language: pt"));
+
+    let adr = tmp.path().join("foo/bar").read_dir()?.flatten().next().unwrap();
+    let content = std::fs::read_to_string(adr.path())?;
+
+    assert!(content.contains("Data: "));
+    assert!(content.contains("## Estado\n\nAceita"));
+    assert!(content.contains("## Consequências"));
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_adr_custom_keywords() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("adr_test")?;
+
+    std::fs::write(
+        tmp.path().join(".docula"),
+        "adr:\n  dirs:\n  - path: adr\n    name: foo\n    index: Sequential\n    locale:\n      \
+        language: de\n      keywords:\n        accepted: Beschlossen\n",
+    )?;
+
+    std::fs::create_dir(tmp.path().join("adr"))?;
+    std::fs::write(
+        tmp.path().join("adr/00001-foo.md"),
+        "# Foo\n\nDatum: 2022-09-01\n\n## Status\n\nBeschlossen\n",
+    )?;
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["adr", "list"])
+        .output()?;
+
+    let stdout = String::from_utf8(output.stdout)?;
+
+    assert!(output.status.success());
+    assert!(stdout.contains("Accepted"));
+    assert!(stdout.contains("2022-09-01"));
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_adr_init_keeps_other_layers_out() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("adr_test")?;
+    let xdg = tmp.path().join("xdg");
+    let repo = tmp.path().join("repo");
+    let text = "# our settings\nversion: 2\nmarkdown:\n  fmt:\n    width: 70 # narrow\n";
+
+    std::fs::create_dir_all(xdg.join("docula"))?;
+    std::fs::create_dir_all(repo.join(".git"))?;
+    std::fs::write(
+        xdg.join("docula/config.yaml"),
+        "version: 2\nmarkdown:\n  fmt:\n    width: 120\n",
+    )?;
+    std::fs::write(repo.join(".docula"), text)?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(&repo)
+        .env("XDG_CONFIG_HOME", &xdg)
+        .env("DOCULA_MARKDOWN_LINT_RULES_HEADING_LEVELS", "false")
+        .args(["adr", "init", "doc/adr", "--name", "main"])
+        .assert()
+        .success();
+
+    assert_eq!(
+        format!(
+            "{}adr:\n  dirs:\n  - path: doc/adr\n    name: main\n    index: Timestamp\n",
+            text
+        ),
+        std::fs::read_to_string(repo.join(".docula"))?
+    );
+
+    tmp.close()?;
+
+    Ok(())
+}
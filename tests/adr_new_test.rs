@@ -0,0 +1,56 @@
+use assert_cmd::cargo::CommandCargoExt;
+use std::collections::HashSet;
+use std::error::Error;
+use std::process::Command;
+
+fn parallel_new(index_type: &str) -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("adr_test")?;
+
+    assert!(Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["adr", "init", "foo/bar", "--name", "foobar"])
+        .args(["--index-type", index_type])
+        .status()?
+        .success());
+
+    let children = (0..8)
+        .map(|i| {
+            Command::cargo_bin("docula")
+                .unwrap()
+                .current_dir(tmp.path())
+                .args(["adr", "new", &format!("decision {}", i)])
+                .spawn()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for mut child in children {
+        assert!(child.wait()?.success());
+    }
+
+    let indexes: Vec<String> = tmp
+        .path()
+        .join("foo/bar")
+        .read_dir()?
+        .flatten()
+        .filter_map(|x| x.file_name().to_str()?.split('-').next().map(String::from))
+        .collect();
+
+    let unique: HashSet<&String> = indexes.iter().collect();
+
+    assert_eq!(9, indexes.len());
+    assert_eq!(9, unique.len());
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_adr_new_parallel_sequential() -> Result<(), Box<dyn Error>> {
+    parallel_new("sequential")
+}
+
+#[test]
+fn test_adr_new_parallel_timestamp() -> Result<(), Box<dyn Error>> {
+    parallel_new("timestamp")
+}
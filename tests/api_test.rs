@@ -0,0 +1,64 @@
+use docula::adr::{Repository, Status};
+use docula::markdown::state::Severity;
+use std::error::Error;
+
+#[test]
+fn test_repository() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("api_test")?;
+
+    std::fs::write(
+        tmp.path().join(".docula"),
+        "version: 2\nadr:\n  dirs:\n  - path: docs/adr\n    name: main\n    index: Sequential\n",
+    )?;
+    std::fs::create_dir_all(tmp.path().join("docs/adr"))?;
+
+    let repo = Repository::open(tmp.path())?;
+
+    assert_eq!(1, repo.dirs().len());
+    assert!(repo.list(None)?.is_empty());
+
+    let adr = repo.create(Some("main"), "Use Rust")?;
+
+    assert_eq!("Use Rust", adr.title);
+    assert_eq!(Some(Status::Proposed), adr.status);
+
+    let adrs = repo.list(None)?;
+
+    assert_eq!(1, adrs.len());
+    assert_eq!(adr.index, adrs[0].index);
+    assert_eq!("Use Rust", repo.find(None, &adr.index)?.title);
+    assert!(repo.find(None, "42").is_err());
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_markdown() {
+    let state = docula::markdown::state::State::default();
+    let content = "# Title\n\n### Detail\n";
+
+    assert_eq!(2, docula::markdown::parse(content).len());
+
+    let findings = docula::markdown::lint(content, &state).unwrap();
+
+    assert_eq!(1, findings.len());
+    assert_eq!("heading-levels", findings[0].rule);
+    assert_eq!(Severity::Error, findings[0].severity);
+    assert_eq!(2, findings[0].issue.line_start);
+
+    let disabled = "# Title
+
+<!-- docula-disable-next-line heading-levels -->
+### Detail
+";
+
+    assert!(docula::markdown::lint(disabled, &state).unwrap().is_empty());
+
+    let long = format!("{}\n", "word ".repeat(30).trim());
+    let formatted = docula::markdown::format(&long, &state);
+
+    assert!(formatted.lines().all(|x| x.len() <= 80));
+    assert_eq!(2, formatted.lines().count());
+}
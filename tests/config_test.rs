@@ -0,0 +1,390 @@
+use assert_cmd::Command;
+use std::error::Error;
+
+const BROKEN: &str = "adr:\n  dirs:\n  - path: foo\n    name: foo\n    index: Timestamp\n  oops\n";
+
+#[test]
+fn test_broken_config_is_not_overwritten() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("config_test")?;
+    let docula = tmp.path().join(".docula");
+
+    std::fs::write(&docula, BROKEN)?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["adr", "init", "bar", "--name", "bar"])
+        .assert()
+        .failure();
+
+    assert_eq!(BROKEN, std::fs::read_to_string(&docula)?);
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_config_validate() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("config_test")?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["config", "validate"])
+        .assert()
+        .failure();
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["adr", "init", "foo", "--name", "foo"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["config", "validate"])
+        .assert()
+        .success();
+
+    std::fs::remove_dir_all(tmp.path().join("foo"))?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["config", "validate"])
+        .assert()
+        .failure();
+
+    std::fs::write(tmp.path().join(".docula"), BROKEN)?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["config", "validate"])
+        .assert()
+        .failure();
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_config_migrate() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("config_test")?;
+    let docula = tmp.path().join(".docula");
+    let old = "adr:\n  dirs:\n  - path: foo\n    name: foo\n    index: Timestamp\n";
+
+    std::fs::create_dir(tmp.path().join("foo"))?;
+    std::fs::write(&docula, old)?;
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["config", "migrate", "--dry-run"])
+        .output()?;
+
+    let stdout = String::from_utf8(output.stdout)?;
+
+    assert!(output.status.success());
+    assert!(stdout.contains("+ version: 2\n"));
+    assert_eq!(old, std::fs::read_to_string(&docula)?);
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["config", "migrate"])
+        .assert()
+        .success();
+
+    assert_eq!(
+        format!("version: 2\n{}", old),
+        std::fs::read_to_string(&docula)?
+    );
+
+    // A nested file only gets the version, none of the defaults.
+    let nested = tmp.path().join("docs/.docula");
+    std::fs::create_dir(tmp.path().join("docs"))?;
+    std::fs::write(&nested, "# wide docs\nmarkdown:\n  fmt:\n    width: 120\n")?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path().join("docs"))
+        .args(["config", "migrate"])
+        .assert()
+        .success();
+
+    assert_eq!(
+        "version: 2\n# wide docs\nmarkdown:\n  fmt:\n    width: 120\n",
+        std::fs::read_to_string(&nested)?
+    );
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path().join("docs"))
+        .args(["config", "validate"])
+        .assert()
+        .success();
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_config_newer_version() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("config_test")?;
+
+    std::fs::write(tmp.path().join(".docula"), "version: 99\n")?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["adr", "list"])
+        .assert()
+        .failure();
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_published_schema_is_current() -> Result<(), Box<dyn Error>> {
+    let published =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("schema/docula.schema.json");
+
+    let output = Command::cargo_bin("docula")?
+        .args(["config", "schema"])
+        .output()?;
+
+    assert!(output.status.success());
+    assert_eq!(
+        std::fs::read_to_string(published)?,
+        String::from_utf8(output.stdout)?,
+        "run `docula config schema > schema/docula.schema.json` to update it"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_config_set_keeps_comments() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("config_test")?;
+    let docula = tmp.path().join(".docula");
+
+    std::fs::write(
+        &docula,
+        "# settings\nversion: 2\n\n# formatting\nmarkdown:\n  fmt:\n    width: 80 # columns\n",
+    )?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["config", "set", "markdown.fmt.width", "100"])
+        .assert()
+        .success();
+
+    assert_eq!(
+        "# settings\nversion: 2\n\n# formatting\nmarkdown:\n  fmt:\n    width: 100 # columns\n",
+        std::fs::read_to_string(&docula)?
+    );
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["config", "get", "markdown.fmt.width"])
+        .assert()
+        .success()
+        .stdout("100\n");
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["config", "set", "markdown.fmt.width", "wide"])
+        .assert()
+        .failure();
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["config", "set", "markdown.nothing", "1"])
+        .assert()
+        .failure();
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["config", "unset", "markdown.fmt.width"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["config", "get", "markdown.fmt.width"])
+        .assert()
+        .success()
+        .stdout("80\n");
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_config_list() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("config_test")?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["adr", "init", "foo", "--name", "main"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["config", "set", "adr.dirs.main.index", "Sequential"])
+        .assert()
+        .success();
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["config", "list"])
+        .output()?;
+
+    let stdout = String::from_utf8(output.stdout)?;
+
+    assert!(stdout.contains("adr.dirs.main.index=Sequential\n"));
+    assert!(stdout.contains("markdown.fmt.width=80\n"));
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_config_layers() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("config_test")?;
+    let xdg = tmp.path().join("xdg");
+    let repo = tmp.path().join("repo");
+    let docs = repo.join("docs");
+
+    std::fs::create_dir_all(xdg.join("docula"))?;
+    std::fs::create_dir_all(repo.join(".git"))?;
+    std::fs::create_dir_all(&docs)?;
+
+    let global = xdg.join("docula").join("config.yaml");
+    std::fs::write(
+        &global,
+        "version: 2\nmarkdown:\n  fmt:\n    width: 60\n  lint:\n    rules:\n      heading-levels: false\n",
+    )?;
+    std::fs::write(
+        repo.join(".docula"),
+        "version: 2\nmarkdown:\n  fmt:\n    width: 70\n",
+    )?;
+    std::fs::write(
+        docs.join(".docula"),
+        "version: 2\nmarkdown:\n  lint:\n    rules:\n      heading-levels: true\n",
+    )?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(&repo)
+        .env("XDG_CONFIG_HOME", &xdg)
+        .args(["config", "list", "--show-origin"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "file:{0}\tversion=2\nfile:{0}\tmarkdown.fmt.width=70\ndefault\tmarkdown.fmt.heading_style=atx\nfile:{1}\tmarkdown.lint.rules.heading-levels=false\ndefault\tmarkdown.graph.entry.0=README.md\n",
+            repo.join(".docula").display(),
+            global.display(),
+        ));
+
+    Command::cargo_bin("docula")?
+        .current_dir(&docs)
+        .env("XDG_CONFIG_HOME", &xdg)
+        .env("DOCULA_MARKDOWN_FMT_WIDTH", "90")
+        .args(["config", "list", "--show-origin"])
+        .assert()
+        .success()
+        .stdout(format!(
+            "file:{0}\tversion=2\nenv:DOCULA_MARKDOWN_FMT_WIDTH\tmarkdown.fmt.width=90\ndefault\tmarkdown.fmt.heading_style=atx\nfile:{0}\tmarkdown.lint.rules.heading-levels=true\ndefault\tmarkdown.graph.entry.0=README.md\n",
+            docs.join(".docula").display(),
+        ));
+
+    Command::cargo_bin("docula")?
+        .current_dir(&docs)
+        .env("XDG_CONFIG_HOME", &xdg)
+        .env("DOCULA_MARKDOWN_FMT_WIDTH", "wide")
+        .args(["config", "list"])
+        .assert()
+        .failure();
+
+    std::fs::write(docs.join(".docula"), "version: 2\nadr:\n  dirs: []\n")?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(&docs)
+        .env("XDG_CONFIG_HOME", &xdg)
+        .args(["config", "list"])
+        .assert()
+        .failure();
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_global_path_options() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("config_test")?;
+    let project = tmp.path().join("project");
+    let docs = project.join("docs");
+
+    std::fs::create_dir_all(&docs)?;
+    std::fs::create_dir(project.join(".git"))?;
+    std::fs::write(
+        project.join(".docula"),
+        "version: 2\nmarkdown:\n  fmt:\n    heading_style: setext\n",
+    )?;
+    std::fs::write(docs.join(".docula"), "markdown:\n  fmt:\n    width: 100\n")?;
+    std::fs::write(
+        tmp.path().join("ci.yaml"),
+        "markdown:\n  fmt:\n    width: 120\n",
+    )?;
+
+    let get = |args: &[&str], key: &str| -> Result<String, Box<dyn Error>> {
+        let output = Command::cargo_bin("docula")?
+            .current_dir(tmp.path())
+            .env("XDG_CONFIG_HOME", tmp.path())
+            .args(args)
+            .args(["config", "get", key])
+            .output()?;
+
+        assert!(output.status.success());
+
+        Ok(String::from_utf8(output.stdout)?)
+    };
+
+    let width = "markdown.fmt.width";
+    let style = "markdown.fmt.heading_style";
+
+    assert_eq!("80\n", get(&[], width)?);
+    assert_eq!("100\n", get(&["--cwd", "project/docs"], width)?);
+    assert_eq!("setext\n", get(&["--cwd", "project/docs"], style)?);
+
+    // The project's own file is above the root, so it is not read.
+    assert_eq!(
+        "100\n",
+        get(&["--cwd", "project/docs", "--root", "."], width)?
+    );
+    assert_eq!(
+        "atx\n",
+        get(&["--cwd", "project/docs", "--root", "."], style)?
+    );
+
+    // An explicit file replaces every .docula file.
+    let config = ["--cwd", "project/docs", "--config", "../../ci.yaml"];
+
+    assert_eq!("120\n", get(&config, width)?);
+    assert_eq!("atx\n", get(&config, style)?);
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .env("XDG_CONFIG_HOME", tmp.path())
+        .args(["--cwd", "project", "adr", "init", "adr", "--name", "main"])
+        .assert()
+        .success();
+
+    assert!(project.join("adr").is_dir());
+    assert!(std::fs::read_to_string(project.join(".docula"))?.contains("name: main"));
+
+    tmp.close()?;
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use assert_cmd::Command;
+use std::error::Error;
+
+#[test]
+fn test_failure_exit_code() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("error_test")?;
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["adr", "list"])
+        .output()?;
+
+    assert_eq!(Some(docula::EXIT_FAILURE), output.status.code());
+    assert_eq!(
+        "error: please set up an adr dir using the `init` command\n",
+        String::from_utf8(output.stderr)?
+    );
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_error_location() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("error_test")?;
+    let docula = tmp.path().join(".docula");
+
+    std::fs::write(&docula, "version: 2\nmarkdown:\n  fmt:\n    widht: 80\n")?;
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["config", "list"])
+        .output()?;
+
+    let stderr = String::from_utf8(output.stderr)?;
+
+    assert_eq!(Some(docula::EXIT_FAILURE), output.status.code());
+    assert!(stderr.starts_with(&format!("error: {}:4:5: ", docula.display())));
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_verbose_shows_cause() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("error_test")?;
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .env("DOCULA_MARKDOWN", "oops")
+        .args(["config", "list", "--verbose"])
+        .output()?;
+
+    let stderr = String::from_utf8(output.stderr)?;
+
+    assert_eq!(Some(docula::EXIT_FAILURE), output.status.code());
+    assert!(stderr.starts_with("error: DOCULA_MARKDOWN=oops is not a valid setting\n"));
+    assert!(stderr.contains("  caused by: "));
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .env("DOCULA_MARKDOWN", "oops")
+        .args(["config", "list"])
+        .output()?;
+
+    let stderr = String::from_utf8(output.stderr)?;
+
+    assert!(stderr.contains("--verbose"));
+    assert!(!stderr.contains("caused by"));
+
+    // Variables of other tools that share the prefix are no settings.
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .env("DOCULA_HOME", "/opt/docula")
+        .env("DOCULA_MARKDOWN_FMT_WIDTHS", "90")
+        .args(["config", "list"])
+        .assert()
+        .success();
+
+    tmp.close()?;
+
+    Ok(())
+}
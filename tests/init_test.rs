@@ -0,0 +1,68 @@
+use assert_cmd::Command;
+use std::error::Error;
+
+#[test]
+fn test_init() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("init_test")?;
+
+    std::fs::create_dir_all(tmp.path().join(".git"))?;
+    std::fs::write(
+        tmp.path().join("GUIDE.md"),
+        format!(
+            "Guide\n=====\n\n{}",
+            "A short wrapped line of prose.\n".repeat(12)
+        ),
+    )?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["init", "--adr", "docs/adr", "--readme", "--lint", "--hook"])
+        .assert()
+        .success();
+
+    let docula = std::fs::read_to_string(tmp.path().join(".docula"))?;
+
+    assert!(docula.starts_with("# Settings for docula."));
+    assert!(docula.contains("    width: 80\n"));
+    assert!(docula.contains("    heading_style: setext\n"));
+    assert!(docula.contains("      heading-levels: true\n"));
+
+    assert!(tmp.path().join("docs/README.md").exists());
+    assert!(tmp.path().join(".git/hooks/pre-commit").exists());
+
+    let adrs = std::fs::read_dir(tmp.path().join("docs/adr"))?.count();
+    assert_eq!(1, adrs);
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["config", "validate"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["init"])
+        .assert()
+        .failure();
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_init_hook_needs_git() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("init_test")?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["init", "--hook"])
+        .assert()
+        .failure();
+
+    assert!(!tmp.path().join(".docula").exists());
+
+    tmp.close()?;
+
+    Ok(())
+}
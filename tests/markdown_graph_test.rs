@@ -0,0 +1,87 @@
+use assert_cmd::Command;
+use serde_json::json;
+use std::error::Error;
+
+#[test]
+fn test_markdown_graph() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("graph_test")?;
+
+    std::fs::create_dir(tmp.path().join(".git"))?;
+    std::fs::create_dir(tmp.path().join("docs"))?;
+    std::fs::write(
+        tmp.path().join("README.md"),
+        "# Readme\n\nSee [the guide](docs/guide.md) and [itself](#readme).\n",
+    )?;
+    std::fs::write(
+        tmp.path().join("docs/guide.md"),
+        "# Guide\n\n[Setup][] and [home](/README.md).\n\n[setup]: setup.md\n",
+    )?;
+    std::fs::write(tmp.path().join("docs/setup.md"), "# Setup\n")?;
+    std::fs::write(
+        tmp.path().join("docs/old.md"),
+        "# Old\n\n[Setup](setup.md)\n",
+    )?;
+
+    let graph = |args: &[&str]| -> Result<std::process::Output, Box<dyn Error>> {
+        Ok(Command::cargo_bin("docula")?
+            .current_dir(tmp.path())
+            .args(["markdown", "graph", "-r", "."])
+            .args(args)
+            .output()?)
+    };
+
+    let output = graph(&[])?;
+
+    assert!(output.status.success());
+    assert_eq!(
+        "orphans:\n  docs/old.md\ndead ends:\n  docs/setup.md\ncycles:\n  README.md, docs/guide.md\n",
+        String::from_utf8(output.stdout)?
+    );
+
+    let output = graph(&["--entry", "docs/old.md", "-f", "dot"])?;
+
+    assert_eq!(
+        "digraph docs {\n  \"README.md\" [style=dashed];\n  \"docs/guide.md\" [style=dashed];\n  \
+         \"docs/old.md\" [shape=box];\n  \"docs/setup.md\";\n  \
+         \"README.md\" -> \"docs/guide.md\";\n  \"docs/guide.md\" -> \"README.md\";\n  \
+         \"docs/guide.md\" -> \"docs/setup.md\";\n  \"docs/old.md\" -> \"docs/setup.md\";\n}\n",
+        String::from_utf8(output.stdout)?
+    );
+
+    let output = graph(&["--entry", "missing.md"])?;
+
+    assert_eq!(Some(2), output.status.code());
+
+    let graph = |args: &[&str]| -> Result<std::process::Output, Box<dyn Error>> {
+        Ok(Command::cargo_bin("docula")?
+            .current_dir(tmp.path())
+            .args(["markdown", "graph", "docs"])
+            .args(args)
+            .output()?)
+    };
+
+    let output = graph(&[])?;
+
+    assert!(output.status.success());
+    assert_eq!(
+        "dead ends:\n  docs/setup.md\n",
+        String::from_utf8(output.stdout)?
+    );
+
+    std::fs::write(
+        tmp.path().join("docs/README.md"),
+        "# Docs\n\n[Guide](guide.md)\n",
+    )?;
+
+    let output = graph(&["-f", "json"])?;
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+
+    assert!(output.status.success());
+    assert_eq!(json!("docs/README.md"), json["nodes"][0]["path"]);
+    assert_eq!(json!(true), json["nodes"][0]["entry"]);
+    assert_eq!(json!(true), json["nodes"][2]["orphan"]);
+
+    tmp.close()?;
+
+    Ok(())
+}
@@ -0,0 +1,473 @@
+use assert_cmd::Command;
+use std::error::Error;
+
+#[test]
+fn test_markdown_lint() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("lint_test")?;
+    let docs = tmp.path().join("docs");
+
+    std::fs::create_dir_all(docs.join("nested"))?;
+    std::fs::write(docs.join("good.md"), "# Title\n\n## Part\n")?;
+    std::fs::write(docs.join("bad.md"), "# Title\n\nText\n\n### Detail\n")?;
+    std::fs::write(docs.join("nested/bad.md"), "## Part\n")?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["markdown", "lint", "docs/good.md"])
+        .assert()
+        .success();
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["markdown", "lint", "docs/bad.md"])
+        .output()?;
+
+    let stdout = String::from_utf8(output.stdout)?;
+
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!(
+        "docs/bad.md:5:1: error[heading-levels]: Skipped 2nd level header\n    ### Detail\n",
+        stdout
+    );
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["markdown", "lint", "docs"])
+        .output()?;
+
+    assert_eq!(1, String::from_utf8(output.stdout)?.lines().count() / 2);
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["markdown", "lint", "--recursive", "docs"])
+        .output()?;
+
+    let stdout = String::from_utf8(output.stdout)?;
+
+    assert!(stdout.contains("docs/nested/bad.md:1:1: error[heading-levels]"));
+    assert!(String::from_utf8(output.stderr)?.contains("2 issue(s) found"));
+
+    std::fs::write(
+        tmp.path().join(".docula"),
+        "version: 2\nmarkdown:\n  lint:\n    rules:\n      heading-levels: false\n",
+    )?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["markdown", "lint", "--recursive", "docs"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["markdown", "lint", "docs/missing.md"])
+        .assert()
+        .code(2);
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_markdown_lint_selection() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("lint_test")?;
+
+    std::fs::create_dir_all(tmp.path().join("docs/drafts"))?;
+    std::fs::create_dir_all(tmp.path().join("notes"))?;
+    std::fs::write(tmp.path().join("docs/drafts/a.md"), "## Draft\n")?;
+    std::fs::write(tmp.path().join("notes/b.markdown"), "## Note\n")?;
+    std::fs::write(tmp.path().join(".doculaignore"), "notes/\n")?;
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["markdown", "lint", "-r", ".", "--exclude", "drafts"])
+        .assert()
+        .success();
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["markdown", "lint", "-r", ".", "notes/b.markdown"])
+        .output()?;
+
+    let stdout = String::from_utf8(output.stdout)?;
+
+    assert!(stdout.contains("docs/drafts/a.md:1:1: error[heading-levels]"));
+    assert!(stdout.contains("notes/b.markdown:1:1: error[heading-levels]"));
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["markdown", "lint", "-"])
+        .write_stdin("# Title\n\n### Detail\n")
+        .output()?;
+
+    assert_eq!(Some(1), output.status.code());
+    assert!(String::from_utf8(output.stdout)?.starts_with("<stdin>:3:1: error[heading-levels]"));
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_markdown_lint_rule_settings() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("lint_test")?;
+    let docula = tmp.path().join(".docula");
+
+    std::fs::write(tmp.path().join("a.md"), "## Part\n\n#### Detail\n")?;
+
+    let lint = || -> Result<std::process::Output, Box<dyn Error>> {
+        Ok(Command::cargo_bin("docula")?
+            .current_dir(tmp.path())
+            .args(["markdown", "lint", "a.md"])
+            .output()?)
+    };
+
+    std::fs::write(
+        &docula,
+        "version: 2\nmarkdown:\n  lint:\n    rules:\n      heading-levels: warning\n",
+    )?;
+
+    let output = lint()?;
+
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("a.md:1:1: warning[heading-levels]"));
+
+    std::fs::write(
+        &docula,
+        "version: 2\nmarkdown:\n  lint:\n    rules:\n      heading-levels:\n        first_level: 2\n",
+    )?;
+
+    let output = lint()?;
+
+    assert_eq!(Some(1), output.status.code());
+    assert!(String::from_utf8(output.stdout)?.starts_with("a.md:3:1: error[heading-levels]"));
+
+    std::fs::write(
+        &docula,
+        "version: 2\nmarkdown:\n  lint:\n    rules:\n      heading-levels:\n        first_levl: 2\n",
+    )?;
+
+    let output = lint()?;
+
+    assert_eq!(Some(2), output.status.code());
+    assert!(String::from_utf8(output.stderr)?
+        .contains("invalid options for lint rule `heading-levels`"));
+
+    std::fs::write(
+        &docula,
+        "version: 2\nmarkdown:\n  lint:\n    rules:\n      no-such-rule: false\n",
+    )?;
+
+    assert_eq!(Some(2), lint()?.status.code());
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["markdown", "lint", "--list-rules"])
+        .output()?;
+
+    let stdout = String::from_utf8(output.stdout)?;
+
+    assert_eq!(
+        Some(vec!["heading-levels", "error", "fixable"]),
+        stdout
+            .lines()
+            .next()
+            .map(|x| x.split_whitespace().take(3).collect::<Vec<_>>())
+    );
+    assert!(stdout.contains("    first_level: 1"));
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_markdown_lint_suppressions() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("lint_test")?;
+
+    std::fs::write(
+        tmp.path().join("a.md"),
+        "<!-- docula-disable-next-line heading-levels -->\n## Part\n\n\
+         <!-- docula-disable-next-line heading-levels -->\nText\n",
+    )?;
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["markdown", "lint", "a.md"])
+        .output()?;
+
+    assert!(output.status.success());
+    assert_eq!(
+        "a.md:4:1: warning[unused-suppression]: \
+         `docula-disable-next-line heading-levels` does not silence anything\n    \
+         <!-- docula-disable-next-line heading-levels -->\n",
+        String::from_utf8(output.stdout)?
+    );
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_markdown_lint_fix() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("lint_test")?;
+    let path = tmp.path().join("a.md");
+
+    std::fs::write(&path, "# A\n### B\n#### C\n")?;
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["markdown", "lint", "--fix", "a.md"])
+        .output()?;
+
+    assert!(output.status.success());
+    assert_eq!("", String::from_utf8(output.stdout)?);
+    assert_eq!(
+        "fixed 2 issue(s) in a.md\n",
+        String::from_utf8(output.stderr)?
+    );
+    assert_eq!("# A\n## B\n### C\n", std::fs::read_to_string(&path)?);
+
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["markdown", "lint", "--fix", "-"])
+        .write_stdin("# A\n### B\n")
+        .assert()
+        .code(2);
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_markdown_lint_formats() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("lint_test")?;
+
+    std::fs::write(tmp.path().join("a.md"), "# A\n\n### B\n")?;
+
+    let lint = |format: &str| -> Result<String, Box<dyn Error>> {
+        let output = Command::cargo_bin("docula")?
+            .current_dir(tmp.path())
+            .args(["markdown", "lint", "--format", format, "a.md"])
+            .output()?;
+
+        assert_eq!(Some(1), output.status.code());
+
+        Ok(String::from_utf8(output.stdout)?)
+    };
+
+    let json: serde_json::Value = serde_json::from_str(&lint("json")?)?;
+
+    assert_eq!("heading-levels", json[0]["rule"]);
+    assert_eq!(3, json[0]["line_start"]);
+    assert_eq!(true, json[0]["fixable"]);
+
+    let sarif: serde_json::Value = serde_json::from_str(&lint("sarif")?)?;
+
+    assert_eq!("docula", sarif["runs"][0]["tool"]["driver"]["name"]);
+    assert_eq!(
+        "a.md",
+        sarif["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["artifactLocation"]
+            ["uri"]
+    );
+
+    assert!(lint("checkstyle")?.contains(r#"<error line="3" column="1" severity="error""#));
+    assert!(lint("junit")?.contains(r#"<testsuite name="a.md" tests="1" failures="1">"#));
+    assert_eq!(
+        "::error file=a.md,line=3,endLine=3,col=1,endColumn=6,\
+         title=heading-levels::Skipped 2nd level header\n",
+        lint("github")?
+    );
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_markdown_lint_links() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("lint_test")?;
+
+    std::fs::create_dir(tmp.path().join(".git"))?;
+    std::fs::create_dir(tmp.path().join("docs"))?;
+    std::fs::write(tmp.path().join("docs/setup.md"), "# Setup\n\n## Install\n")?;
+    std::fs::write(
+        tmp.path().join("docs/index.md"),
+        "# Docs\n\nSee [setup](./setup.md#install), [usage](usage.md) \
+         and [the end](/docs/setup.md#uninstall).\n",
+    )?;
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["markdown", "lint", "docs/index.md"])
+        .output()?;
+
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!(
+        "docs/index.md:3:34: error[broken-links]: Link target `usage.md` does not exist\n    \
+         See [setup](./setup.md#install), [usage](usage.md) and [the end](/docs/setup.md#uninstall).\n\
+         docs/index.md:3:56: error[broken-links]: No heading for `#uninstall` in `/docs/setup.md`\n    \
+         See [setup](./setup.md#install), [usage](usage.md) and [the end](/docs/setup.md#uninstall).\n",
+        String::from_utf8(output.stdout)?
+    );
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_markdown_lint_external_links() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("lint_test")?;
+    let server = tiny_http::Server::http("127.0.0.1:0").map_err(|e| e.to_string())?;
+    let base = format!("http://{}", server.server_addr());
+
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let status = match request.url() {
+                "/ok" => 200,
+                _ => 404,
+            };
+
+            let _ = request.respond(tiny_http::Response::empty(status));
+        }
+    });
+
+    std::fs::write(
+        tmp.path().join("a.md"),
+        format!("# A\n\n[ok]({0}/ok) [gone]({0}/gone)\n", base),
+    )?;
+
+    let lint = |external: bool| -> Result<std::process::Output, Box<dyn Error>> {
+        let mut cmd = Command::cargo_bin("docula")?;
+        cmd.current_dir(tmp.path())
+            .env("XDG_CACHE_HOME", tmp.path().join("cache"))
+            .args(["markdown", "lint", "a.md"]);
+
+        if external {
+            cmd.arg("--check-external");
+        }
+
+        Ok(cmd.output()?)
+    };
+
+    assert!(lint(false)?.status.success());
+
+    let output = lint(true)?;
+
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!(
+        format!(
+            "a.md:3:{}: error[broken-links]: `{}/gone` returned HTTP 404\n",
+            base.len() + 11,
+            base
+        ),
+        String::from_utf8(output.stdout)?
+            .lines()
+            .next()
+            .unwrap()
+            .to_owned()
+            + "\n"
+    );
+    assert!(tmp.path().join("cache/docula/links.json").exists());
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_markdown_lint_images() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("lint_test")?;
+
+    std::fs::create_dir(tmp.path().join("img"))?;
+    std::fs::write(tmp.path().join("img/logo.png"), [0; 10])?;
+    std::fs::write(tmp.path().join("img/diagram.svg"), "<svg/>")?;
+    std::fs::write(tmp.path().join("img/old.PNG"), [0; 10])?;
+    std::fs::write(
+        tmp.path().join("README.md"),
+        "# Readme\n\n![](img/logo.png) ![Flow](img/flow.png)\n\n<img src=\"img/diagram.svg\">\n",
+    )?;
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["markdown", "lint", "-r", ".", "--unused-assets"])
+        .output()?;
+
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!(
+        "README.md:3:1: warning[image-alt]: Image has no alt text\n    \
+         ![](img/logo.png) ![Flow](img/flow.png)\n\
+         README.md:3:19: error[images]: Image `img/flow.png` does not exist\n    \
+         ![](img/logo.png) ![Flow](img/flow.png)\n\
+         img/old.PNG:1:1: warning[unused-assets]: Not referenced by any Markdown file\n",
+        String::from_utf8(output.stdout)?
+    );
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_markdown_lint_images_used_elsewhere() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("lint_test")?;
+
+    std::fs::create_dir(tmp.path().join(".git"))?;
+    std::fs::create_dir_all(tmp.path().join("docs/img"))?;
+    std::fs::write(tmp.path().join("docs/img/shown.png"), [0; 10])?;
+    std::fs::write(tmp.path().join("docs/img/old.png"), [0; 10])?;
+    std::fs::write(tmp.path().join("docs/guide.md"), "# Guide\n")?;
+    std::fs::write(
+        tmp.path().join("README.md"),
+        "# Readme\n\n![Shown](docs/img/shown.png)\n",
+    )?;
+
+    // Only docs is linted, but the README outside it still shows an image.
+    Command::cargo_bin("docula")?
+        .current_dir(tmp.path().join("docs"))
+        .args(["markdown", "lint", "-r", ".", "--unused-assets"])
+        .assert()
+        .stdout("img/old.png:1:1: warning[unused-assets]: Not referenced by any Markdown file\n");
+
+    tmp.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn test_markdown_lint_references() -> Result<(), Box<dyn Error>> {
+    let tmp = tempdir::TempDir::new("lint_test")?;
+
+    std::fs::write(tmp.path().join("setup.md"), "# Setup\n")?;
+    std::fs::write(
+        tmp.path().join("a.md"),
+        "# A\n\nSee [setup], [usage][] and [faq][FAQ].\n\n\
+         [setup]: setup.md\n[usage]: usage.md\n[Setup]: other.md\n[old]: old.md\n",
+    )?;
+
+    let output = Command::cargo_bin("docula")?
+        .current_dir(tmp.path())
+        .args(["markdown", "lint", "a.md"])
+        .output()?;
+
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!(
+        "a.md:3:14: error[broken-links]: Link target `usage.md` does not exist\n    \
+         See [setup], [usage][] and [faq][FAQ].\n\
+         a.md:3:28: error[undefined-references]: No definition for reference `FAQ`\n    \
+         See [setup], [usage][] and [faq][FAQ].\n\
+         a.md:7:1: error[duplicate-definitions]: `Setup` is already defined on line 5\n    \
+         [Setup]: other.md\n\
+         a.md:8:1: warning[unused-definitions]: Definition `old` is not used\n    \
+         [old]: old.md\n",
+        String::from_utf8(output.stdout)?
+    );
+
+    tmp.close()?;
+
+    Ok(())
+}